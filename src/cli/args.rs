@@ -0,0 +1,808 @@
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+use super::config::{parse_hex, parse_iv, parse_key};
+use crate::core::io::compression::DEFAULT_MAX_DECOMPRESSED_SIZE;
+
+#[derive(Parser, Debug)]
+#[command(name = "cryptocore", version, about = "Cryptographic tool for block cipher operations")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Commands,
+
+    #[command(flatten)]
+    pub rng: RngArgs,
+}
+
+/// Where keys, IVs, nonces and salts come from; accepted by every subcommand.
+#[derive(Args, Debug, Default)]
+pub struct RngArgs {
+    /// Random bit generator (default: system, or ctr-drbg with any option below)
+    #[arg(long, global = true, value_name = "RNG", value_parser = ["system", "ctr-drbg"])]
+    pub rng: Option<String>,
+
+    /// Seed the CTR_DRBG with this hex entropy (at least 32 bytes) instead of
+    /// the system; output is reproducible, so use it for tests only
+    #[arg(long, global = true, hide = true, value_name = "HEX", value_parser = parse_hex)]
+    pub rng_seed: Option<::std::vec::Vec<u8>>,
+
+    /// Personalization string mixed into the CTR_DRBG
+    #[arg(long, global = true, value_name = "TEXT")]
+    pub rng_personalization: Option<String>,
+
+    /// Reseed the CTR_DRBG from the system before every request
+    #[arg(long, global = true)]
+    pub prediction_resistance: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Commands {
+    /// Encrypt a file
+    Enc(CryptArgs),
+    /// Decrypt a file
+    Dec(CryptArgs),
+    /// Generate a random key
+    Keygen(KeygenArgs),
+    /// Print random bytes from the configured generator
+    Rand(RandArgs),
+    /// Compute or check file digests
+    Dgst(DgstArgs),
+    /// Compute or verify a message authentication code
+    Mac(MacArgs),
+    /// Sign a file with an Ed25519 or ECDSA P-256 private key
+    Sign(SignArgs),
+    /// Check a signature made with `sign` (or by openssl)
+    Verify(VerifyArgs),
+    /// Derive a key from a master key
+    Kdf(KdfArgs),
+    /// Wrap a key under a key-encryption key (RFC 3394 / RFC 5649)
+    Wrap(WrapArgs),
+    /// Unwrap a key wrapped with `wrap`
+    Unwrap(WrapArgs),
+    /// Create or read Fernet tokens (compatible with Python's cryptography.fernet)
+    Fernet(FernetArgs),
+    /// Create or read JSON Web Encryption tokens (RFC 7516 compact serialization)
+    Jwe(JweArgs),
+    /// Encrypt or decrypt OpenPGP messages with a passphrase (compatible with `gpg -c`)
+    Pgp(PgpArgs),
+    /// Encrypt or decrypt age v1 files (compatible with `age` and `rage`)
+    Age(AgeArgs),
+    /// Encrypt or decrypt large files as independently authenticated chunks
+    Stream(StreamArgs),
+    /// Tokenize CSV columns with format-preserving encryption (FF1 or FF3-1)
+    Fpe(FpeArgs),
+    /// Bundle a directory into a single encrypted archive
+    Pack(PackArgs),
+    /// Extract an archive created with `pack`
+    Unpack(UnpackArgs),
+    /// Re-encrypt files under a new key and/or mode, replacing them atomically
+    Reencrypt(ReencryptArgs),
+    /// Re-wrap the data keys of envelope-encrypted files under a new master key
+    Rewrap(RewrapArgs),
+    /// Show what can be learned about an encrypted file without the key
+    Inspect(InspectArgs),
+    /// Measure encryption and decryption throughput
+    Bench(BenchArgs),
+    /// Run the built-in known-answer tests
+    Selftest,
+}
+
+#[derive(Args, Debug)]
+pub struct CryptArgs {
+    /// Cryptographic algorithm
+    #[arg(long, value_name = "ALGORITHM", default_value = "aes", value_parser = ["aes"])]
+    pub algorithm: String,
+
+    /// Mode of operation
+    #[arg(long, value_name = "MODE", value_parser = ["ecb", "cbc", "cfb", "ofb", "ctr", "gcm", "ocb"])]
+    pub mode: String,
+
+    /// Key as hexadecimal string (e.g., 00112233445566778899aabbccddeeff)
+    #[arg(long, value_name = "KEY", value_parser = parse_key, required_unless_present_any = ["pass", "recipient", "private_key"])]
+    pub key: Option<::std::vec::Vec<u8>>,
+
+    /// Initialization vector as hexadecimal string (for decryption only)
+    #[arg(long, value_name = "IV", value_parser = parse_iv)]
+    pub iv: Option<::std::vec::Vec<u8>>,
+
+    /// File format: cryptocore's own, or that of `openssl enc` with a password
+    #[arg(long, value_name = "FORMAT", default_value = "cryptocore", value_parser = ["cryptocore", "openssl"])]
+    pub format: String,
+
+    /// Password to derive the key and IV from (--format openssl only)
+    #[arg(long, value_name = "PASSWORD", conflicts_with = "key")]
+    pub pass: Option<String>,
+
+    /// Derive the key with PBKDF2, like `openssl enc -pbkdf2` (--format openssl only)
+    #[arg(long)]
+    pub pbkdf2: bool,
+
+    /// PBKDF2 iteration count, like `openssl enc -iter`; implies --pbkdf2
+    #[arg(long, value_name = "COUNT")]
+    pub iter: Option<u32>,
+
+    /// Digest for the OpenSSL key derivation (md5 for files from OpenSSL before 1.1.0)
+    #[arg(long, value_name = "DIGEST", default_value = "sha256", value_parser = ["md5", "sha256"])]
+    pub md: String,
+
+    /// AES key size for --format openssl, as in aes-256-cbc
+    #[arg(long, value_name = "BITS", default_value = "256", value_parser = ["128", "192", "256"])]
+    pub key_bits: String,
+
+    /// Encrypt under an HKDF-SHA256 subkey bound to this context (stored in the file header)
+    #[arg(long, value_name = "CONTEXT")]
+    pub derive_info: Option<String>,
+
+    /// Encrypt with a random per-file data key, stored wrapped under --key
+    #[arg(long)]
+    pub envelope: bool,
+
+    /// Encrypt to the holder of this public key (PEM, X25519 or RSA); may be repeated
+    #[arg(long, value_name = "PUBLIC_KEY", conflicts_with_all = ["key", "pass", "envelope", "derive_info"])]
+    pub recipient: Vec<PathBuf>,
+
+    /// Decrypt with this private key (PEM) instead of --key; may be repeated
+    #[arg(long, value_name = "PRIVATE_KEY", conflicts_with_all = ["key", "pass"])]
+    pub private_key: Vec<PathBuf>,
+
+    /// Sign the plaintext with this private key (PEM, Ed25519 or P-256), then encrypt it
+    #[arg(long, value_name = "PRIVATE_KEY")]
+    pub sign_with: Option<PathBuf>,
+
+    /// Check and remove the signature added by --sign-with, using this public key
+    #[arg(long, value_name = "PUBLIC_KEY")]
+    pub verify_with: Option<PathBuf>,
+
+    /// Compress the plaintext before encryption; see the README before enabling
+    /// it for data that mixes secrets with attacker-controlled input
+    #[arg(long, value_name = "ALGORITHM", default_value = "none", value_parser = ["zstd", "gzip", "none"])]
+    pub compress: String,
+
+    /// Largest decompressed size in bytes to accept when decrypting a compressed file
+    #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_MAX_DECOMPRESSED_SIZE)]
+    pub max_decompressed_size: u64,
+
+    /// Write the output as a base64 `-----BEGIN CRYPTOCORE MESSAGE-----` block
+    #[arg(long)]
+    pub armor: bool,
+
+    /// Process a whole directory tree into a mirror tree with an encrypted manifest
+    #[arg(short, long)]
+    pub recursive: bool,
+
+    /// With --recursive, also encrypt files that already look encrypted instead of copying them
+    #[arg(long, requires = "recursive")]
+    pub include_encrypted: bool,
+
+    /// Input file path (a directory with --recursive)
+    #[arg(short, long, value_name = "INPUT_FILE")]
+    pub input: PathBuf,
+
+    /// Output file path, or directory with --recursive (derived from the input path if omitted)
+    #[arg(short, long, value_name = "OUTPUT_FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct KeygenArgs {
+    /// Key length in bytes
+    #[arg(long, value_name = "BYTES", default_value_t = 16, conflicts_with = "algorithm")]
+    pub length: usize,
+
+    /// Generate a key pair instead of a symmetric key: x25519 or rsa for
+    /// --recipient, ed25519 or p256 for signing
+    #[arg(long, value_name = "ALGORITHM", value_parser = ["x25519", "rsa", "ed25519", "p256"])]
+    pub algorithm: Option<String>,
+
+    /// RSA modulus size in bits
+    #[arg(long, value_name = "BITS", default_value_t = 3072)]
+    pub bits: usize,
+
+    /// Write the hex-encoded key (or the PEM private key) to a file instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// With --algorithm, write the PEM public key to a file instead of stdout
+    #[arg(long, value_name = "FILE", requires = "algorithm")]
+    pub public_out: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct RandArgs {
+    /// Number of random bytes
+    #[arg(value_name = "BYTES")]
+    pub length: usize,
+
+    /// Encode as base64 instead of hex
+    #[arg(long, conflicts_with = "raw")]
+    pub base64: bool,
+
+    /// Write the bytes unencoded
+    #[arg(long)]
+    pub raw: bool,
+
+    /// Write to a file instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct DgstArgs {
+    /// Hash algorithm
+    #[arg(
+        short,
+        long,
+        value_name = "ALGORITHM",
+        default_value = "sha256",
+        value_parser = ["sha256", "sha384", "sha512", "sha3-256", "sha3-512", "blake2b", "blake3"]
+    )]
+    pub algorithm: String,
+
+    /// Files to hash ("-" reads standard input)
+    #[arg(value_name = "FILE", required_unless_present = "check")]
+    pub files: Vec<PathBuf>,
+
+    /// Write the digests to a checksum manifest instead of stdout
+    #[arg(short, long, value_name = "MANIFEST")]
+    pub output: Option<PathBuf>,
+
+    /// Verify the files listed in a sha256sum-style manifest
+    #[arg(short, long, value_name = "MANIFEST", conflicts_with_all = ["files", "output"])]
+    pub check: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct MacArgs {
+    /// MAC algorithm
+    #[arg(
+        short,
+        long,
+        value_name = "ALGORITHM",
+        default_value = "hmac-sha256",
+        value_parser = ["hmac-sha256", "hmac-sha384", "hmac-sha512", "aes-cmac"]
+    )]
+    pub algorithm: String,
+
+    /// MAC key as hexadecimal string (16 bytes for aes-cmac)
+    #[arg(long, value_name = "KEY", value_parser = parse_hex)]
+    pub key: ::std::vec::Vec<u8>,
+
+    /// Expected tag as hexadecimal string; the command fails if it does not match
+    #[arg(long, value_name = "TAG", value_parser = parse_hex)]
+    pub verify: Option<::std::vec::Vec<u8>>,
+
+    /// File to authenticate ("-" reads standard input)
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct SignArgs {
+    /// Private key (PEM, Ed25519 or P-256)
+    #[arg(long, value_name = "PRIVATE_KEY")]
+    pub key: PathBuf,
+
+    /// Write one signed file holding the data, instead of a detached signature
+    #[arg(long)]
+    pub embed: bool,
+
+    /// File to sign ("-" reads standard input)
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Output file (default: FILE.sig, or FILE.signed with --embed)
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifyArgs {
+    /// Public key (PEM, Ed25519 or P-256)
+    #[arg(long, value_name = "PUBLIC_KEY")]
+    pub key: PathBuf,
+
+    /// Detached signature; without it, FILE must be a signed file from `sign --embed`
+    #[arg(long, value_name = "SIGNATURE")]
+    pub signature: Option<PathBuf>,
+
+    /// File to check ("-" reads standard input)
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Write the data of a signed file here once the signature checks out
+    #[arg(short, long, value_name = "FILE", conflicts_with = "signature")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct KdfArgs {
+    /// Key derivation function
+    #[arg(
+        short,
+        long,
+        value_name = "ALGORITHM",
+        default_value = "hkdf-sha256",
+        value_parser = ["hkdf-sha256", "hkdf-sha512"]
+    )]
+    pub algorithm: String,
+
+    /// Master key as hexadecimal string
+    #[arg(long, value_name = "KEY", value_parser = parse_hex)]
+    pub key: ::std::vec::Vec<u8>,
+
+    /// Optional salt as hexadecimal string
+    #[arg(long, value_name = "SALT", value_parser = parse_hex)]
+    pub salt: Option<::std::vec::Vec<u8>>,
+
+    /// Context string binding the derived key to its purpose (e.g. a tenant id)
+    #[arg(long, value_name = "CONTEXT", default_value = "")]
+    pub info: String,
+
+    /// Length of the derived key in bytes
+    #[arg(long, value_name = "BYTES", default_value_t = 16)]
+    pub length: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct WrapArgs {
+    /// Key-encryption key as hexadecimal string (16, 24 or 32 bytes)
+    #[arg(long, value_name = "KEK", value_parser = parse_hex)]
+    pub kek: ::std::vec::Vec<u8>,
+
+    /// Use key wrap with padding (RFC 5649), which accepts keys of any length
+    #[arg(long)]
+    pub padding: bool,
+
+    /// Key (for wrap) or wrapped key (for unwrap) as hexadecimal string
+    #[arg(value_name = "HEX", value_parser = parse_hex)]
+    pub data: ::std::vec::Vec<u8>,
+}
+
+#[derive(Args, Debug)]
+pub struct RewrapArgs {
+    /// Current master key as hexadecimal string
+    #[arg(long, value_name = "KEY", value_parser = parse_key)]
+    pub old_key: ::std::vec::Vec<u8>,
+
+    /// New master key as hexadecimal string
+    #[arg(long, value_name = "KEY", value_parser = parse_key)]
+    pub new_key: ::std::vec::Vec<u8>,
+
+    /// Envelope-encrypted files to update in place
+    #[arg(value_name = "FILE", required = true)]
+    pub files: Vec<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct FernetArgs {
+    #[command(subcommand)]
+    pub command: FernetCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum FernetCommand {
+    /// Generate a base64url Fernet key
+    Keygen,
+    /// Encrypt a file into a token
+    Encrypt(FernetEncryptArgs),
+    /// Decrypt a token
+    Decrypt(FernetDecryptArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct FernetEncryptArgs {
+    /// Fernet key as base64url string
+    #[arg(long, value_name = "KEY")]
+    pub key: String,
+
+    /// File to encrypt ("-" reads standard input)
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Write the token to a file instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct FernetDecryptArgs {
+    /// Fernet key as base64url string
+    #[arg(long, value_name = "KEY")]
+    pub key: String,
+
+    /// Reject tokens created more than this many seconds ago
+    #[arg(long, value_name = "SECONDS")]
+    pub ttl: Option<u64>,
+
+    /// File holding the token ("-" reads standard input)
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Write the plaintext to a file instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct JweArgs {
+    #[command(subcommand)]
+    pub command: JweCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum JweCommand {
+    /// Encrypt a file into a compact token
+    Encrypt(JweEncryptArgs),
+    /// Decrypt a compact token
+    Decrypt(JweDecryptArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct JweEncryptArgs {
+    /// Shared key as hexadecimal string (the key-encryption key, or with "dir" the content key)
+    #[arg(long, value_name = "KEY", value_parser = parse_hex)]
+    pub key: ::std::vec::Vec<u8>,
+
+    /// Key management algorithm
+    #[arg(long, value_name = "ALG", default_value = "A256KW", value_parser = ["dir", "A128KW", "A256KW"])]
+    pub alg: String,
+
+    /// Content encryption algorithm
+    #[arg(long, value_name = "ENC", default_value = "A256GCM", value_parser = ["A128CBC-HS256", "A128GCM", "A192GCM", "A256GCM"])]
+    pub enc: String,
+
+    /// Key ID to put in the header
+    #[arg(long, value_name = "KID")]
+    pub kid: Option<String>,
+
+    /// File to encrypt ("-" reads standard input)
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Write the token to a file instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct JweDecryptArgs {
+    /// Shared key as hexadecimal string
+    #[arg(long, value_name = "KEY", value_parser = parse_hex)]
+    pub key: ::std::vec::Vec<u8>,
+
+    /// Reject tokens whose header names another key management algorithm
+    #[arg(long, value_name = "ALG", value_parser = ["dir", "A128KW", "A256KW"])]
+    pub alg: Option<String>,
+
+    /// Reject tokens whose header names another content encryption algorithm
+    #[arg(long, value_name = "ENC", value_parser = ["A128CBC-HS256", "A128GCM", "A192GCM", "A256GCM"])]
+    pub enc: Option<String>,
+
+    /// File holding the token ("-" reads standard input)
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Write the plaintext to a file instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct PgpArgs {
+    #[command(subcommand)]
+    pub command: PgpCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PgpCommand {
+    /// Encrypt a file into an OpenPGP message
+    Encrypt(PgpEncryptArgs),
+    /// Decrypt an OpenPGP message (binary or armored)
+    Decrypt(PgpDecryptArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct PgpEncryptArgs {
+    /// Passphrase
+    #[arg(long, value_name = "PASSWORD")]
+    pub pass: String,
+
+    /// AES key size in bits
+    #[arg(long, value_name = "BITS", default_value = "256", value_parser = ["128", "192", "256"])]
+    pub key_bits: String,
+
+    /// Write an RFC 9580 AEAD packet (SEIPD v2) instead of the CFB packet gpg 2.2 reads
+    #[arg(long, value_name = "MODE", value_parser = ["gcm"])]
+    pub aead: Option<String>,
+
+    /// Bytes hashed when deriving the key from the passphrase
+    #[arg(long, value_name = "COUNT", default_value_t = 65_011_712)]
+    pub s2k_count: u32,
+
+    /// Write an ASCII-armored message ("-----BEGIN PGP MESSAGE-----")
+    #[arg(short, long)]
+    pub armor: bool,
+
+    /// File to encrypt ("-" reads standard input)
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Output file (default: FILE.gpg, or FILE.asc with --armor)
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct PgpDecryptArgs {
+    /// Passphrase
+    #[arg(long, value_name = "PASSWORD")]
+    pub pass: String,
+
+    /// Also decrypt legacy messages without integrity protection
+    #[arg(long)]
+    pub allow_unprotected: bool,
+
+    /// File holding the message ("-" reads standard input)
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Write the plaintext to a file instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct AgeArgs {
+    #[command(subcommand)]
+    pub command: AgeCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AgeCommand {
+    /// Generate an X25519 identity, printing its "age1..." recipient
+    Keygen(AgeKeygenArgs),
+    /// Encrypt a file to recipients or with a passphrase
+    Encrypt(AgeEncryptArgs),
+    /// Decrypt a file with identities or a passphrase
+    Decrypt(AgeDecryptArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct AgeKeygenArgs {
+    /// Write the identity to a file instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct AgeEncryptArgs {
+    /// Recipient public key ("age1..."); may be repeated
+    #[arg(short, long, value_name = "RECIPIENT", required_unless_present = "pass", conflicts_with = "pass")]
+    pub recipient: Vec<String>,
+
+    /// Passphrase, instead of recipients
+    #[arg(long, value_name = "PASSWORD")]
+    pub pass: Option<String>,
+
+    /// scrypt work factor (log2 of N) for --pass
+    #[arg(long, value_name = "LOG_N", default_value_t = 18, requires = "pass")]
+    pub work_factor: u8,
+
+    /// File to encrypt ("-" reads standard input)
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Output file (default: FILE.age)
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct AgeDecryptArgs {
+    /// Identity file with "AGE-SECRET-KEY-1..." lines; may be repeated
+    #[arg(short, long, value_name = "FILE", required_unless_present = "pass", conflicts_with = "pass")]
+    pub identity: Vec<PathBuf>,
+
+    /// Passphrase, instead of identities
+    #[arg(long, value_name = "PASSWORD")]
+    pub pass: Option<String>,
+
+    /// Largest scrypt work factor to accept with --pass
+    #[arg(long, value_name = "LOG_N", default_value_t = 22)]
+    pub max_work_factor: u8,
+
+    /// File to decrypt ("-" reads standard input)
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Write the plaintext to a file instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct StreamArgs {
+    #[command(subcommand)]
+    pub command: StreamCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum StreamCommand {
+    /// Encrypt a file chunk by chunk
+    Encrypt(StreamEncryptArgs),
+    /// Decrypt and verify a file chunk by chunk
+    Decrypt(StreamDecryptArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct StreamEncryptArgs {
+    /// AES key (16, 24 or 32 bytes) as hexadecimal string
+    #[arg(long, value_name = "KEY", value_parser = parse_hex)]
+    pub key: ::std::vec::Vec<u8>,
+
+    /// AEAD sealing each chunk
+    #[arg(long, value_name = "MODE", default_value = "gcm", value_parser = ["gcm", "ocb"])]
+    pub mode: String,
+
+    /// Plaintext bytes per chunk, in KiB
+    #[arg(long, value_name = "KIB", default_value_t = 64)]
+    pub chunk_size: usize,
+
+    /// File to encrypt ("-" reads standard input)
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Output file (default: FILE.cstream)
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct StreamDecryptArgs {
+    /// AES key as hexadecimal string
+    #[arg(long, value_name = "KEY", value_parser = parse_hex)]
+    pub key: ::std::vec::Vec<u8>,
+
+    /// File to decrypt ("-" reads standard input)
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Write the plaintext to a file instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct FpeArgs {
+    #[command(subcommand)]
+    pub command: FpeCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum FpeCommand {
+    /// Replace the values of CSV columns with same-format tokens
+    Encrypt(FpeCsvArgs),
+    /// Restore CSV columns tokenized with `fpe encrypt`
+    Decrypt(FpeCsvArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct FpeCsvArgs {
+    /// AES key (16, 24 or 32 bytes) as hexadecimal string
+    #[arg(long, value_name = "KEY", value_parser = parse_hex)]
+    pub key: ::std::vec::Vec<u8>,
+
+    /// Format-preserving cipher
+    #[arg(long, value_name = "ALGORITHM", default_value = "ff1", value_parser = ["ff1", "ff3-1"])]
+    pub algorithm: String,
+
+    /// Tweak as hexadecimal string (any length for FF1, 7 bytes for FF3-1)
+    #[arg(long, value_name = "HEX", value_parser = parse_hex)]
+    pub tweak: Option<::std::vec::Vec<u8>>,
+
+    /// Symbols of the values, in numeral order (default: the digits 0-9)
+    #[arg(long, value_name = "SYMBOLS", conflicts_with = "radix")]
+    pub alphabet: Option<String>,
+
+    /// Use the first RADIX symbols of 0-9a-z as the alphabet
+    #[arg(long, value_name = "RADIX")]
+    pub radix: Option<u32>,
+
+    /// Columns to process, by header name or 1-based index
+    #[arg(long, value_name = "COLUMN", value_delimiter = ',', required = true)]
+    pub columns: Vec<String>,
+
+    /// The first row is data, not a header
+    #[arg(long)]
+    pub no_header: bool,
+
+    /// Field delimiter
+    #[arg(long, value_name = "CHAR", default_value_t = ',')]
+    pub delimiter: char,
+
+    /// CSV file ("-" reads standard input)
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Write the result to a file instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct PackArgs {
+    /// Mode of operation
+    #[arg(long, value_name = "MODE", default_value = "ctr", value_parser = ["cbc", "cfb", "ofb", "ctr"])]
+    pub mode: String,
+
+    /// Key as hexadecimal string
+    #[arg(long, value_name = "KEY", value_parser = parse_key)]
+    pub key: ::std::vec::Vec<u8>,
+
+    /// Directory to pack
+    #[arg(value_name = "DIR")]
+    pub dir: PathBuf,
+
+    /// Archive path (`<DIR>.cca` if omitted)
+    #[arg(short, long, value_name = "ARCHIVE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct UnpackArgs {
+    /// Mode the archive was packed with
+    #[arg(long, value_name = "MODE", default_value = "ctr", value_parser = ["cbc", "cfb", "ofb", "ctr"])]
+    pub mode: String,
+
+    /// Key as hexadecimal string
+    #[arg(long, value_name = "KEY", value_parser = parse_key)]
+    pub key: ::std::vec::Vec<u8>,
+
+    /// Archive to extract
+    #[arg(value_name = "ARCHIVE")]
+    pub archive: PathBuf,
+
+    /// Directory to extract into (the archive name without extension if omitted)
+    #[arg(short, long, value_name = "DIR")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct ReencryptArgs {
+    /// Key the files are currently encrypted under, as hexadecimal string
+    #[arg(long, value_name = "KEY", value_parser = parse_key)]
+    pub old_key: ::std::vec::Vec<u8>,
+
+    /// Key to encrypt the files under, as hexadecimal string
+    #[arg(long, value_name = "KEY", value_parser = parse_key)]
+    pub new_key: ::std::vec::Vec<u8>,
+
+    /// Mode the files are currently encrypted with
+    #[arg(long, value_name = "MODE", value_parser = ["ecb", "cbc", "cfb", "ofb", "ctr", "gcm", "ocb"])]
+    pub old_mode: String,
+
+    /// Mode to encrypt the files with (keeps the current mode if omitted)
+    #[arg(long, value_name = "MODE", value_parser = ["ecb", "cbc", "cfb", "ofb", "ctr", "gcm", "ocb"])]
+    pub mode: Option<String>,
+
+    /// Files to re-encrypt in place; directories are processed recursively
+    #[arg(value_name = "PATH", required = true)]
+    pub paths: Vec<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct InspectArgs {
+    /// File to inspect
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct BenchArgs {
+    /// Mode to benchmark (all modes if omitted)
+    #[arg(long, value_name = "MODE", value_parser = ["ecb", "cbc", "cfb", "ofb", "ctr", "gcm", "ocb"])]
+    pub mode: Option<String>,
+
+    /// Amount of data to process per run, in MiB
+    #[arg(long, value_name = "MIB", default_value_t = 16)]
+    pub size: usize,
+}
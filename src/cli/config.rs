@@ -1,6 +1,15 @@
-use clap::{Arg, ArgAction, Command};
+use clap::{Arg, ArgAction, Command, Parser};
+use std::ffi::OsString;
 use std::path::PathBuf;
 use crate::Operation;
+use crate::core::crypto::asymmetric::{PrivateKey, PublicKey};
+use crate::core::crypto::drbg::CtrDrbg;
+use crate::core::crypto::random;
+use crate::core::io;
+use crate::core::io::compression::{self, CompressionAlgorithm};
+use crate::core::io::openssl::{self, OpensslDigest, OpensslKdf};
+use crate::error::CryptoCoreError;
+use super::args::{Cli, Commands, CryptArgs, RngArgs};
 
 #[derive(Debug)]
 pub struct CliConfig {
@@ -9,11 +18,247 @@ pub struct CliConfig {
     pub operation: Operation,
     pub key: Vec<u8>,
     pub iv: Option<Vec<u8>>,
+    pub derive_info: Option<String>,
+    pub envelope: bool,
+    /// Public keys to encrypt the data key to, instead of using --key
+    pub recipients: Vec<PublicKey>,
+    pub private_keys: Vec<PrivateKey>,
+    /// Key to sign the plaintext with before it is encrypted
+    pub signing_key: Option<PrivateKey>,
+    /// Key to check the embedded signature of the plaintext with after decryption
+    pub verify_key: Option<PublicKey>,
+    pub compression: Option<CompressionAlgorithm>,
+    pub max_decompressed_size: u64,
+    /// Set for `--format openssl`, whose key and IV come from a password
+    pub openssl: Option<OpensslOptions>,
+    pub armor: bool,
+    pub recursive: bool,
+    pub include_encrypted: bool,
     pub input_file: PathBuf,
     pub output_file: Option<PathBuf>,
 }
 
-pub fn parse_args() -> Result<CliConfig, Box<dyn std::error::Error>> {
+#[derive(Debug)]
+pub struct OpensslOptions {
+    pub password: String,
+    pub kdf: OpensslKdf,
+    pub key_size: usize,
+}
+
+impl CryptArgs {
+    pub fn into_config(self, operation: Operation) -> crate::Result<CliConfig> {
+        // Validate IV usage
+        if self.iv.is_some() && operation == Operation::Encrypt {
+            eprintln!("Warning: --iv is ignored during encryption. Using randomly generated IV.");
+        }
+        if self.derive_info.is_some() && operation == Operation::Decrypt {
+            eprintln!("Warning: --derive-info is ignored during decryption. The context is read from the file header.");
+        }
+        if self.envelope && operation == Operation::Decrypt {
+            eprintln!("Warning: --envelope is ignored during decryption. The wrapped key is read from the file header.");
+        }
+        if !self.recipient.is_empty() && operation == Operation::Decrypt {
+            return Err(CryptoCoreError::ConfigError(
+                "--recipient is for encryption; decrypt with --private-key".to_string(),
+            ));
+        }
+        if !self.private_key.is_empty() && operation == Operation::Encrypt {
+            return Err(CryptoCoreError::ConfigError(
+                "--private-key is for decryption; encrypt with --recipient".to_string(),
+            ));
+        }
+        if self.sign_with.is_some() && operation == Operation::Decrypt {
+            return Err(CryptoCoreError::ConfigError(
+                "--sign-with is for encryption; check the signature with --verify-with".to_string(),
+            ));
+        }
+        if self.verify_with.is_some() && operation == Operation::Encrypt {
+            return Err(CryptoCoreError::ConfigError(
+                "--verify-with is for decryption; sign with --sign-with".to_string(),
+            ));
+        }
+
+        // `none` is the opt-out for data where the compressed size could leak plaintext
+        let compression = match self.compress.as_str() {
+            "none" => None,
+            name => Some(CompressionAlgorithm::from_name(name)?),
+        };
+        if compression.is_some() && operation == Operation::Encrypt {
+            eprintln!(
+                "Warning: compression makes the output size depend on the content. Use --compress none \
+                 for data that mixes secrets with attacker-controlled input."
+            );
+        }
+        if self.armor && operation == Operation::Decrypt {
+            eprintln!("Warning: --armor is ignored during decryption. Armored input is detected automatically.");
+        }
+        if compression.is_some() && operation == Operation::Decrypt {
+            eprintln!("Warning: --compress is ignored during decryption. The algorithm is read from the file header.");
+        }
+
+        let openssl = if self.format == "openssl" {
+            let password = self.pass.ok_or_else(|| {
+                CryptoCoreError::ConfigError("--format openssl needs --pass".to_string())
+            })?;
+            if self.mode == "gcm" || self.mode == "ocb" {
+                return Err(CryptoCoreError::ConfigError(
+                    "openssl enc does not support GCM or OCB; use cbc, cfb, ofb, ctr or ecb".to_string(),
+                ));
+            }
+            if self.derive_info.is_some()
+                || self.envelope
+                || compression.is_some()
+                || !self.recipient.is_empty()
+                || self.sign_with.is_some()
+                || self.verify_with.is_some()
+            {
+                return Err(CryptoCoreError::ConfigError(
+                    "--derive-info, --envelope, --compress, --recipient and signing need the cryptocore format"
+                        .to_string(),
+                ));
+            }
+
+            let digest = OpensslDigest::from_name(&self.md)?;
+            Some(OpensslOptions {
+                password,
+                // Like `openssl enc`, an iteration count switches to PBKDF2
+                kdf: match self.iter {
+                    Some(iterations) => OpensslKdf::Pbkdf2 { digest, iterations },
+                    None if self.pbkdf2 => OpensslKdf::Pbkdf2 { digest, iterations: openssl::DEFAULT_ITERATIONS },
+                    None => OpensslKdf::BytesToKey(digest),
+                },
+                key_size: self.key_bits.parse::<usize>().unwrap_or(256) / 8,
+            })
+        } else if self.pass.is_some() {
+            return Err(CryptoCoreError::ConfigError(
+                "--pass is only supported with --format openssl".to_string(),
+            ));
+        } else {
+            None
+        };
+
+        let recipients: Vec<PublicKey> = self
+            .recipient
+            .iter()
+            .map(|path| PublicKey::from_pem(&read_pem(path)?))
+            .collect::<crate::Result<_>>()?;
+        if let Some(recipient) = recipients.iter().find(|recipient| recipient.algorithm().can_sign()) {
+            return Err(CryptoCoreError::ConfigError(format!(
+                "--recipient needs an X25519 or RSA public key; {} keys can only sign",
+                recipient.algorithm()
+            )));
+        }
+        let private_keys = self
+            .private_key
+            .iter()
+            .map(|path| PrivateKey::from_pem(&read_pem(path)?))
+            .collect::<crate::Result<_>>()?;
+
+        let signing_key = self.sign_with.as_deref().map(|path| PrivateKey::from_pem(&read_pem(path)?)).transpose()?;
+        let verify_key = self.verify_with.as_deref().map(|path| PublicKey::from_pem(&read_pem(path)?)).transpose()?;
+        let signing_algorithms = signing_key.iter().map(PrivateKey::algorithm).chain(verify_key.iter().map(PublicKey::algorithm));
+        for algorithm in signing_algorithms {
+            if !algorithm.can_sign() {
+                return Err(CryptoCoreError::ConfigError(format!(
+                    "--sign-with and --verify-with need an Ed25519 or P-256 key, not {}",
+                    algorithm
+                )));
+            }
+        }
+
+        Ok(CliConfig {
+            algorithm: self.algorithm,
+            mode: self.mode,
+            operation,
+            key: self.key.unwrap_or_default(),
+            iv: self.iv,
+            derive_info: self.derive_info,
+            envelope: self.envelope,
+            recipients,
+            private_keys,
+            signing_key,
+            verify_key,
+            compression,
+            max_decompressed_size: self.max_decompressed_size,
+            openssl,
+            armor: self.armor,
+            recursive: self.recursive,
+            include_encrypted: self.include_encrypted,
+            input_file: self.input,
+            output_file: self.output,
+        })
+    }
+}
+
+fn read_pem(path: &std::path::Path) -> crate::Result<String> {
+    String::from_utf8(io::read_file(path)?)
+        .map_err(|_| CryptoCoreError::InvalidKey(format!("{} is not a PEM file", path.display())))
+}
+
+/// The CTR_DRBG runs AES-256
+const DRBG_KEY_SIZE: usize = 32;
+
+impl RngArgs {
+    /// Installs the chosen generator as the process-wide random source.
+    pub fn install(&self) -> crate::Result<()> {
+        let drbg_options = self.rng_seed.is_some() || self.rng_personalization.is_some() || self.prediction_resistance;
+        match self.rng.as_deref() {
+            Some("system") if drbg_options => Err(CryptoCoreError::ConfigError(
+                "--rng-seed, --rng-personalization and --prediction-resistance need --rng ctr-drbg".to_string()
+            )),
+            Some("ctr-drbg") => self.install_ctr_drbg(),
+            None if drbg_options => self.install_ctr_drbg(),
+            _ => {
+                random::use_system();
+                Ok(())
+            }
+        }
+    }
+
+    fn install_ctr_drbg(&self) -> crate::Result<()> {
+        let personalization = self.rng_personalization.as_deref().unwrap_or_default().as_bytes();
+        let mut drbg = match &self.rng_seed {
+            Some(seed) => {
+                eprintln!("Warning: --rng-seed makes every key, IV and nonce predictable. Use it for tests only.");
+                CtrDrbg::instantiate(DRBG_KEY_SIZE, seed, &[], personalization)?
+            }
+            None => CtrDrbg::new(DRBG_KEY_SIZE, personalization)?,
+        };
+        drbg.set_prediction_resistance(self.prediction_resistance)?;
+        random::use_ctr_drbg(drbg);
+        Ok(())
+    }
+}
+
+pub fn parse_args() -> Result<Cli, Box<dyn std::error::Error>> {
+    let args: Vec<OsString> = std::env::args_os().collect();
+
+    if is_legacy_invocation(&args) {
+        eprintln!(
+            "Warning: the flag-style interface (--encrypt/--decrypt) is deprecated; \
+             use `cryptocore enc` or `cryptocore dec` instead."
+        );
+        return parse_legacy_args(args);
+    }
+
+    Ok(Cli::parse_from(args))
+}
+
+// Global options that may come before the subcommand
+const GLOBAL_FLAGS: [&str; 4] = ["--rng", "--rng-seed", "--rng-personalization", "--prediction-resistance"];
+
+// Старый формат вызова начинается сразу с флага, а не с имени подкоманды
+fn is_legacy_invocation(args: &[OsString]) -> bool {
+    match args.get(1).and_then(|arg| arg.to_str()) {
+        Some(first) => {
+            let flag = first.split('=').next().unwrap_or(first);
+            first.starts_with("--") && !matches!(first, "--help" | "--version") && !GLOBAL_FLAGS.contains(&flag)
+        }
+        None => false,
+    }
+}
+
+fn parse_legacy_args(args: Vec<OsString>) -> Result<Cli, Box<dyn std::error::Error>> {
     let matches = Command::new("cryptocore")
         .version("0.1.0")
         .about("Cryptographic tool for block cipher operations")
@@ -77,38 +322,45 @@ pub fn parse_args() -> Result<CliConfig, Box<dyn std::error::Error>> {
                 .value_parser(clap::value_parser!(PathBuf))
                 .help("Output file path (optional)"),
         )
-        .get_matches();
-
-    let operation = if matches.get_flag("encrypt") {
-        Operation::Encrypt
-    } else if matches.get_flag("decrypt") {
-        Operation::Decrypt
-    } else {
-        return Err("Either --encrypt or --decrypt must be specified".into());
-    };
-
-    // Validate IV usage
-    let iv = matches.get_one::<Vec<u8>>("iv").cloned();
-    if let Some(ref _iv_vec) = iv {
-        if operation == Operation::Encrypt {
-            eprintln!("Warning: --iv is ignored during encryption. Using randomly generated IV.");
-        }
-    }
+        .get_matches_from(args);
 
-    let config = CliConfig {
+    let args = CryptArgs {
         algorithm: matches.get_one::<String>("algorithm").unwrap().to_string(),
         mode: matches.get_one::<String>("mode").unwrap().to_string(),
-        operation,
-        key: matches.get_one::<Vec<u8>>("key").unwrap().clone(),
-        iv,
-        input_file: matches.get_one::<PathBuf>("input").unwrap().clone(),
-        output_file: matches.get_one::<PathBuf>("output").cloned(),
+        key: matches.get_one::<Vec<u8>>("key").cloned(),
+        iv: matches.get_one::<Vec<u8>>("iv").cloned(),
+        format: "cryptocore".to_string(),
+        pass: None,
+        pbkdf2: false,
+        iter: None,
+        md: "sha256".to_string(),
+        key_bits: "256".to_string(),
+        derive_info: None,
+        envelope: false,
+        recipient: Vec::new(),
+        private_key: Vec::new(),
+        sign_with: None,
+        verify_with: None,
+        compress: "none".to_string(),
+        max_decompressed_size: compression::DEFAULT_MAX_DECOMPRESSED_SIZE,
+        armor: false,
+        recursive: false,
+        include_encrypted: false,
+        input: matches.get_one::<PathBuf>("input").unwrap().clone(),
+        output: matches.get_one::<PathBuf>("output").cloned(),
     };
 
-    Ok(config)
+    let command = if matches.get_flag("encrypt") {
+        Commands::Enc(args)
+    } else if matches.get_flag("decrypt") {
+        Commands::Dec(args)
+    } else {
+        return Err("Either --encrypt or --decrypt must be specified".into());
+    };
+    Ok(Cli { command, rng: RngArgs::default() })
 }
 
-fn parse_key(s: &str) -> Result<Vec<u8>, String> {
+pub(crate) fn parse_key(s: &str) -> Result<Vec<u8>, String> {
     let key_str = s.trim_start_matches('@');
     
     if key_str.len() != 32 {
@@ -119,13 +371,25 @@ fn parse_key(s: &str) -> Result<Vec<u8>, String> {
         .map_err(|e| format!("Invalid hex string: {}", e))
 }
 
-fn parse_iv(s: &str) -> Result<Vec<u8>, String> {
+pub(crate) fn parse_iv(s: &str) -> Result<Vec<u8>, String> {
     let iv_str = s.trim_start_matches('@');
     
-    if iv_str.len() != 32 {
-        return Err("IV must be 16 bytes (32 hex characters)".into());
+    // 16 bytes for the classic modes, a 12-byte nonce for GCM and OCB
+    if iv_str.len() != 32 && iv_str.len() != 24 {
+        return Err("IV must be 16 bytes (32 hex characters), or 12 bytes (24 hex characters) for GCM and OCB".into());
     }
 
     hex::decode(iv_str)
         .map_err(|e| format!("Invalid hex string: {}", e))
-}
\ No newline at end of file
+}
+
+pub(crate) fn parse_hex(s: &str) -> Result<Vec<u8>, String> {
+    let hex_str = s.trim_start_matches('@');
+
+    if hex_str.is_empty() {
+        return Err("Value must not be empty".into());
+    }
+
+    hex::decode(hex_str)
+        .map_err(|e| format!("Invalid hex string: {}", e))
+}
@@ -1,2 +1,4 @@
+mod args;
 mod config;
-pub use config::{CliConfig, parse_args};
\ No newline at end of file
+pub use args::{AgeArgs, AgeCommand, AgeDecryptArgs, AgeEncryptArgs, AgeKeygenArgs, BenchArgs, Cli, Commands, CryptArgs, DgstArgs, FernetArgs, FernetCommand, FernetDecryptArgs, FernetEncryptArgs, FpeArgs, FpeCommand, FpeCsvArgs, InspectArgs, JweArgs, JweCommand, JweDecryptArgs, JweEncryptArgs, KdfArgs, KeygenArgs, MacArgs, PackArgs, PgpArgs, PgpCommand, PgpDecryptArgs, PgpEncryptArgs, RandArgs, ReencryptArgs, RewrapArgs, RngArgs, SignArgs, StreamArgs, StreamCommand, StreamDecryptArgs, StreamEncryptArgs, UnpackArgs, VerifyArgs, WrapArgs};
+pub use config::{CliConfig, OpensslOptions, parse_args};
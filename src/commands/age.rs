@@ -0,0 +1,79 @@
+use cryptocore::cli::{AgeArgs, AgeCommand, AgeDecryptArgs, AgeEncryptArgs, AgeKeygenArgs};
+use cryptocore::core::crypto::age::{self, Identity, Recipient, X25519Identity, X25519Recipient};
+use cryptocore::core::io;
+use cryptocore::Result;
+use std::path::{Path, PathBuf};
+
+use super::{open_input, write_atomically};
+
+pub fn run(args: AgeArgs) -> Result<()> {
+    match args.command {
+        AgeCommand::Keygen(args) => keygen(args),
+        AgeCommand::Encrypt(args) => encrypt(args),
+        AgeCommand::Decrypt(args) => decrypt(args),
+    }
+}
+
+// The same layout as age-keygen, so the files work with either tool
+fn keygen(args: AgeKeygenArgs) -> Result<()> {
+    let identity = X25519Identity::generate()?;
+    let recipient = identity.recipient();
+    let contents = format!("# public key: {}\n{}\n", recipient, identity);
+
+    match args.output {
+        Some(path) => {
+            io::write_private_file(&path, contents.as_bytes())?;
+            eprintln!("Public key: {}", recipient);
+        }
+        None => print!("{}", contents),
+    }
+    Ok(())
+}
+
+fn encrypt(args: AgeEncryptArgs) -> Result<()> {
+    let recipients = match args.pass {
+        Some(pass) => vec![Recipient::Scrypt { passphrase: pass.into_bytes(), work_factor: args.work_factor }],
+        None => args
+            .recipient
+            .iter()
+            .map(|recipient| X25519Recipient::parse(recipient).map(Recipient::X25519))
+            .collect::<Result<_>>()?,
+    };
+    let reader = open_input(&args.file)?;
+
+    match args.output.or_else(|| default_output(&args.file)) {
+        Some(path) => write_atomically(&path, |writer| age::encrypt(&recipients, reader, writer)),
+        None => age::encrypt(&recipients, reader, std::io::stdout().lock()).map(|_| ()),
+    }
+}
+
+fn decrypt(args: AgeDecryptArgs) -> Result<()> {
+    let identities = match args.pass {
+        Some(pass) => vec![Identity::Scrypt { passphrase: pass.into_bytes(), max_work_factor: args.max_work_factor }],
+        None => {
+            let mut identities = Vec::new();
+            for path in &args.identity {
+                let text = String::from_utf8_lossy(&io::read_file(path)?).into_owned();
+                identities.extend(age::parse_identities(&text)?);
+            }
+            identities
+        }
+    };
+    let reader = open_input(&args.file)?;
+
+    // Chunks reach stdout as they are verified; a file only appears once the
+    // whole payload has checked out
+    match args.output {
+        Some(path) => write_atomically(&path, |writer| age::decrypt(&identities, reader, writer)),
+        None => age::decrypt(&identities, reader, std::io::stdout().lock()).map(|_| ()),
+    }
+}
+
+fn default_output(path: &Path) -> Option<PathBuf> {
+    if path == Path::new("-") {
+        return None;
+    }
+    let mut output = path.as_os_str().to_owned();
+    output.push(".age");
+    Some(PathBuf::from(output))
+}
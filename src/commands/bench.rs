@@ -0,0 +1,40 @@
+use cryptocore::cli::BenchArgs;
+use cryptocore::core::{crypto, io};
+use cryptocore::{CryptoCoreError, Result};
+use std::time::Instant;
+
+const MODES: [&str; 7] = ["ecb", "cbc", "cfb", "ofb", "ctr", "gcm", "ocb"];
+
+pub fn run(args: BenchArgs) -> Result<()> {
+    if args.size == 0 {
+        return Err(CryptoCoreError::InvalidArgument(
+            "Benchmark size must be at least 1 MiB".to_string(),
+        ));
+    }
+
+    let modes: Vec<&str> = match args.mode.as_deref() {
+        Some(mode) => vec![mode],
+        None => MODES.to_vec(),
+    };
+
+    let key = io::generate_key(16)?;
+    let data = vec![0u8; args.size * 1024 * 1024];
+
+    println!("{:<10} {:>14} {:>14}", "mode", "encrypt MiB/s", "decrypt MiB/s");
+    for mode in modes {
+        let iv = io::generate_nonce(crypto::iv_size(mode))?;
+        let cipher = crypto::create_cipher("aes", mode, &key, Some(&iv))?;
+
+        let start = Instant::now();
+        let ciphertext = cipher.encrypt(&data)?;
+        let encrypt_rate = args.size as f64 / start.elapsed().as_secs_f64();
+
+        let start = Instant::now();
+        cipher.decrypt(&ciphertext)?;
+        let decrypt_rate = args.size as f64 / start.elapsed().as_secs_f64();
+
+        println!("{:<10} {:>14.1} {:>14.1}", format!("aes-{}", mode), encrypt_rate, decrypt_rate);
+    }
+
+    Ok(())
+}
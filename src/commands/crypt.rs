@@ -0,0 +1,247 @@
+use cryptocore::{cli, CryptoCoreError, Operation, Result};
+use cryptocore::core::{io, crypto};
+use cryptocore::core::crypto::kdf::KdfAlgorithm;
+use cryptocore::core::io::{armor, compression, openssl, signed};
+use cryptocore::core::io::header::{FileHeader, KeyDerivation};
+use std::path::Path;
+
+use super::tree;
+
+/// Size of the random data key encrypted to `--recipient` public keys
+/// (AES-256). Decryption uses the key at whatever length the header holds,
+/// so files written with 16-byte data keys still open.
+const RECIPIENT_DATA_KEY_SIZE: usize = 32;
+
+/// Ciphertext together with what has to be stored in front of it.
+pub struct Encrypted {
+    pub header: Option<FileHeader>,
+    pub iv: Option<Vec<u8>>,
+    /// Password salt of an OpenSSL-format file (whose IV is derived, not stored)
+    pub salt: Option<Vec<u8>>,
+    pub ciphertext: Vec<u8>,
+    pub armor: bool,
+}
+
+impl Encrypted {
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let data = self.to_bytes()?;
+        if self.armor {
+            io::write_file(path, armor::armor(&data).as_bytes())
+        } else {
+            io::write_file(path, &data)
+        }
+    }
+
+    /// The stored prefix (salt, header or IV) followed by the ciphertext.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let prefix = match (&self.salt, &self.header, &self.iv) {
+            (Some(salt), _, _) => return Ok(openssl::to_bytes(salt, &self.ciphertext)),
+            (None, Some(header), _) => header.to_bytes()?,
+            (None, None, Some(iv)) => iv.clone(),
+            (None, None, None) => Vec::new(),
+        };
+        Ok([prefix, self.ciphertext.clone()].concat())
+    }
+}
+
+pub fn run(config: cli::CliConfig) -> Result<()> {
+    if config.recursive {
+        return tree::run(&config);
+    }
+
+    // Determine output path
+    let output_path = config.output_file.clone()
+        .unwrap_or_else(|| io::derive_output_path(&config.input_file, &config.operation));
+
+    match config.operation {
+        Operation::Encrypt => {
+            let encrypted = encrypt(&config, &io::read_file(&config.input_file)?)?;
+            encrypted.write(&output_path)?;
+
+            println!("Operation completed successfully!");
+            println!("Output: {}", output_path.display());
+
+            // Print IV info for encryption
+            if let Some(salt) = &encrypted.salt {
+                println!("Salt (hex): {}", hex::encode(salt));
+                println!("Note: key and IV are derived from the password and salt, as by openssl enc");
+            } else if let Some(ref iv_ref) = encrypted.iv {  // Используем ref для заимствования
+                println!("IV (hex): {}", hex::encode(iv_ref));
+                if encrypted.header.is_some() {
+                    println!("Note: IV and key parameters are stored in the file header");
+                } else {
+                    println!("Note: IV has been prepended to the output file");
+                }
+            }
+        }
+        Operation::Decrypt => {
+            let (header, input_data) = io::read_file_with_header(&config.input_file)?;
+            let plaintext = decrypt(&config, header, input_data)?;
+            io::write_file(&output_path, &plaintext)?;
+
+            println!("Operation completed successfully!");
+            println!("Output: {}", output_path.display());
+            if config.verify_key.is_none() && signed::is_signed(&plaintext) {
+                println!("Note: the output is a signed file; check and remove the signature with --verify-with");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn encrypt(config: &cli::CliConfig, input_data: &[u8]) -> Result<Encrypted> {
+    let iv_size = crypto::iv_size(&config.mode);
+
+    if let Some(options) = &config.openssl {
+        let salt = io::generate_nonce(openssl::SALT_SIZE)?;
+        let (key, iv) = openssl_key_iv(config, options, &salt)?;
+        let cipher = crypto::create_cipher(&config.algorithm, &config.mode, &key, iv.as_deref())?;
+        return Ok(Encrypted {
+            header: None,
+            iv,
+            salt: Some(salt),
+            ciphertext: cipher.encrypt(input_data)?,
+            armor: config.armor,
+        });
+    }
+
+    // Generate random IV for encryption
+    let iv = (iv_size > 0).then(|| io::generate_nonce(iv_size)).transpose()?;
+    // A header is only written when the file needs more than an IV
+    let mut data_key = None;
+    let header = if config.derive_info.is_some()
+        || config.envelope
+        || config.compression.is_some()
+        || !config.recipients.is_empty()
+    {
+        let mut header = FileHeader {
+            iv: iv.clone(),
+            key_derivation: config.derive_info.as_ref().map(|info| KeyDerivation {
+                algorithm: KdfAlgorithm::HkdfSha256,
+                info: info.as_bytes().to_vec(),
+            }),
+            wrapped_key: None,
+            compression: config.compression,
+            recipients: Vec::new(),
+        };
+        if config.envelope {
+            // Fresh data key per file, so the master key can be rotated with `rewrap`
+            header.wrap_data_key(&config.key, &io::generate_key(config.key.len())?)?;
+        }
+        if !config.recipients.is_empty() {
+            // One data key, encrypted separately to every recipient
+            let key = io::generate_key(RECIPIENT_DATA_KEY_SIZE)?;
+            header.wrap_for_recipients(&config.recipients, &key)?;
+            data_key = Some(key);
+        }
+        Some(header)
+    } else {
+        None
+    };
+
+    let key = match data_key {
+        Some(key) => key,
+        None => content_key(config, &header)?,
+    };
+    let cipher = crypto::create_cipher(&config.algorithm, &config.mode, &key, iv.as_deref())?;
+
+    // Signed before encryption, so the signature does not reveal who wrote the
+    // file, and bound to the recipients, so it cannot be re-encrypted to others
+    let signed_data = match &config.signing_key {
+        Some(key) => Some(signed::sign_bound(key, input_data, &signature_context(&header)?)?),
+        None => None,
+    };
+    let input_data = signed_data.as_deref().unwrap_or(input_data);
+
+    // GCM and OCB authenticate the header too, so it cannot be edited undetected
+    let aad = associated_data(&config.mode, &header)?;
+    let ciphertext = match config.compression {
+        Some(algorithm) => cipher.encrypt_with_aad(&aad, &compression::compress(algorithm, input_data)?)?,
+        None => cipher.encrypt_with_aad(&aad, input_data)?,
+    };
+
+    Ok(Encrypted {
+        ciphertext,
+        header,
+        iv,
+        salt: None,
+        armor: config.armor,
+    })
+}
+
+pub fn decrypt(config: &cli::CliConfig, header: Option<FileHeader>, input_data: Vec<u8>) -> Result<Vec<u8>> {
+    let iv_size = crypto::iv_size(&config.mode);
+
+    if let Some(options) = &config.openssl {
+        let (salt, ciphertext) = openssl::parse(&input_data)?;
+        let (key, iv) = openssl_key_iv(config, options, salt)?;
+        let cipher = crypto::create_cipher(&config.algorithm, &config.mode, &key, iv.as_deref())?;
+        return cipher.decrypt(ciphertext);
+    }
+
+    let (input_data, iv) = match &header {
+        Some(header) => (input_data, header.iv.clone()),
+        // ECB mode doesn't use IV
+        None if iv_size == 0 => (input_data, None),
+        // Use provided IV or read it from the beginning of the file
+        None => match &config.iv {
+            Some(provided_iv) => (input_data, Some(provided_iv.clone())),
+            None => {
+                let (input_data, file_iv) = io::split_iv(input_data, iv_size)?;
+                (input_data, Some(file_iv))
+            }
+        },
+    };
+
+    let key = content_key(config, &header)?;
+    let cipher = crypto::create_cipher(&config.algorithm, &config.mode, &key, iv.as_deref())?;
+    let plaintext = cipher.decrypt_with_aad(&associated_data(&config.mode, &header)?, &input_data)?;
+
+    // Compression is recorded in the header, so it is undone without being asked for
+    let plaintext = match header.as_ref().and_then(|header| header.compression) {
+        Some(algorithm) => compression::decompress(algorithm, &plaintext, config.max_decompressed_size)?,
+        None => plaintext,
+    };
+
+    match &config.verify_key {
+        Some(key) => Ok(signed::open_bound(key, &plaintext, &signature_context(&header)?)?.to_vec()),
+        None => Ok(plaintext),
+    }
+}
+
+fn openssl_key_iv(
+    config: &cli::CliConfig,
+    options: &cli::OpensslOptions,
+    salt: &[u8],
+) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
+    let iv_size = crypto::iv_size(&config.mode);
+    let (key, iv) = openssl::derive_key_iv(options.kdf, options.password.as_bytes(), salt, options.key_size, iv_size)?;
+    Ok((key, (iv_size > 0).then_some(iv)))
+}
+
+fn signature_context(header: &Option<FileHeader>) -> Result<Vec<u8>> {
+    match header {
+        Some(header) => header.signature_context(),
+        None => Ok(Vec::new()),
+    }
+}
+
+pub(super) fn associated_data(mode: &str, header: &Option<FileHeader>) -> Result<Vec<u8>> {
+    match header {
+        Some(header) if crypto::is_authenticated_mode(mode) => header.associated_data(),
+        _ => Ok(Vec::new()),
+    }
+}
+
+// Derived subkeys and wrapped data keys are resolved through the header
+fn content_key(config: &cli::CliConfig, header: &Option<FileHeader>) -> Result<Vec<u8>> {
+    match header {
+        Some(header) if !config.private_keys.is_empty() => header.recipient_content_key(&config.private_keys),
+        Some(header) => header.content_key(&config.key),
+        None if !config.private_keys.is_empty() => Err(CryptoCoreError::InvalidArgument(
+            "File is not encrypted to public-key recipients; decrypt it with --key".to_string(),
+        )),
+        None => Ok(config.key.clone()),
+    }
+}
@@ -0,0 +1,77 @@
+use cryptocore::cli::DgstArgs;
+use cryptocore::core::crypto::hash::{self, HashAlgorithm};
+use cryptocore::core::io::{self, checksums};
+use cryptocore::{CryptoCoreError, Result};
+use std::path::Path;
+
+pub fn run(args: DgstArgs) -> Result<()> {
+    let algorithm = HashAlgorithm::from_name(&args.algorithm)?;
+
+    if let Some(manifest) = args.check {
+        return check(algorithm, &manifest);
+    }
+
+    let mut lines = Vec::with_capacity(args.files.len());
+    for path in &args.files {
+        let digest = if path == Path::new("-") {
+            hash::hash_reader(algorithm, &mut std::io::stdin().lock())?
+        } else {
+            checksums::digest_file(algorithm, path)?
+        };
+        lines.push(checksums::format_entry(&digest, path));
+    }
+
+    match args.output {
+        Some(manifest) => {
+            let mut content = lines.join("\n");
+            content.push('\n');
+            io::write_file(&manifest, content.as_bytes())?;
+            println!("Wrote {} digest(s) to {}", lines.len(), manifest.display());
+        }
+        None => {
+            for line in lines {
+                println!("{}", line);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn check(algorithm: HashAlgorithm, manifest: &Path) -> Result<()> {
+    let content = String::from_utf8(io::read_file(manifest)?).map_err(|_| {
+        CryptoCoreError::FileError(format!("{} is not a text file", manifest.display()))
+    })?;
+
+    let mut failures = 0;
+    for entry in checksums::parse_checksums(&content)? {
+        if entry.digest.len() != algorithm.output_size() {
+            return Err(CryptoCoreError::FileError(format!(
+                "Digest for {} is not a {} digest",
+                entry.path.display(),
+                algorithm
+            )));
+        }
+
+        match checksums::digest_file(algorithm, &entry.path) {
+            Ok(digest) if digest == entry.digest => println!("{}: OK", entry.path.display()),
+            Ok(_) => {
+                println!("{}: FAILED", entry.path.display());
+                failures += 1;
+            }
+            Err(e) => {
+                println!("{}: FAILED open or read ({})", entry.path.display(), e);
+                failures += 1;
+            }
+        }
+    }
+
+    if failures > 0 {
+        return Err(CryptoCoreError::Crypto(format!(
+            "{} computed checksum(s) did NOT match",
+            failures
+        )));
+    }
+
+    Ok(())
+}
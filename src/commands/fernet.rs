@@ -0,0 +1,43 @@
+use cryptocore::cli::{FernetArgs, FernetCommand, FernetDecryptArgs, FernetEncryptArgs};
+use cryptocore::core::crypto::fernet::Fernet;
+use cryptocore::core::io;
+use cryptocore::{CryptoCoreError, Result};
+use std::io::Write;
+
+use super::read_input;
+
+pub fn run(args: FernetArgs) -> Result<()> {
+    match args.command {
+        FernetCommand::Keygen => {
+            println!("{}", Fernet::generate_key()?);
+            Ok(())
+        }
+        FernetCommand::Encrypt(args) => encrypt(args),
+        FernetCommand::Decrypt(args) => decrypt(args),
+    }
+}
+
+fn encrypt(args: FernetEncryptArgs) -> Result<()> {
+    let fernet = Fernet::from_base64(&args.key)?;
+    let token = fernet.encrypt(&read_input(&args.file)?)?;
+
+    match args.output {
+        Some(path) => io::write_file(&path, format!("{}\n", token).as_bytes()),
+        None => {
+            println!("{}", token);
+            Ok(())
+        }
+    }
+}
+
+fn decrypt(args: FernetDecryptArgs) -> Result<()> {
+    let fernet = Fernet::from_base64(&args.key)?;
+    let token = String::from_utf8(read_input(&args.file)?)
+        .map_err(|_| CryptoCoreError::InvalidArgument("Fernet token must be text".to_string()))?;
+    let plaintext = fernet.decrypt(&token, args.ttl)?;
+
+    match args.output {
+        Some(path) => io::write_file(&path, &plaintext),
+        None => Ok(std::io::stdout().write_all(&plaintext)?),
+    }
+}
@@ -0,0 +1,95 @@
+use cryptocore::cli::{FpeArgs, FpeCommand, FpeCsvArgs};
+use cryptocore::core::crypto::fpe::{Alphabet, FpeAlgorithm, FpeCipher};
+use cryptocore::core::io::{self, csv};
+use cryptocore::{CryptoCoreError, Operation, Result};
+use std::io::Write;
+
+use super::read_input;
+
+pub fn run(args: FpeArgs) -> Result<()> {
+    match args.command {
+        FpeCommand::Encrypt(args) => process(args, Operation::Encrypt),
+        FpeCommand::Decrypt(args) => process(args, Operation::Decrypt),
+    }
+}
+
+fn process(args: FpeCsvArgs, operation: Operation) -> Result<()> {
+    let algorithm = FpeAlgorithm::from_name(&args.algorithm)?;
+    if algorithm == FpeAlgorithm::Ff3_1 && args.tweak.is_none() {
+        return Err(CryptoCoreError::ConfigError(
+            "FF3-1 needs a 7-byte --tweak".to_string()
+        ));
+    }
+    let alphabet = match (&args.alphabet, args.radix) {
+        (Some(symbols), _) => Alphabet::new(symbols)?,
+        (None, Some(radix)) => Alphabet::for_radix(radix)?,
+        (None, None) => Alphabet::digits(),
+    };
+    let cipher = FpeCipher::new(algorithm, &args.key, alphabet)?;
+    let tweak = args.tweak.unwrap_or_default();
+
+    let content = String::from_utf8(read_input(&args.file)?)
+        .map_err(|_| CryptoCoreError::FileError("CSV input must be UTF-8 text".to_string()))?;
+    let mut records = csv::parse(&content, args.delimiter)?;
+    let header = if args.no_header { None } else { records.first().cloned() };
+    let columns = args
+        .columns
+        .iter()
+        .map(|column| resolve_column(column, header.as_deref()))
+        .collect::<Result<Vec<_>>>()?;
+
+    let first_row = usize::from(header.is_some());
+    for (index, record) in records.iter_mut().enumerate().skip(first_row) {
+        for (&column, name) in columns.iter().zip(&args.columns) {
+            let at_cell = |message: String| {
+                CryptoCoreError::InvalidArgument(format!("{} (row {}, column {})", message, index + 1, name))
+            };
+            let field = record
+                .get_mut(column)
+                .ok_or_else(|| at_cell("Row is too short".to_string()))?;
+            // Missing values stay missing
+            if field.is_empty() {
+                continue;
+            }
+            let result = match operation {
+                Operation::Encrypt => cipher.encrypt(&tweak, field),
+                Operation::Decrypt => cipher.decrypt(&tweak, field),
+            };
+            *field = result.map_err(|e| match e {
+                CryptoCoreError::InvalidArgument(message) => at_cell(message),
+                other => other,
+            })?;
+        }
+    }
+
+    let line_ending = csv::line_ending(&content);
+    let output: String = records
+        .iter()
+        .map(|record| csv::format_record(record, args.delimiter) + line_ending)
+        .collect();
+    match args.output {
+        Some(path) => io::write_file(&path, output.as_bytes()),
+        None => {
+            std::io::stdout().write_all(output.as_bytes())?;
+            Ok(())
+        }
+    }
+}
+
+// A column is a 1-based index, or a name from the header row
+fn resolve_column(column: &str, header: Option<&[String]>) -> Result<usize> {
+    if let Ok(index) = column.parse::<usize>() {
+        return index.checked_sub(1).ok_or_else(|| {
+            CryptoCoreError::ConfigError("Column indices start at 1".to_string())
+        });
+    }
+    let header = header.ok_or_else(|| {
+        CryptoCoreError::ConfigError(format!(
+            "Column '{}' can only be found by name when the file has a header",
+            column
+        ))
+    })?;
+    header.iter().position(|name| name == column).ok_or_else(|| {
+        CryptoCoreError::ConfigError(format!("No column named '{}' in the header", column))
+    })
+}
@@ -0,0 +1,72 @@
+use cryptocore::cli::InspectArgs;
+use cryptocore::core::crypto::aead_stream::{self, StreamHeader};
+use cryptocore::core::io::{self, header::FileHeader, signed};
+use cryptocore::Result;
+
+pub fn run(args: InspectArgs) -> Result<()> {
+    let data = io::read_file(&args.file)?;
+
+    println!("File: {}", args.file.display());
+    println!("Size: {} bytes", data.len());
+
+    if FileHeader::is_present(&data) {
+        let (header, offset) = FileHeader::parse(&data)?;
+        print_header(&header, offset);
+        return Ok(());
+    }
+
+    if signed::is_signed(&data) {
+        println!("Signed file: {} (not encrypted; check it with `cryptocore verify`)", signed::algorithm(&data)?);
+        return Ok(());
+    }
+
+    if aead_stream::is_stream(&data) {
+        let header = StreamHeader::parse(&data)?;
+        println!(
+            "Chunked stream: {} with {}-byte chunks (decrypt it with `cryptocore stream decrypt`)",
+            header.aead,
+            header.chunk_size
+        );
+        return Ok(());
+    }
+
+    println!("Header: none (legacy format)");
+    if data.len().is_multiple_of(16) {
+        println!("Block aligned: yes ({} blocks of 16 bytes)", data.len() / 16);
+        println!("Possible modes: any (ecb/cbc need block-aligned ciphertext)");
+    } else {
+        println!("Block aligned: no");
+        println!("Possible modes: cfb, ofb, ctr");
+    }
+
+    if data.len() >= 16 {
+        println!("Leading IV (if prepended): {}", hex::encode(&data[..16]));
+    }
+
+    Ok(())
+}
+
+fn print_header(header: &FileHeader, payload_offset: usize) {
+    println!("Header: cryptocore v{} ({} bytes)", io::header::VERSION, payload_offset);
+
+    if let Some(iv) = &header.iv {
+        println!("IV: {}", hex::encode(iv));
+    }
+    if let Some(derivation) = &header.key_derivation {
+        println!(
+            "Key derivation: {} (context: {:?})",
+            derivation.algorithm,
+            String::from_utf8_lossy(&derivation.info)
+        );
+    }
+    if let Some(wrapped_key) = &header.wrapped_key {
+        println!("Envelope: AES-KW wrapped data key ({} bytes)", wrapped_key.len() - 8);
+    }
+    if let Some(compression) = &header.compression {
+        println!("Compression: {}", compression);
+    }
+    if !header.recipients.is_empty() {
+        let algorithms: Vec<&str> = header.recipients.iter().map(|recipient| recipient.algorithm.name()).collect();
+        println!("Recipients: {} ({})", header.recipients.len(), algorithms.join(", "));
+    }
+}
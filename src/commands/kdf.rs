@@ -0,0 +1,17 @@
+use cryptocore::cli::KdfArgs;
+use cryptocore::core::crypto::kdf::{self, KdfAlgorithm};
+use cryptocore::Result;
+
+pub fn run(args: KdfArgs) -> Result<()> {
+    let algorithm = KdfAlgorithm::from_name(&args.algorithm)?;
+    let key = kdf::hkdf(
+        algorithm,
+        &args.key,
+        args.salt.as_deref(),
+        args.info.as_bytes(),
+        args.length,
+    )?;
+
+    println!("{}", hex::encode(key));
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use cryptocore::cli::KeygenArgs;
+use cryptocore::core::crypto::asymmetric::{KeyAlgorithm, PrivateKey};
+use cryptocore::core::io;
+use cryptocore::{CryptoCoreError, Result};
+
+pub fn run(args: KeygenArgs) -> Result<()> {
+    if let Some(algorithm) = &args.algorithm {
+        return key_pair(KeyAlgorithm::from_name(algorithm)?, &args);
+    }
+
+    if args.length == 0 {
+        return Err(CryptoCoreError::InvalidArgument(
+            "Key length must be at least 1 byte".to_string(),
+        ));
+    }
+
+    let key_hex = hex::encode(io::generate_key(args.length)?);
+
+    match args.output {
+        Some(path) => {
+            io::write_file(&path, format!("{}\n", key_hex).as_bytes())?;
+            println!("Key written to {}", path.display());
+        }
+        None => println!("{}", key_hex),
+    }
+
+    Ok(())
+}
+
+fn key_pair(algorithm: KeyAlgorithm, args: &KeygenArgs) -> Result<()> {
+    let private_key = PrivateKey::generate(algorithm, args.bits)?;
+    let public_pem = private_key.public_key().to_pem();
+
+    match &args.output {
+        Some(path) => {
+            io::write_private_file(path, private_key.to_pem().as_bytes())?;
+            println!("Private key written to {}", path.display());
+        }
+        None => print!("{}", private_key.to_pem()),
+    }
+    match &args.public_out {
+        Some(path) => {
+            io::write_file(path, public_pem.as_bytes())?;
+            println!("Public key written to {}", path.display());
+        }
+        None => print!("{}", public_pem),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use cryptocore::cli::MacArgs;
+use cryptocore::core::crypto::mac::{self, MacAlgorithm};
+use cryptocore::{CryptoCoreError, Result};
+use std::fs::File;
+use std::path::Path;
+
+pub fn run(args: MacArgs) -> Result<()> {
+    let algorithm = MacAlgorithm::from_name(&args.algorithm)?;
+
+    let mac = if args.file == Path::new("-") {
+        mac::mac_reader(algorithm, &args.key, &mut std::io::stdin().lock())?
+    } else {
+        let mut file = File::open(&args.file).map_err(|e| {
+            CryptoCoreError::FileError(format!("Failed to read file {}: {}", args.file.display(), e))
+        })?;
+        mac::mac_reader(algorithm, &args.key, &mut file)?
+    };
+
+    match args.verify {
+        Some(tag) => {
+            mac.verify(&tag)?;
+            println!("Verified OK");
+        }
+        None => println!("{}  {}", hex::encode(mac.finalize()), args.file.display()),
+    }
+
+    Ok(())
+}
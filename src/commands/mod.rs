@@ -0,0 +1,102 @@
+mod age;
+mod bench;
+mod crypt;
+mod dgst;
+mod fernet;
+mod fpe;
+mod inspect;
+mod jwe;
+mod kdf;
+mod keygen;
+mod mac;
+mod pack;
+mod pgp;
+mod rand;
+mod reencrypt;
+mod rewrap;
+mod selftest;
+mod sign;
+mod stream;
+mod tree;
+mod wrap;
+
+use cryptocore::cli::Commands;
+use cryptocore::core::io;
+use cryptocore::{CryptoCoreError, Operation, Result};
+use std::fs::{self, File};
+use std::io::{BufWriter, Read, Write};
+use std::path::Path;
+
+pub fn run(command: Commands) -> Result<()> {
+    match command {
+        Commands::Enc(args) => crypt::run(args.into_config(Operation::Encrypt)?),
+        Commands::Dec(args) => crypt::run(args.into_config(Operation::Decrypt)?),
+        Commands::Keygen(args) => keygen::run(args),
+        Commands::Rand(args) => rand::run(args),
+        Commands::Dgst(args) => dgst::run(args),
+        Commands::Mac(args) => mac::run(args),
+        Commands::Sign(args) => sign::sign(args),
+        Commands::Verify(args) => sign::verify(args),
+        Commands::Kdf(args) => kdf::run(args),
+        Commands::Wrap(args) => wrap::wrap(args),
+        Commands::Unwrap(args) => wrap::unwrap(args),
+        Commands::Fernet(args) => fernet::run(args),
+        Commands::Jwe(args) => jwe::run(args),
+        Commands::Pgp(args) => pgp::run(args),
+        Commands::Age(args) => age::run(args),
+        Commands::Stream(args) => stream::run(args),
+        Commands::Fpe(args) => fpe::run(args),
+        Commands::Pack(args) => pack::pack(args),
+        Commands::Unpack(args) => pack::unpack(args),
+        Commands::Reencrypt(args) => reencrypt::run(args),
+        Commands::Rewrap(args) => rewrap::run(args),
+        Commands::Inspect(args) => inspect::run(args),
+        Commands::Bench(args) => bench::run(args),
+        Commands::Selftest => selftest::run(),
+    }
+}
+
+/// Reads a file, or standard input when the path is "-".
+fn read_input(path: &Path) -> Result<Vec<u8>> {
+    if path == Path::new("-") {
+        let mut data = Vec::new();
+        std::io::stdin().lock().read_to_end(&mut data)?;
+        Ok(data)
+    } else {
+        io::read_file(path)
+    }
+}
+
+/// Opens a file, or standard input when the path is "-", for streaming.
+fn open_input(path: &Path) -> Result<Box<dyn Read>> {
+    if path == Path::new("-") {
+        return Ok(Box::new(std::io::stdin().lock()));
+    }
+    let file = File::open(path).map_err(|e| {
+        CryptoCoreError::FileError(format!("Failed to read file {}: {}", path.display(), e))
+    })?;
+    Ok(Box::new(file))
+}
+
+/// Streams output into a temporary file that only replaces `path` once
+/// `write` has succeeded and the data is on disk.
+fn write_atomically(path: &Path, write: impl FnOnce(&mut BufWriter<File>) -> Result<u64>) -> Result<()> {
+    let temp_path = io::temp_path_for(path)?;
+    let file = File::create(&temp_path).map_err(|e| {
+        CryptoCoreError::FileError(format!("Failed to write file {}: {}", temp_path.display(), e))
+    })?;
+
+    let mut writer = BufWriter::new(file);
+    let written = write(&mut writer)
+        .and_then(|_| Ok(writer.flush()?))
+        .and_then(|_| Ok(writer.get_ref().sync_all()?))
+        .and_then(|_| {
+            fs::rename(&temp_path, path).map_err(|e| {
+                CryptoCoreError::FileError(format!("Failed to write file {}: {}", path.display(), e))
+            })
+        });
+    if written.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    written
+}
@@ -0,0 +1,195 @@
+use cryptocore::cli::{PackArgs, UnpackArgs};
+use cryptocore::core::io::archive::{ArchiveEntry, ArchiveReader, ArchiveWriter, EntryKind};
+use cryptocore::core::io::manifest;
+use cryptocore::{CryptoCoreError, Result};
+use std::fs::{self, File, Metadata, OpenOptions};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use super::tree::{collect_entries, create_dir};
+use super::write_atomically;
+
+pub fn pack(args: PackArgs) -> Result<()> {
+    if !args.dir.is_dir() {
+        return Err(CryptoCoreError::InvalidArgument(format!(
+            "{} is not a directory",
+            args.dir.display()
+        )));
+    }
+
+    let output = args.output.clone().unwrap_or_else(|| args.dir.with_extension("cca"));
+
+    let mut entries = Vec::new();
+    collect_entries(&args.dir, Path::new(""), output.canonicalize().ok().as_deref(), &mut entries)?;
+
+    // A failed pack leaves no truncated archive behind
+    write_atomically(&output, |writer| add_entries(&args, &entries, writer))?;
+
+    println!("Packed {} entries into {}", entries.len(), output.display());
+    Ok(())
+}
+
+fn add_entries(args: &PackArgs, entries: &[(PathBuf, Metadata)], writer: &mut BufWriter<File>) -> Result<u64> {
+    let mut archive = ArchiveWriter::new(writer, &args.key, &args.mode)?;
+    for (relative, metadata) in entries {
+        let path = args.dir.join(relative);
+        let entry = ArchiveEntry {
+            path: manifest::manifest_path(relative)?,
+            mode: permissions(metadata),
+            modified: metadata.modified().unwrap_or(UNIX_EPOCH),
+            kind: entry_kind(&path, metadata)?,
+        };
+
+        if let EntryKind::File { .. } = entry.kind {
+            let file = File::open(&path).map_err(|e| {
+                CryptoCoreError::FileError(format!("Failed to read file {}: {}", path.display(), e))
+            })?;
+            archive.add_file(&entry, &mut BufReader::new(file))?;
+        } else {
+            archive.add_entry(&entry)?;
+        }
+    }
+    archive.finish()?;
+    Ok(entries.len() as u64)
+}
+
+pub fn unpack(args: UnpackArgs) -> Result<()> {
+    let file = File::open(&args.archive).map_err(|e| {
+        CryptoCoreError::FileError(format!("Failed to read file {}: {}", args.archive.display(), e))
+    })?;
+    let output_dir = args.output.clone().unwrap_or_else(|| args.archive.with_extension(""));
+
+    // Each segment is authenticated before it is decrypted; a damaged archive
+    // stops the extraction at the damage
+    let mut archive = ArchiveReader::open(BufReader::new(file), &args.key, &args.mode)?;
+    create_dir(&output_dir)?;
+
+    // Links are created last so no later entry can be written through one, and
+    // directory metadata last so extracting their contents does not change it
+    let mut directories = Vec::new();
+    let mut symlinks = Vec::new();
+    let mut count = 0;
+
+    while let Some(entry) = archive.next_entry()? {
+        let relative = manifest::relative_path(&entry.path)
+            .ok_or_else(|| CryptoCoreError::FileError(format!("Unsafe path in archive: {}", entry.path)))?;
+        refuse_links(&output_dir, &relative)?;
+        let target = output_dir.join(relative);
+        count += 1;
+
+        match &entry.kind {
+            EntryKind::Directory => {
+                create_dir(&target)?;
+                directories.push((target, entry));
+            }
+            EntryKind::File { .. } => {
+                let write_error = |e: std::io::Error| {
+                    CryptoCoreError::FileError(format!("Failed to write file {}: {}", target.display(), e))
+                };
+                // Existing files are never overwritten
+                let file = OpenOptions::new().write(true).create_new(true).open(&target).map_err(write_error)?;
+                let mut file = BufWriter::new(file);
+                archive.read_contents(&mut file)?;
+                let file = file.into_inner().map_err(|e| write_error(e.into_error()))?;
+                restore_metadata(&file, &target, &entry)?;
+            }
+            EntryKind::Symlink { .. } => symlinks.push((target, entry)),
+        }
+    }
+
+    for (target, entry) in &symlinks {
+        create_symlink(target, entry)?;
+    }
+    for (target, entry) in directories.iter().rev() {
+        let dir = File::open(target).map_err(|e| {
+            CryptoCoreError::FileError(format!("Failed to open directory {}: {}", target.display(), e))
+        })?;
+        restore_metadata(&dir, target, entry)?;
+    }
+
+    println!("Unpacked {} entries into {}", count, output_dir.display());
+    Ok(())
+}
+
+// Nothing is extracted through a link that already exists in the output
+// directory, so an archive cannot write outside it
+fn refuse_links(output_dir: &Path, relative: &Path) -> Result<()> {
+    let mut path = output_dir.to_path_buf();
+    for component in relative.components() {
+        path.push(component);
+        if fs::symlink_metadata(&path).is_ok_and(|metadata| metadata.is_symlink()) {
+            return Err(CryptoCoreError::FileError(format!(
+                "Refusing to extract through the existing link {}",
+                path.display()
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn entry_kind(path: &Path, metadata: &Metadata) -> Result<EntryKind> {
+    if metadata.is_dir() {
+        Ok(EntryKind::Directory)
+    } else if metadata.is_symlink() {
+        let target = fs::read_link(path).map_err(|e| {
+            CryptoCoreError::FileError(format!("Failed to read link {}: {}", path.display(), e))
+        })?;
+        Ok(EntryKind::Symlink {
+            target: target.to_str().map(str::to_string).ok_or_else(|| {
+                CryptoCoreError::FileError(format!("Unsupported link target: {}", target.display()))
+            })?,
+        })
+    } else {
+        Ok(EntryKind::File { size: metadata.len() })
+    }
+}
+
+fn restore_metadata(file: &File, path: &Path, entry: &ArchiveEntry) -> Result<()> {
+    let metadata_error = |e: std::io::Error| {
+        CryptoCoreError::FileError(format!("Failed to set metadata of {}: {}", path.display(), e))
+    };
+    file.set_modified(entry.modified).map_err(metadata_error)?;
+    set_permissions(path, entry.mode).map_err(metadata_error)
+}
+
+#[cfg(unix)]
+fn permissions(metadata: &Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o777
+}
+
+#[cfg(not(unix))]
+fn permissions(metadata: &Metadata) -> u32 {
+    if metadata.permissions().readonly() { 0o444 } else { 0o644 }
+}
+
+#[cfg(unix)]
+fn set_permissions(path: &Path, mode: u32) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    // Set-user-ID, set-group-ID and sticky bits are never restored
+    fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o777))
+}
+
+#[cfg(not(unix))]
+fn set_permissions(path: &Path, mode: u32) -> std::io::Result<()> {
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_readonly(mode & 0o222 == 0);
+    fs::set_permissions(path, permissions)
+}
+
+#[cfg(unix)]
+fn create_symlink(path: &Path, entry: &ArchiveEntry) -> Result<()> {
+    let EntryKind::Symlink { target } = &entry.kind else {
+        return Ok(());
+    };
+    std::os::unix::fs::symlink(target, path).map_err(|e| {
+        CryptoCoreError::FileError(format!("Failed to create link {}: {}", path.display(), e))
+    })
+}
+
+#[cfg(not(unix))]
+fn create_symlink(path: &Path, _entry: &ArchiveEntry) -> Result<()> {
+    eprintln!("Warning: symbolic links are not supported here, skipping {}", path.display());
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use cryptocore::cli::{PgpArgs, PgpCommand, PgpDecryptArgs, PgpEncryptArgs};
+use cryptocore::core::crypto::openpgp::{self, AeadAlgorithm, EncryptOptions, LiteralData, SymmetricAlgorithm};
+use cryptocore::core::io::{self, armor};
+use cryptocore::{CryptoCoreError, Result};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use super::read_input;
+
+pub fn run(args: PgpArgs) -> Result<()> {
+    match args.command {
+        PgpCommand::Encrypt(args) => encrypt(args),
+        PgpCommand::Decrypt(args) => decrypt(args),
+    }
+}
+
+fn encrypt(args: PgpEncryptArgs) -> Result<()> {
+    let key_bits: usize = args.key_bits.parse().map_err(|_| {
+        CryptoCoreError::InvalidArgument(format!("Invalid key size: {}", args.key_bits))
+    })?;
+    let options = EncryptOptions {
+        cipher: SymmetricAlgorithm::from_key_size(key_bits / 8)?,
+        aead: args.aead.as_deref().map(AeadAlgorithm::from_name).transpose()?,
+        s2k_iterations: args.s2k_count,
+        ..EncryptOptions::default()
+    };
+
+    let literal = LiteralData {
+        filename: file_name(&args.file),
+        modified: modified(&args.file),
+        data: read_input(&args.file)?,
+    };
+    let message = openpgp::encrypt(args.pass.as_bytes(), &literal, &options)?;
+    let message = if args.armor {
+        armor::armor_as(armor::PGP_LABEL, &message).into_bytes()
+    } else {
+        message
+    };
+
+    match args.output.or_else(|| default_output(&args.file, args.armor)) {
+        Some(path) => io::write_file(&path, &message),
+        None => Ok(std::io::stdout().write_all(&message)?),
+    }
+}
+
+fn decrypt(args: PgpDecryptArgs) -> Result<()> {
+    let mut message = read_input(&args.file)?;
+    if armor::is_armored_as(armor::PGP_LABEL, &message) {
+        message = armor::dearmor_as(armor::PGP_LABEL, &message)?;
+    }
+    let literal = openpgp::decrypt(args.pass.as_bytes(), &message, args.allow_unprotected)?;
+
+    match args.output {
+        Some(path) => io::write_file(&path, &literal.data),
+        None => Ok(std::io::stdout().write_all(&literal.data)?),
+    }
+}
+
+// Standard input has no name; gpg stores an empty one too
+fn file_name(path: &Path) -> String {
+    if path == Path::new("-") {
+        return String::new();
+    }
+    path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+fn modified(path: &Path) -> u32 {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .and_then(|elapsed| u32::try_from(elapsed.as_secs()).ok())
+        .unwrap_or(0)
+}
+
+fn default_output(path: &Path, armored: bool) -> Option<PathBuf> {
+    if path == Path::new("-") {
+        return None;
+    }
+    let mut output = path.as_os_str().to_owned();
+    output.push(if armored { ".asc" } else { ".gpg" });
+    Some(PathBuf::from(output))
+}
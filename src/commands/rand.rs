@@ -0,0 +1,40 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use cryptocore::cli::RandArgs;
+use cryptocore::core::crypto::random;
+use cryptocore::{CryptoCoreError, Result};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+// A multiple of 3 so base64 chunks join without padding in between
+const CHUNK_SIZE: usize = 48 * 1024;
+
+pub fn run(args: RandArgs) -> Result<()> {
+    let writer: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(File::create(path).map_err(|e| {
+            CryptoCoreError::FileError(format!("Failed to write file {}: {}", path.display(), e))
+        })?),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    let mut writer = BufWriter::new(writer);
+
+    let mut remaining = args.length;
+    let mut chunk = vec![0u8; CHUNK_SIZE.min(args.length)];
+    while remaining > 0 {
+        let bytes = &mut chunk[..CHUNK_SIZE.min(remaining)];
+        random::fill(bytes)?;
+        if args.raw {
+            writer.write_all(bytes)?;
+        } else if args.base64 {
+            writer.write_all(BASE64.encode(&bytes).as_bytes())?;
+        } else {
+            writer.write_all(hex::encode(&bytes).as_bytes())?;
+        }
+        remaining -= bytes.len();
+    }
+    if !args.raw {
+        writeln!(writer)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use cryptocore::cli::ReencryptArgs;
+use cryptocore::core::crypto::{self, streaming::{StreamDecryptor, StreamEncryptor}};
+use cryptocore::core::io::{self, armor, header::FileHeader};
+use cryptocore::{CryptoCoreError, Result};
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use super::crypt::associated_data;
+
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+pub fn run(args: ReencryptArgs) -> Result<()> {
+    let new_mode = args.mode.as_deref().unwrap_or(&args.old_mode);
+
+    let mut files = Vec::new();
+    for path in &args.paths {
+        collect_files(path, &mut files)?;
+    }
+
+    for path in &files {
+        reencrypt_file(path, &args, new_mode)?;
+        println!("Re-encrypted: {} ({} -> {})", path.display(), args.old_mode, new_mode);
+    }
+
+    Ok(())
+}
+
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    // Links are neither followed nor replaced by a regular file
+    let metadata = fs::symlink_metadata(path).map_err(|e| {
+        CryptoCoreError::FileError(format!("Failed to read file {}: {}", path.display(), e))
+    })?;
+    if metadata.is_symlink() {
+        eprintln!("Warning: skipping symbolic link {}", path.display());
+        return Ok(());
+    }
+    if !metadata.is_dir() {
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+
+    let read_error = |e: std::io::Error| {
+        CryptoCoreError::FileError(format!("Failed to read directory {}: {}", path.display(), e))
+    };
+    let mut entries = fs::read_dir(path)
+        .map_err(read_error)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()
+        .map_err(read_error)?;
+    entries.sort();
+
+    for entry in entries {
+        collect_files(&entry, files)?;
+    }
+    Ok(())
+}
+
+fn reencrypt_file(path: &Path, args: &ReencryptArgs, new_mode: &str) -> Result<()> {
+    let read_error = |e: std::io::Error| {
+        CryptoCoreError::FileError(format!("Failed to read file {}: {}", path.display(), e))
+    };
+    let mut source = File::open(path).map_err(read_error)?;
+
+    // Files with a header keep its key derivation, envelope and compression settings;
+    // legacy files keep the plain `IV || ciphertext` layout
+    let (old_header, old_iv) = match FileHeader::read_from(&mut source)? {
+        Some((header, _)) => {
+            let iv = header.iv.clone();
+            (Some(header), iv)
+        }
+        None => {
+            source.seek(SeekFrom::Start(0)).map_err(read_error)?;
+            // Leave room for whitespace in front of the BEGIN line
+            let mut start = [0u8; 256];
+            let read = source.read(&mut start).map_err(read_error)?;
+            if armor::is_armored(&start[..read]) {
+                return Err(CryptoCoreError::FileError(format!(
+                    "{} is armored; decrypt and re-encrypt it instead",
+                    path.display()
+                )));
+            }
+
+            source.seek(SeekFrom::Start(0)).map_err(read_error)?;
+            let mut iv = vec![0u8; crypto::iv_size(&args.old_mode)];
+            source.read_exact(&mut iv).map_err(|_| {
+                CryptoCoreError::FileError("File is too short to contain IV".to_string())
+            })?;
+            (None, (!iv.is_empty()).then_some(iv))
+        }
+    };
+
+    let old_key = match &old_header {
+        Some(header) => header.content_key(&args.old_key)?,
+        None => args.old_key.clone(),
+    };
+
+    let new_iv = (crypto::iv_size(new_mode) > 0).then(|| io::generate_nonce(crypto::iv_size(new_mode))).transpose()?;
+    let new_header = match &old_header {
+        Some(old_header) => {
+            let mut header = FileHeader {
+                iv: new_iv.clone(),
+                key_derivation: old_header.key_derivation.clone(),
+                wrapped_key: None,
+                // The payload is re-encrypted as is, so it stays compressed
+                compression: old_header.compression,
+                recipients: Vec::new(),
+            };
+            if old_header.wrapped_key.is_some() {
+                header.wrap_data_key(&args.new_key, &io::generate_key(args.new_key.len())?)?;
+            }
+            Some(header)
+        }
+        None => None,
+    };
+    let new_key = match &new_header {
+        Some(header) => header.content_key(&args.new_key)?,
+        None => args.new_key.clone(),
+    };
+
+    let old_aad = associated_data(&args.old_mode, &old_header)?;
+    let new_aad = associated_data(new_mode, &new_header)?;
+    let mut decryptor = StreamDecryptor::with_aad("aes", &args.old_mode, &old_key, old_iv.as_deref(), &old_aad)?;
+    let mut encryptor = StreamEncryptor::with_aad("aes", new_mode, &new_key, new_iv.as_deref(), &new_aad)?;
+
+    // Plaintext only ever lives in memory; the replacement is built in a
+    // sibling file and renamed over the original once it is complete
+    let temp_path = io::temp_path_for(path)?;
+    let written = (|| -> Result<()> {
+        let write_error = |e: std::io::Error| {
+            CryptoCoreError::FileError(format!("Failed to write file {}: {}", temp_path.display(), e))
+        };
+        let mut target = File::create(&temp_path).map_err(write_error)?;
+        let permissions = source.metadata().map_err(read_error)?.permissions();
+        target.set_permissions(permissions).map_err(write_error)?;
+
+        match (&new_header, &new_iv) {
+            (Some(header), _) => target.write_all(&header.to_bytes()?).map_err(write_error)?,
+            (None, Some(iv)) => target.write_all(iv).map_err(write_error)?,
+            (None, None) => {}
+        }
+
+        let mut buffer = vec![0u8; READ_BUFFER_SIZE];
+        loop {
+            let read = source.read(&mut buffer).map_err(read_error)?;
+            if read == 0 {
+                break;
+            }
+            let plaintext = decryptor.update(&buffer[..read])?;
+            target.write_all(&encryptor.update(&plaintext)?).map_err(write_error)?;
+        }
+
+        let plaintext = decryptor.finish()?;
+        let mut ciphertext = encryptor.update(&plaintext)?;
+        ciphertext.extend(encryptor.finish()?);
+        target.write_all(&ciphertext).map_err(write_error)?;
+        target.sync_all().map_err(write_error)?;
+
+        fs::rename(&temp_path, path).map_err(write_error)
+    })();
+
+    if written.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    written
+}
@@ -0,0 +1,19 @@
+use cryptocore::cli::RewrapArgs;
+use cryptocore::core::io;
+use cryptocore::{CryptoCoreError, Result};
+
+pub fn run(args: RewrapArgs) -> Result<()> {
+    for path in &args.files {
+        let (mut header, header_len) = io::read_header(path)?.ok_or_else(|| {
+            CryptoCoreError::FileError(format!("{} has no cryptocore header", path.display()))
+        })?;
+
+        // Nothing is written unless the old key unwraps the data key
+        header.rewrap(&args.old_key, &args.new_key)?;
+        io::replace_header(path, header_len, &header)?;
+
+        println!("Rewrapped: {}", path.display());
+    }
+
+    Ok(())
+}
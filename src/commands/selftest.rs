@@ -0,0 +1,23 @@
+use cryptocore::core::crypto::selftest;
+use cryptocore::{CryptoCoreError, Result};
+
+pub fn run() -> Result<()> {
+    let mut failures = 0;
+
+    for test in selftest::known_answer_tests() {
+        match test.run() {
+            Ok(()) => println!("{:<40} ok", test.name),
+            Err(e) => {
+                println!("{:<40} FAILED ({})", test.name, e);
+                failures += 1;
+            }
+        }
+    }
+
+    if failures > 0 {
+        return Err(CryptoCoreError::Crypto(format!("{} self-test(s) failed", failures)));
+    }
+
+    println!("All self-tests passed");
+    Ok(())
+}
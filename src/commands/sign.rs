@@ -0,0 +1,49 @@
+use cryptocore::cli::{SignArgs, VerifyArgs};
+use cryptocore::core::crypto::asymmetric::{PrivateKey, PublicKey};
+use cryptocore::core::io::{self, signed};
+use cryptocore::Result;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::read_input;
+
+pub fn sign(args: SignArgs) -> Result<()> {
+    let key = PrivateKey::from_pem(&String::from_utf8_lossy(&io::read_file(&args.key)?))?;
+    let data = read_input(&args.file)?;
+    let output = if args.embed { signed::sign(&key, &data)? } else { key.sign(&data)? };
+
+    match args.output.or_else(|| default_output(&args.file, args.embed)) {
+        Some(path) => {
+            io::write_file(&path, &output)?;
+            println!("Signature written to {}", path.display());
+            Ok(())
+        }
+        None => Ok(std::io::stdout().write_all(&output)?),
+    }
+}
+
+pub fn verify(args: VerifyArgs) -> Result<()> {
+    let key = PublicKey::from_pem(&String::from_utf8_lossy(&io::read_file(&args.key)?))?;
+    let data = read_input(&args.file)?;
+
+    match &args.signature {
+        Some(signature) => key.verify(&data, &io::read_file(signature)?)?,
+        None => {
+            let message = signed::open(&key, &data)?;
+            if let Some(path) = &args.output {
+                io::write_file(path, message)?;
+            }
+        }
+    }
+    println!("Verified OK");
+    Ok(())
+}
+
+fn default_output(path: &Path, embed: bool) -> Option<PathBuf> {
+    if path == Path::new("-") {
+        return None;
+    }
+    let mut output = path.as_os_str().to_owned();
+    output.push(if embed { ".signed" } else { ".sig" });
+    Some(PathBuf::from(output))
+}
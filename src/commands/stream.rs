@@ -0,0 +1,44 @@
+use cryptocore::cli::{StreamArgs, StreamCommand, StreamDecryptArgs, StreamEncryptArgs};
+use cryptocore::core::crypto::aead_stream::{self, StreamAead, StreamHeader};
+use cryptocore::Result;
+use std::path::{Path, PathBuf};
+
+use super::{open_input, write_atomically};
+
+pub fn run(args: StreamArgs) -> Result<()> {
+    match args.command {
+        StreamCommand::Encrypt(args) => encrypt(args),
+        StreamCommand::Decrypt(args) => decrypt(args),
+    }
+}
+
+fn encrypt(args: StreamEncryptArgs) -> Result<()> {
+    let chunk_size = args.chunk_size.saturating_mul(1024);
+    let header = StreamHeader::new(StreamAead::from_name(&args.mode)?, chunk_size)?;
+    let reader = open_input(&args.file)?;
+
+    match args.output.or_else(|| default_output(&args.file)) {
+        Some(path) => write_atomically(&path, |writer| aead_stream::encrypt(&args.key, header, reader, writer)),
+        None => aead_stream::encrypt(&args.key, header, reader, std::io::stdout().lock()).map(|_| ()),
+    }
+}
+
+// Chunks reach stdout as they are verified; a file only appears once the
+// whole stream has checked out
+fn decrypt(args: StreamDecryptArgs) -> Result<()> {
+    let reader = open_input(&args.file)?;
+
+    match args.output {
+        Some(path) => write_atomically(&path, |writer| aead_stream::decrypt(&args.key, reader, writer)),
+        None => aead_stream::decrypt(&args.key, reader, std::io::stdout().lock()).map(|_| ()),
+    }
+}
+
+fn default_output(path: &Path) -> Option<PathBuf> {
+    if path == Path::new("-") {
+        return None;
+    }
+    let mut output = path.as_os_str().to_owned();
+    output.push(".cstream");
+    Some(PathBuf::from(output))
+}
@@ -0,0 +1,176 @@
+use cryptocore::cli::CliConfig;
+use cryptocore::core::crypto::hash::{self, HashAlgorithm};
+use cryptocore::core::io::{self, header::FileHeader};
+use cryptocore::core::io::manifest::{self, Manifest, ManifestEntry, MANIFEST_NAME};
+use cryptocore::{CryptoCoreError, Operation, Result};
+use std::collections::HashSet;
+use std::fs::{self, Metadata};
+use std::path::{Path, PathBuf};
+
+use super::crypt;
+
+pub fn run(config: &CliConfig) -> Result<()> {
+    if !config.input_file.is_dir() {
+        return Err(CryptoCoreError::InvalidArgument(format!(
+            "--recursive expects a directory: {}",
+            config.input_file.display()
+        )));
+    }
+
+    let output_dir = config.output_file.clone()
+        .unwrap_or_else(|| io::derive_output_path(&config.input_file, &config.operation));
+    create_dir(&output_dir)?;
+
+    match config.operation {
+        Operation::Encrypt => encrypt_tree(config, &output_dir),
+        Operation::Decrypt => decrypt_tree(config, &output_dir),
+    }
+}
+
+fn encrypt_tree(config: &CliConfig, output_dir: &Path) -> Result<()> {
+    // The output may live inside the input tree; it must not be walked into
+    let skip = output_dir.canonicalize().ok();
+    let mut entries = Vec::new();
+    collect_entries(&config.input_file, Path::new(""), skip.as_deref(), &mut entries)?;
+
+    let mut manifest = Manifest::default();
+    let mut stored_paths = HashSet::from([MANIFEST_NAME.to_string()]);
+    let (mut encrypted_count, mut copied_count) = (0, 0);
+
+    for (relative, metadata) in entries {
+        if metadata.is_symlink() {
+            eprintln!("Warning: skipping symbolic link {}", config.input_file.join(&relative).display());
+            continue;
+        }
+
+        let path = manifest::manifest_path(&relative)?;
+        if metadata.is_dir() {
+            create_dir(&output_dir.join(&relative))?;
+            manifest.entries.push(ManifestEntry::Directory { path });
+            continue;
+        }
+
+        let data = io::read_file(&config.input_file.join(&relative))?;
+        let entry = ManifestEntry::File {
+            path,
+            size: data.len() as u64,
+            digest: hash::hash(HashAlgorithm::Sha256, &data),
+            encrypted: config.include_encrypted || !looks_encrypted(&relative, &data),
+        };
+
+        let stored_path = entry.stored_path();
+        if !stored_paths.insert(stored_path.clone()) {
+            return Err(CryptoCoreError::FileError(format!(
+                "{} would overwrite another file in the encrypted tree",
+                stored_path
+            )));
+        }
+
+        let target = output_dir.join(&stored_path);
+        if let ManifestEntry::File { encrypted: true, .. } = entry {
+            crypt::encrypt(config, &data)?.write(&target)?;
+            encrypted_count += 1;
+        } else {
+            io::write_file(&target, &data)?;
+            copied_count += 1;
+        }
+        manifest.entries.push(entry);
+    }
+
+    crypt::encrypt(config, &manifest.to_bytes())?.write(&output_dir.join(MANIFEST_NAME))?;
+
+    println!("Operation completed successfully!");
+    println!("Output: {}", output_dir.display());
+    println!("Encrypted {} files", encrypted_count);
+    if copied_count > 0 {
+        println!("Copied {} already encrypted files unchanged (use --include-encrypted to encrypt them too)", copied_count);
+    }
+    Ok(())
+}
+
+fn decrypt_tree(config: &CliConfig, output_dir: &Path) -> Result<()> {
+    let (header, data) = io::read_file_with_header(&config.input_file.join(MANIFEST_NAME))?;
+    let manifest = Manifest::parse(&crypt::decrypt(config, header, data)?)?;
+    let mut restored_count = 0;
+
+    for entry in &manifest.entries {
+        // `Manifest::parse` has already rejected paths that are not plain relative names
+        let target = output_dir.join(entry.path());
+        let ManifestEntry::File { path, size, digest, encrypted } = entry else {
+            create_dir(&target)?;
+            continue;
+        };
+
+        let source = config.input_file.join(entry.stored_path());
+        let data = if *encrypted {
+            let (header, data) = io::read_file_with_header(&source)?;
+            crypt::decrypt(config, header, data)?
+        } else {
+            io::read_file(&source)?
+        };
+
+        if data.len() as u64 != *size || hash::hash(HashAlgorithm::Sha256, &data) != *digest {
+            return Err(CryptoCoreError::IntegrityCheckFailed(format!(
+                "{} does not match the manifest",
+                path
+            )));
+        }
+
+        io::write_file(&target, &data)?;
+        restored_count += 1;
+    }
+
+    println!("Operation completed successfully!");
+    println!("Output: {}", output_dir.display());
+    println!("Restored {} files", restored_count);
+    Ok(())
+}
+
+/// Lists everything below `root` depth-first, parents before their contents.
+/// Sorted so the result does not depend on directory order; symbolic links
+/// are listed but not followed.
+pub(super) fn collect_entries(
+    root: &Path,
+    relative: &Path,
+    skip: Option<&Path>,
+    entries: &mut Vec<(PathBuf, Metadata)>,
+) -> Result<()> {
+    let dir = root.join(relative);
+    let read_error = |e: std::io::Error| {
+        CryptoCoreError::FileError(format!("Failed to read directory {}: {}", dir.display(), e))
+    };
+    let mut names = fs::read_dir(&dir)
+        .map_err(read_error)?
+        .map(|entry| entry.map(|entry| entry.file_name()))
+        .collect::<std::io::Result<Vec<_>>>()
+        .map_err(read_error)?;
+    names.sort();
+
+    for name in names {
+        let relative = relative.join(name);
+        let path = root.join(&relative);
+        let metadata = fs::symlink_metadata(&path).map_err(read_error)?;
+
+        if metadata.is_dir() {
+            if skip.is_some() && path.canonicalize().ok().as_deref() == skip {
+                continue;
+            }
+            entries.push((relative.clone(), metadata));
+            collect_entries(root, &relative, skip, entries)?;
+        } else {
+            entries.push((relative, metadata));
+        }
+    }
+
+    Ok(())
+}
+
+fn looks_encrypted(relative: &Path, data: &[u8]) -> bool {
+    FileHeader::is_present(data) || relative.extension().is_some_and(|extension| extension == "enc")
+}
+
+pub(super) fn create_dir(path: &Path) -> Result<()> {
+    fs::create_dir_all(path).map_err(|e| {
+        CryptoCoreError::FileError(format!("Failed to create directory {}: {}", path.display(), e))
+    })
+}
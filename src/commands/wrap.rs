@@ -0,0 +1,25 @@
+use cryptocore::cli::WrapArgs;
+use cryptocore::core::crypto::keywrap;
+use cryptocore::Result;
+
+pub fn wrap(args: WrapArgs) -> Result<()> {
+    let wrapped = if args.padding {
+        keywrap::wrap_with_padding(&args.kek, &args.data)?
+    } else {
+        keywrap::wrap(&args.kek, &args.data)?
+    };
+
+    println!("{}", hex::encode(wrapped));
+    Ok(())
+}
+
+pub fn unwrap(args: WrapArgs) -> Result<()> {
+    let key = if args.padding {
+        keywrap::unwrap_with_padding(&args.kek, &args.data)?
+    } else {
+        keywrap::unwrap(&args.kek, &args.data)?
+    };
+
+    println!("{}", hex::encode(key));
+    Ok(())
+}
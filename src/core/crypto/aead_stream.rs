@@ -0,0 +1,370 @@
+//! STREAM segmented authenticated encryption (Hoang, Reyhanitabar, Rogaway
+//! and Vizár, "Online Authenticated-Encryption and its Nonce-Reuse
+//! Misuse-Resistance").
+//!
+//! Layout: `MAGIC | VERSION | AEAD id (u8) | chunk size (u32, big-endian) |
+//! salt (16 bytes) | nonce prefix (7 bytes)`, then the chunks. The chunks are
+//! sealed under a key of their own, HKDF-SHA256 of the given key with the
+//! salt, so a key used for many streams never repeats a nonce across them.
+//! Every chunk but the last holds exactly `chunk size` bytes of plaintext and
+//! is sealed on its own, with the nonce `prefix | chunk counter (u32,
+//! big-endian) | last-chunk flag` and the header as associated data. Each
+//! chunk is verified before it is released, so decryption needs one chunk of
+//! memory; a reordered chunk fails its counter, and a truncated stream ends
+//! on a chunk not sealed as the last.
+
+use crate::core::crypto::kdf::{self, KdfAlgorithm};
+use crate::core::crypto::modes::gcm::GcmMode;
+use crate::core::crypto::modes::ocb::OcbMode;
+use crate::core::io;
+use crate::error::{CryptoCoreError, Result};
+use std::fmt;
+use std::io::{Read, Write};
+
+pub const MAGIC: &[u8; 10] = b"CRYPTOSTRM";
+pub const VERSION: u8 = 2;
+pub const SALT_SIZE: usize = 16;
+pub const NONCE_PREFIX_SIZE: usize = 7;
+pub const HEADER_SIZE: usize = MAGIC.len() + 1 + 1 + 4 + SALT_SIZE + NONCE_PREFIX_SIZE;
+
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+/// Chunk sizes are bounded so a header cannot make decryption allocate much
+pub const MIN_CHUNK_SIZE: usize = 16;
+pub const MAX_CHUNK_SIZE: usize = 16 * 1024 * 1024;
+
+const NONCE_SIZE: usize = 12;
+const TAG_SIZE: usize = 16;
+const KEY_INFO: &[u8] = b"cryptocore stream key";
+
+/// The AEAD sealing each chunk. Both take a 12-byte nonce and a 16-byte tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamAead {
+    Gcm = 1,
+    Ocb = 2,
+}
+
+impl StreamAead {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "gcm" => Ok(Self::Gcm),
+            "ocb" => Ok(Self::Ocb),
+            _ => Err(CryptoCoreError::InvalidArgument(format!(
+                "Unsupported stream AEAD: {}",
+                name
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Gcm => "gcm",
+            Self::Ocb => "ocb",
+        }
+    }
+
+    pub fn id(&self) -> u8 {
+        *self as u8
+    }
+
+    pub fn from_id(id: u8) -> Result<Self> {
+        match id {
+            1 => Ok(Self::Gcm),
+            2 => Ok(Self::Ocb),
+            _ => Err(CryptoCoreError::FileError(format!(
+                "Unsupported stream AEAD id: {}",
+                id
+            ))),
+        }
+    }
+
+}
+
+impl fmt::Display for StreamAead {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+// The AEAD keyed once per stream; each chunk only sets its nonce
+enum ChunkCipher {
+    Gcm(GcmMode),
+    Ocb(OcbMode),
+}
+
+impl ChunkCipher {
+    fn new(key: &[u8], header: &StreamHeader) -> Result<Self> {
+        let key = kdf::hkdf(KdfAlgorithm::HkdfSha256, key, Some(&header.salt), KEY_INFO, key.len())?;
+        let nonce = chunk_nonce(&header.nonce_prefix, 0, false);
+        Ok(match header.aead {
+            StreamAead::Gcm => Self::Gcm(GcmMode::new(&key, &nonce)?),
+            StreamAead::Ocb => Self::Ocb(OcbMode::new(&key, &nonce)?),
+        })
+    }
+
+    fn seal(&mut self, nonce: &[u8], aad: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::Gcm(gcm) => {
+                gcm.set_nonce(nonce)?;
+                gcm.encrypt_with_aad(aad, data)
+            }
+            Self::Ocb(ocb) => {
+                ocb.set_nonce(nonce)?;
+                ocb.encrypt_with_aad(aad, data)
+            }
+        }
+    }
+
+    fn open(&mut self, nonce: &[u8], aad: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::Gcm(gcm) => {
+                gcm.set_nonce(nonce)?;
+                gcm.decrypt_with_aad(aad, data)
+            }
+            Self::Ocb(ocb) => {
+                ocb.set_nonce(nonce)?;
+                ocb.decrypt_with_aad(aad, data)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamHeader {
+    pub aead: StreamAead,
+    pub chunk_size: usize,
+    pub salt: [u8; SALT_SIZE],
+    pub nonce_prefix: [u8; NONCE_PREFIX_SIZE],
+}
+
+impl StreamHeader {
+    /// A header with a fresh random salt and nonce prefix.
+    pub fn new(aead: StreamAead, chunk_size: usize) -> Result<Self> {
+        check_chunk_size(chunk_size)?;
+        Ok(Self {
+            aead,
+            chunk_size,
+            salt: io::generate_nonce(SALT_SIZE)?.try_into().unwrap(),
+            nonce_prefix: io::generate_nonce(NONCE_PREFIX_SIZE)?.try_into().unwrap(),
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; HEADER_SIZE] {
+        let mut bytes = [0u8; HEADER_SIZE];
+        bytes[..MAGIC.len()].copy_from_slice(MAGIC);
+        bytes[MAGIC.len()] = VERSION;
+        bytes[MAGIC.len() + 1] = self.aead.id();
+        bytes[MAGIC.len() + 2..MAGIC.len() + 6].copy_from_slice(&(self.chunk_size as u32).to_be_bytes());
+        bytes[MAGIC.len() + 6..HEADER_SIZE - NONCE_PREFIX_SIZE].copy_from_slice(&self.salt);
+        bytes[HEADER_SIZE - NONCE_PREFIX_SIZE..].copy_from_slice(&self.nonce_prefix);
+        bytes
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if !is_stream(data) || data.len() < HEADER_SIZE {
+            return Err(CryptoCoreError::FileError("Not a chunked stream".to_string()));
+        }
+        if data[MAGIC.len()] != VERSION {
+            return Err(CryptoCoreError::FileError(format!(
+                "Unsupported stream version: {}",
+                data[MAGIC.len()]
+            )));
+        }
+
+        let aead = StreamAead::from_id(data[MAGIC.len() + 1])?;
+        let chunk_size = u32::from_be_bytes(data[MAGIC.len() + 2..MAGIC.len() + 6].try_into().unwrap()) as usize;
+        check_chunk_size(chunk_size).map_err(|_| {
+            CryptoCoreError::FileError(format!("Malformed stream header: chunk size {}", chunk_size))
+        })?;
+        Ok(Self {
+            aead,
+            chunk_size,
+            salt: data[MAGIC.len() + 6..HEADER_SIZE - NONCE_PREFIX_SIZE].try_into().unwrap(),
+            nonce_prefix: data[HEADER_SIZE - NONCE_PREFIX_SIZE..HEADER_SIZE].try_into().unwrap(),
+        })
+    }
+}
+
+pub fn is_stream(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+fn check_chunk_size(chunk_size: usize) -> Result<()> {
+    if !(MIN_CHUNK_SIZE..=MAX_CHUNK_SIZE).contains(&chunk_size) {
+        return Err(CryptoCoreError::InvalidArgument(format!(
+            "Chunk size must be between {} bytes and {} MiB",
+            MIN_CHUNK_SIZE,
+            MAX_CHUNK_SIZE / (1024 * 1024)
+        )));
+    }
+    Ok(())
+}
+
+/// Seals chunks one at a time, in order.
+pub struct Sealer {
+    cipher: ChunkCipher,
+    header: StreamHeader,
+    aad: [u8; HEADER_SIZE],
+    counter: u32,
+    finished: bool,
+}
+
+impl Sealer {
+    pub fn new(key: &[u8], header: StreamHeader) -> Result<Self> {
+        let cipher = ChunkCipher::new(key, &header)?;
+        Ok(Self { cipher, aad: header.to_bytes(), header, counter: 0, finished: false })
+    }
+
+    /// Seals the next chunk, which must be full unless it is the last.
+    pub fn seal_chunk(&mut self, chunk: &[u8], last: bool) -> Result<Vec<u8>> {
+        if self.finished {
+            return Err(CryptoCoreError::Crypto("The stream is already finished".to_string()));
+        }
+        if chunk.len() > self.header.chunk_size || (!last && chunk.len() != self.header.chunk_size) {
+            return Err(CryptoCoreError::InvalidArgument(format!(
+                "Only the last chunk may be shorter than {} bytes",
+                self.header.chunk_size
+            )));
+        }
+
+        let nonce = chunk_nonce(&self.header.nonce_prefix, self.counter, last);
+        let sealed = self.cipher.seal(&nonce, &self.aad, chunk)?;
+        if last {
+            self.finished = true;
+        } else {
+            self.counter = next_counter(self.counter)?;
+        }
+        Ok(sealed)
+    }
+}
+
+/// Verifies and opens chunks one at a time, in order.
+pub struct Opener {
+    cipher: ChunkCipher,
+    header: StreamHeader,
+    aad: [u8; HEADER_SIZE],
+    counter: u32,
+    finished: bool,
+}
+
+impl Opener {
+    pub fn new(key: &[u8], header: StreamHeader) -> Result<Self> {
+        let cipher = ChunkCipher::new(key, &header)?;
+        Ok(Self { cipher, aad: header.to_bytes(), header, counter: 0, finished: false })
+    }
+
+    /// Opens the next sealed chunk; `last` says whether the stream ends after it.
+    pub fn open_chunk(&mut self, sealed: &[u8], last: bool) -> Result<Vec<u8>> {
+        if self.finished {
+            return Err(CryptoCoreError::Crypto("The stream is already finished".to_string()));
+        }
+        if sealed.len() < TAG_SIZE || sealed.len() > self.header.chunk_size + TAG_SIZE {
+            return Err(CryptoCoreError::FileError(format!("Malformed stream chunk {}", self.counter)));
+        }
+
+        let nonce = chunk_nonce(&self.header.nonce_prefix, self.counter, last);
+        let chunk = self.cipher.open(&nonce, &self.aad, sealed).map_err(|_| {
+            CryptoCoreError::AuthenticationFailed(format!(
+                "Stream chunk {} failed to authenticate (modified, reordered or truncated)",
+                self.counter
+            ))
+        })?;
+        if last {
+            self.finished = true;
+        } else {
+            self.counter = next_counter(self.counter)?;
+        }
+        Ok(chunk)
+    }
+}
+
+fn chunk_nonce(prefix: &[u8; NONCE_PREFIX_SIZE], counter: u32, last: bool) -> [u8; NONCE_SIZE] {
+    let mut nonce = [0u8; NONCE_SIZE];
+    nonce[..NONCE_PREFIX_SIZE].copy_from_slice(prefix);
+    nonce[NONCE_PREFIX_SIZE..NONCE_SIZE - 1].copy_from_slice(&counter.to_be_bytes());
+    nonce[NONCE_SIZE - 1] = u8::from(last);
+    nonce
+}
+
+fn next_counter(counter: u32) -> Result<u32> {
+    counter.checked_add(1).ok_or_else(|| {
+        CryptoCoreError::InvalidArgument("Stream exceeds 2^32 chunks; use a larger chunk size".to_string())
+    })
+}
+
+/// Encrypts `reader` to `writer` (header first) and returns the plaintext length.
+pub fn encrypt<R: Read, W: Write>(key: &[u8], header: StreamHeader, mut reader: R, mut writer: W) -> Result<u64> {
+    let (chunk_size, header_bytes) = (header.chunk_size, header.to_bytes());
+    // A bad key fails before anything is written
+    let mut sealer = Sealer::new(key, header)?;
+    writer.write_all(&header_bytes)?;
+    let mut total = 0u64;
+
+    // One chunk of lookahead: a full chunk is only known to be the last once
+    // the next read comes back empty, so no empty chunk follows a full one
+    let mut current = read_chunk(&mut reader, chunk_size)?;
+    loop {
+        let next = if current.len() == chunk_size { read_chunk(&mut reader, chunk_size)? } else { Vec::new() };
+        let last = next.is_empty();
+        writer.write_all(&sealer.seal_chunk(&current, last)?)?;
+        total += current.len() as u64;
+        if last {
+            break;
+        }
+        current = next;
+    }
+
+    writer.flush()?;
+    Ok(total)
+}
+
+/// Decrypts a stream written by `encrypt`, writing each chunk once it has
+/// been verified, and returns the plaintext length. On an error, the chunks
+/// before the failing one have already been written.
+pub fn decrypt<R: Read, W: Write>(key: &[u8], mut reader: R, mut writer: W) -> Result<u64> {
+    let mut header_bytes = [0u8; HEADER_SIZE];
+    let read = read_full(&mut reader, &mut header_bytes)?;
+    let header = StreamHeader::parse(&header_bytes[..read])?;
+    let sealed_size = header.chunk_size + TAG_SIZE;
+    let mut opener = Opener::new(key, header)?;
+    let mut total = 0u64;
+
+    let mut current = read_chunk(&mut reader, sealed_size)?;
+    if current.is_empty() {
+        return Err(CryptoCoreError::AuthenticationFailed("Stream has no chunks (truncated)".to_string()));
+    }
+    loop {
+        let next = if current.len() == sealed_size { read_chunk(&mut reader, sealed_size)? } else { Vec::new() };
+        let last = next.is_empty();
+        let chunk = opener.open_chunk(&current, last)?;
+        writer.write_all(&chunk)?;
+        total += chunk.len() as u64;
+        if last {
+            break;
+        }
+        current = next;
+    }
+
+    writer.flush()?;
+    Ok(total)
+}
+
+// Up to `size` bytes, fewer only at the end of the input
+fn read_chunk<R: Read>(reader: &mut R, size: usize) -> Result<Vec<u8>> {
+    let mut chunk = vec![0u8; size];
+    let read = read_full(reader, &mut chunk)?;
+    chunk.truncate(read);
+    Ok(chunk)
+}
+
+fn read_full<R: Read>(reader: &mut R, buffer: &mut [u8]) -> Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(filled)
+}
@@ -1,44 +1,102 @@
 use super::traits::Cipher;
 use crate::error::{CryptoCoreError, Result};
-use aes::Aes128;
+use aes::{Aes128, Aes192, Aes256};
 use cipher::{BlockDecrypt, BlockEncrypt, KeyInit};
 use cipher::generic_array::GenericArray;
 
+enum AesKey {
+    Aes128(Aes128),
+    Aes192(Aes192),
+    Aes256(Aes256),
+}
+
 pub struct AesCipher {
-    pub cipher: Aes128,
+    cipher: AesKey,
 }
 
 impl AesCipher {
     pub fn new(key: &[u8]) -> Result<Self> {
-        if key.len() != 16 {
-            return Err(CryptoCoreError::InvalidKey(
-                "AES-128 requires exactly 16 bytes key".to_string(),
-            ));
-        }
+        let cipher = match key.len() {
+            16 => AesKey::Aes128(Aes128::new(GenericArray::from_slice(key))),
+            24 => AesKey::Aes192(Aes192::new(GenericArray::from_slice(key))),
+            32 => AesKey::Aes256(Aes256::new(GenericArray::from_slice(key))),
+            _ => {
+                return Err(CryptoCoreError::InvalidKey(
+                    "AES requires a 16, 24 or 32 bytes key".to_string(),
+                ))
+            }
+        };
 
-        let key_array = GenericArray::from_slice(key);
-        let cipher = Aes128::new(key_array);
         Ok(AesCipher { cipher })
     }
+
+    pub fn key_size(&self) -> usize {
+        match self.cipher {
+            AesKey::Aes128(_) => 16,
+            AesKey::Aes192(_) => 24,
+            AesKey::Aes256(_) => 32,
+        }
+    }
+
+    pub fn encrypt_block(&self, block: &mut [u8; 16]) {
+        let block = GenericArray::from_mut_slice(block);
+        match &self.cipher {
+            AesKey::Aes128(cipher) => cipher.encrypt_block(block),
+            AesKey::Aes192(cipher) => cipher.encrypt_block(block),
+            AesKey::Aes256(cipher) => cipher.encrypt_block(block),
+        }
+    }
+
+    pub fn decrypt_block(&self, block: &mut [u8; 16]) {
+        let block = GenericArray::from_mut_slice(block);
+        match &self.cipher {
+            AesKey::Aes128(cipher) => cipher.decrypt_block(block),
+            AesKey::Aes192(cipher) => cipher.decrypt_block(block),
+            AesKey::Aes256(cipher) => cipher.decrypt_block(block),
+        }
+    }
 }
 
 impl Cipher for AesCipher {
     fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
         let block_size = self.block_size();
         let padded_data = self.pad_data(data, block_size)?;
-        let mut result = Vec::with_capacity(padded_data.len());
+        self.encrypt_blocks(&padded_data)
+    }
+
+    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let result = self.decrypt_blocks(data)?;
+        self.unpad_data(&result)
+    }
+
+    fn block_size(&self) -> usize {
+        16
+    }
+}
+
+impl AesCipher {
+    /// ECB encryption without padding; `data` must be a whole number of blocks.
+    pub fn encrypt_blocks(&self, data: &[u8]) -> Result<Vec<u8>> {
+        if !data.len().is_multiple_of(self.block_size()) {
+            return Err(CryptoCoreError::Crypto(
+                "Plaintext length must be a multiple of block size".to_string(),
+            ));
+        }
+
+        let mut result = Vec::with_capacity(data.len());
         
-        for chunk in padded_data.chunks(block_size) {
-            let mut block = GenericArray::clone_from_slice(chunk);
-            self.cipher.encrypt_block(&mut block);
+        for chunk in data.chunks(self.block_size()) {
+            let mut block: [u8; 16] = chunk.try_into().unwrap();
+            self.encrypt_block(&mut block);
             result.extend_from_slice(&block);
         }
         
         Ok(result)
     }
 
-    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
-        if data.len() % self.block_size() != 0 {
+    /// ECB decryption without removing padding.
+    pub fn decrypt_blocks(&self, data: &[u8]) -> Result<Vec<u8>> {
+        if !data.len().is_multiple_of(self.block_size()) {
             return Err(CryptoCoreError::Crypto(
                 "Ciphertext length must be a multiple of block size".to_string(),
             ));
@@ -47,24 +105,18 @@ impl Cipher for AesCipher {
         let mut result = Vec::with_capacity(data.len());
         
         for chunk in data.chunks(self.block_size()) {
-            let mut block = GenericArray::clone_from_slice(chunk);
-            self.cipher.decrypt_block(&mut block);
+            let mut block: [u8; 16] = chunk.try_into().unwrap();
+            self.decrypt_block(&mut block);
             result.extend_from_slice(&block);
         }
         
-        self.unpad_data(&result)
-    }
-
-    fn block_size(&self) -> usize {
-        16
+        Ok(result)
     }
-}
 
-impl AesCipher {
     fn pad_data(&self, data: &[u8], block_size: usize) -> Result<Vec<u8>> {
         let mut padded = data.to_vec();
         let pad_len = block_size - (data.len() % block_size);
-        padded.extend(std::iter::repeat(pad_len as u8).take(pad_len));
+        padded.extend(std::iter::repeat_n(pad_len as u8, pad_len));
         Ok(padded)
     }
 
@@ -81,8 +133,8 @@ impl AesCipher {
         }
         
         let mut padding_valid = true;
-        for i in (data.len() - pad_len)..data.len() {
-            padding_valid &= data[i] == pad_byte;
+        for &byte in &data[data.len() - pad_len..] {
+            padding_valid &= byte == pad_byte;
         }
         
         if !padding_valid {
@@ -0,0 +1,94 @@
+//! Bech32 (BIP 173), the encoding of age keys. age does not apply the
+//! 90-character limit, so neither does this.
+
+use crate::error::{CryptoCoreError, Result};
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+
+pub(super) fn encode(hrp: &str, data: &[u8]) -> String {
+    let values = convert_bits(data, 8, 5, true).unwrap();
+    let checksum = checksum(hrp, &values);
+
+    let mut encoded = format!("{}1", hrp);
+    for value in values.iter().chain(&checksum) {
+        encoded.push(CHARSET[usize::from(*value)] as char);
+    }
+    encoded
+}
+
+/// Decodes `encoded`, returning the lowercase human-readable part and the data.
+pub(super) fn decode(encoded: &str) -> Result<(String, Vec<u8>)> {
+    let invalid = |reason: &str| CryptoCoreError::InvalidKey(format!("Invalid Bech32 string: {}", reason));
+
+    if encoded.chars().any(|c| c.is_ascii_lowercase()) && encoded.chars().any(|c| c.is_ascii_uppercase()) {
+        return Err(invalid("mixed case"));
+    }
+    let encoded = encoded.to_ascii_lowercase();
+    let separator = encoded.rfind('1').ok_or_else(|| invalid("no separator"))?;
+    let (hrp, rest) = (&encoded[..separator], &encoded[separator + 1..]);
+    if hrp.is_empty() || rest.len() < 6 || !hrp.bytes().all(|byte| (33..=126).contains(&byte)) {
+        return Err(invalid("malformed"));
+    }
+
+    let values = rest
+        .bytes()
+        .map(|byte| CHARSET.iter().position(|&c| c == byte).map(|value| value as u8))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| invalid("invalid character"))?;
+    if polymod(&[&expand_hrp(hrp), &values[..]].concat()) != 1 {
+        return Err(invalid("checksum mismatch"));
+    }
+
+    let data = convert_bits(&values[..values.len() - 6], 5, 8, false).ok_or_else(|| invalid("invalid padding"))?;
+    Ok((hrp.to_string(), data))
+}
+
+fn checksum(hrp: &str, values: &[u8]) -> [u8; 6] {
+    let polymod = polymod(&[&expand_hrp(hrp), values, &[0; 6]].concat()) ^ 1;
+    std::array::from_fn(|i| ((polymod >> (5 * (5 - i))) & 31) as u8)
+}
+
+fn polymod(values: &[u8]) -> u32 {
+    let mut checksum = 1u32;
+    for &value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x1ffffff) << 5) ^ u32::from(value);
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+fn expand_hrp(hrp: &str) -> Vec<u8> {
+    let mut expanded: Vec<u8> = hrp.bytes().map(|byte| byte >> 5).collect();
+    expanded.push(0);
+    expanded.extend(hrp.bytes().map(|byte| byte & 31));
+    expanded
+}
+
+// Regroups bits; without `pad`, leftover bits must be zero padding of under one group
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let (mut accumulator, mut bits) = (0u32, 0u32);
+    let max = (1u32 << to) - 1;
+    let mut result = Vec::new();
+    for &value in data {
+        accumulator = (accumulator << from) | u32::from(value);
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            result.push(((accumulator >> bits) & max) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            result.push(((accumulator << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || (accumulator << (to - bits)) & max != 0 {
+        return None;
+    }
+    Some(result)
+}
@@ -0,0 +1,452 @@
+//! The age v1 file format (age-encryption.org/v1), with X25519 and scrypt recipients.
+//!
+//! A random 16-byte file key is wrapped once per recipient into the text
+//! header, which is authenticated with HMAC-SHA256 under a key derived from the
+//! file key. The payload is a nonce followed by ChaCha20-Poly1305 STREAM chunks
+//! of 64 KiB, so both directions work through `Read`/`Write` in one chunk of
+//! memory.
+
+mod bech32;
+
+use crate::core::crypto::chacha20poly1305::{self, ChaCha20Poly1305};
+use crate::core::crypto::curve25519::x25519;
+use crate::core::crypto::kdf::{self, KdfAlgorithm};
+use crate::core::crypto::mac::{self, MacAlgorithm};
+use crate::core::io;
+use crate::error::{CryptoCoreError, Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD_NO_PAD;
+use std::fmt;
+use std::io::{BufRead, BufReader, Read, Write};
+
+pub const INTRO: &str = "age-encryption.org/v1";
+/// The scrypt work factor (log2 of N) `age` uses when encrypting.
+pub const DEFAULT_WORK_FACTOR: u8 = 18;
+/// The largest scrypt work factor accepted when decrypting, as in `age`.
+pub const MAX_WORK_FACTOR: u8 = 22;
+
+const FILE_KEY_SIZE: usize = 16;
+const PAYLOAD_NONCE_SIZE: usize = 16;
+const CHUNK_SIZE: usize = 64 * 1024;
+const SCRYPT_SALT_SIZE: usize = 16;
+// The wrapped file key plus its Poly1305 tag
+const WRAPPED_KEY_SIZE: usize = FILE_KEY_SIZE + chacha20poly1305::TAG_SIZE;
+const COLUMNS: usize = 64;
+// Longer header lines are rejected rather than buffered
+const MAX_HEADER_LINE: usize = 1024;
+
+const X25519_LABEL: &[u8] = b"age-encryption.org/v1/X25519";
+const SCRYPT_LABEL: &[u8] = b"age-encryption.org/v1/scrypt";
+const RECIPIENT_HRP: &str = "age";
+const IDENTITY_HRP: &str = "age-secret-key-";
+
+/// An X25519 public key, written as `age1...`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct X25519Recipient {
+    public: [u8; x25519::KEY_SIZE],
+}
+
+impl X25519Recipient {
+    pub fn parse(encoded: &str) -> Result<Self> {
+        let public = decode_key(encoded, RECIPIENT_HRP)?;
+        Ok(Self { public })
+    }
+}
+
+impl fmt::Display for X25519Recipient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&bech32::encode(RECIPIENT_HRP, &self.public))
+    }
+}
+
+/// An X25519 secret key, written as `AGE-SECRET-KEY-1...`.
+#[derive(Clone)]
+pub struct X25519Identity {
+    secret: [u8; x25519::KEY_SIZE],
+}
+
+impl X25519Identity {
+    pub fn generate() -> Result<Self> {
+        Ok(Self { secret: x25519::generate_secret()? })
+    }
+
+    pub fn parse(encoded: &str) -> Result<Self> {
+        let secret = decode_key(encoded, IDENTITY_HRP)?;
+        Ok(Self { secret })
+    }
+
+    pub fn recipient(&self) -> X25519Recipient {
+        X25519Recipient { public: x25519::public_key(&self.secret) }
+    }
+}
+
+impl fmt::Display for X25519Identity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&bech32::encode(IDENTITY_HRP, &self.secret).to_uppercase())
+    }
+}
+
+pub enum Recipient {
+    X25519(X25519Recipient),
+    /// Must be the only recipient of a file. `work_factor` is log2 of scrypt's N.
+    Scrypt { passphrase: Vec<u8>, work_factor: u8 },
+}
+
+pub enum Identity {
+    X25519(X25519Identity),
+    /// Files needing a work factor above `max_work_factor` are rejected.
+    Scrypt { passphrase: Vec<u8>, max_work_factor: u8 },
+}
+
+/// Parses an identity file: one `AGE-SECRET-KEY-1...` per line, with blank
+/// lines and `#` comments ignored.
+pub fn parse_identities(text: &str) -> Result<Vec<Identity>> {
+    let mut identities = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let identity = X25519Identity::parse(line).map_err(|_| {
+            CryptoCoreError::InvalidKey(format!("Invalid age identity on line {}", number + 1))
+        })?;
+        identities.push(Identity::X25519(identity));
+    }
+
+    if identities.is_empty() {
+        return Err(CryptoCoreError::InvalidKey("No age identities found".to_string()));
+    }
+    Ok(identities)
+}
+
+/// Encrypts `reader` to `writer` and returns the plaintext length.
+pub fn encrypt<R: Read, W: Write>(recipients: &[Recipient], mut reader: R, mut writer: W) -> Result<u64> {
+    if recipients.is_empty() {
+        return Err(CryptoCoreError::InvalidArgument("At least one age recipient is required".to_string()));
+    }
+    if recipients.len() > 1 && recipients.iter().any(|r| matches!(r, Recipient::Scrypt { .. })) {
+        return Err(CryptoCoreError::InvalidArgument(
+            "A passphrase cannot be combined with other age recipients".to_string(),
+        ));
+    }
+
+    let file_key: [u8; FILE_KEY_SIZE] = io::generate_key(FILE_KEY_SIZE)?.try_into().unwrap();
+    let stanzas = recipients
+        .iter()
+        .map(|recipient| wrap(recipient, &file_key))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut header = format!("{}\n", INTRO).into_bytes();
+    for stanza in &stanzas {
+        stanza.write(&mut header);
+    }
+    header.extend_from_slice(b"---");
+    let header_mac = mac::compute(MacAlgorithm::HmacSha256, &header_key(&file_key)?, &header)?;
+    header.extend_from_slice(format!(" {}\n", STANDARD_NO_PAD.encode(header_mac)).as_bytes());
+    writer.write_all(&header)?;
+
+    let nonce = io::generate_nonce(PAYLOAD_NONCE_SIZE)?;
+    writer.write_all(&nonce)?;
+    let aead = ChaCha20Poly1305::new(&payload_key(&file_key, &nonce)?)?;
+    let mut total = 0u64;
+
+    // One chunk of lookahead: a payload that fills its last chunk exactly
+    // ends there, without an empty chunk after it
+    let mut current = read_chunk(&mut reader, CHUNK_SIZE)?;
+    for counter in 0u64.. {
+        let next = if current.len() == CHUNK_SIZE { read_chunk(&mut reader, CHUNK_SIZE)? } else { Vec::new() };
+        let last = next.is_empty();
+        writer.write_all(&aead.encrypt(&chunk_nonce(counter, last), &[], &current)?)?;
+        total += current.len() as u64;
+        if last {
+            break;
+        }
+        current = next;
+    }
+
+    writer.flush()?;
+    Ok(total)
+}
+
+/// Decrypts `reader` to `writer`, writing each chunk once it has been
+/// verified, and returns the plaintext length. On an error, the chunks
+/// before the failing one have already been written.
+pub fn decrypt<R: Read, W: Write>(identities: &[Identity], reader: R, mut writer: W) -> Result<u64> {
+    let mut reader = BufReader::new(reader);
+    let header = Header::read(&mut reader)?;
+    if header.stanzas.iter().any(|s| s.kind == "scrypt") && header.stanzas.len() != 1 {
+        return Err(malformed("scrypt stanza must be the only recipient"));
+    }
+
+    let mut file_key = None;
+    'search: for stanza in &header.stanzas {
+        for identity in identities {
+            if let Some(key) = unwrap(identity, stanza)? {
+                file_key = Some(key);
+                break 'search;
+            }
+        }
+    }
+    let file_key = file_key.ok_or_else(|| {
+        CryptoCoreError::AuthenticationFailed("No identity matched any of the age recipients".to_string())
+    })?;
+
+    let expected = mac::compute(MacAlgorithm::HmacSha256, &header_key(&file_key)?, &header.authenticated)?;
+    if !mac::constant_time_eq(&expected, &header.mac) {
+        return Err(CryptoCoreError::AuthenticationFailed("age header MAC mismatch".to_string()));
+    }
+
+    let nonce = read_chunk(&mut reader, PAYLOAD_NONCE_SIZE)?;
+    if nonce.len() < PAYLOAD_NONCE_SIZE {
+        return Err(malformed("truncated payload nonce"));
+    }
+    let aead = ChaCha20Poly1305::new(&payload_key(&file_key, &nonce)?)?;
+    let sealed_size = CHUNK_SIZE + chacha20poly1305::TAG_SIZE;
+    let mut total = 0u64;
+
+    let mut current = read_chunk(&mut reader, sealed_size)?;
+    if current.is_empty() {
+        return Err(malformed("missing payload"));
+    }
+    for counter in 0u64.. {
+        let next = if current.len() == sealed_size { read_chunk(&mut reader, sealed_size)? } else { Vec::new() };
+        let last = next.is_empty();
+        // A truncated file ends on a chunk sealed as non-final, which fails here
+        let plaintext = aead
+            .decrypt(&chunk_nonce(counter, last), &[], &current)
+            .map_err(|_| CryptoCoreError::AuthenticationFailed("age payload chunk failed to authenticate".to_string()))?;
+        if last && plaintext.is_empty() && counter > 0 {
+            return Err(malformed("empty final chunk"));
+        }
+        writer.write_all(&plaintext)?;
+        total += plaintext.len() as u64;
+        if last {
+            break;
+        }
+        current = next;
+    }
+
+    writer.flush()?;
+    Ok(total)
+}
+
+struct Stanza {
+    kind: String,
+    args: Vec<String>,
+    body: Vec<u8>,
+}
+
+impl Stanza {
+    // The body is wrapped at 64 columns and always ends with a short line
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(format!("-> {} {}\n", self.kind, self.args.join(" ")).as_bytes());
+        let body = STANDARD_NO_PAD.encode(&self.body);
+        let mut lines = body.as_bytes().chunks(COLUMNS).peekable();
+        while let Some(line) = lines.next() {
+            out.extend_from_slice(line);
+            out.push(b'\n');
+            if lines.peek().is_none() && line.len() == COLUMNS {
+                out.push(b'\n');
+            }
+        }
+        if body.is_empty() {
+            out.push(b'\n');
+        }
+    }
+}
+
+struct Header {
+    stanzas: Vec<Stanza>,
+    /// Everything up to and including the `---` that begins the MAC line
+    authenticated: Vec<u8>,
+    mac: Vec<u8>,
+}
+
+impl Header {
+    // Reads up to the end of the MAC line, leaving the payload in `reader`
+    fn read<R: BufRead>(reader: &mut R) -> Result<Self> {
+        let mut authenticated = Vec::new();
+        if next_line(reader, &mut authenticated)? != INTRO.as_bytes() {
+            return Err(malformed("not an age v1 file"));
+        }
+
+        let mut stanzas = Vec::new();
+        loop {
+            let line = next_line(reader, &mut authenticated)?;
+            if let Some(encoded) = line.strip_prefix(b"--- ") {
+                let mac = decode_base64(encoded)?;
+                if mac.len() != 32 {
+                    return Err(malformed("invalid header MAC"));
+                }
+                // Only the `---` of the MAC line is authenticated
+                authenticated.truncate(authenticated.len() - line.len() - 1 + 3);
+                return Ok(Self { stanzas, authenticated, mac });
+            }
+
+            let line = line.strip_prefix(b"-> ").ok_or_else(|| malformed("invalid header line"))?;
+            let line = std::str::from_utf8(line).map_err(|_| malformed("invalid stanza"))?;
+            let mut args: Vec<String> = line.split(' ').map(str::to_string).collect();
+            if args.iter().any(|arg| arg.is_empty() || !arg.bytes().all(|byte| byte.is_ascii_graphic())) {
+                return Err(malformed("invalid stanza argument"));
+            }
+            let kind = args.remove(0);
+
+            let mut body = Vec::new();
+            loop {
+                let line = next_line(reader, &mut authenticated)?;
+                if line.len() > COLUMNS {
+                    return Err(malformed("stanza body line is too long"));
+                }
+                body.extend(decode_base64(&line)?);
+                if line.len() < COLUMNS {
+                    break;
+                }
+            }
+            stanzas.push(Stanza { kind, args, body });
+        }
+    }
+}
+
+fn wrap(recipient: &Recipient, file_key: &[u8; FILE_KEY_SIZE]) -> Result<Stanza> {
+    match recipient {
+        Recipient::X25519(recipient) => {
+            let ephemeral = x25519::generate_secret()?;
+            let share = x25519::public_key(&ephemeral);
+            let shared = x25519::shared_secret(&ephemeral, &recipient.public)?;
+            let key = x25519_wrap_key(&shared, &share, &recipient.public)?;
+            Ok(Stanza {
+                kind: "X25519".to_string(),
+                args: vec![STANDARD_NO_PAD.encode(share)],
+                body: ChaCha20Poly1305::new(&key)?.encrypt(&[0; chacha20poly1305::NONCE_SIZE], &[], file_key)?,
+            })
+        }
+        Recipient::Scrypt { passphrase, work_factor } => {
+            let salt = io::generate_nonce(SCRYPT_SALT_SIZE)?;
+            let key = scrypt_wrap_key(passphrase, &salt, *work_factor)?;
+            Ok(Stanza {
+                kind: "scrypt".to_string(),
+                args: vec![STANDARD_NO_PAD.encode(&salt), work_factor.to_string()],
+                body: ChaCha20Poly1305::new(&key)?.encrypt(&[0; chacha20poly1305::NONCE_SIZE], &[], file_key)?,
+            })
+        }
+    }
+}
+
+// Ok(None) when the stanza is not for this identity; unknown stanza types
+// are skipped, as `age` does
+fn unwrap(identity: &Identity, stanza: &Stanza) -> Result<Option<[u8; FILE_KEY_SIZE]>> {
+    let key = match (identity, stanza.kind.as_str()) {
+        (Identity::X25519(identity), "X25519") => {
+            let [share] = stanza.args.as_slice() else {
+                return Err(malformed("invalid X25519 stanza"));
+            };
+            let share: [u8; x25519::KEY_SIZE] =
+                decode_base64(share.as_bytes())?.try_into().map_err(|_| malformed("invalid X25519 share"))?;
+            let public = x25519::public_key(&identity.secret);
+            let shared = x25519::shared_secret(&identity.secret, &share)?;
+            x25519_wrap_key(&shared, &share, &public)?
+        }
+        (Identity::Scrypt { passphrase, max_work_factor }, "scrypt") => {
+            let [salt, work_factor] = stanza.args.as_slice() else {
+                return Err(malformed("invalid scrypt stanza"));
+            };
+            let salt = decode_base64(salt.as_bytes())?;
+            if salt.len() != SCRYPT_SALT_SIZE {
+                return Err(malformed("invalid scrypt salt"));
+            }
+            // Decimal without leading zeros, as the specification requires
+            let work_factor = work_factor
+                .parse::<u8>()
+                .ok()
+                .filter(|factor| *factor > 0 && !work_factor.starts_with('0'))
+                .ok_or_else(|| malformed("invalid scrypt work factor"))?;
+            if work_factor > *max_work_factor {
+                return Err(CryptoCoreError::InvalidArgument(format!(
+                    "age scrypt work factor {} exceeds the maximum of {}",
+                    work_factor, max_work_factor
+                )));
+            }
+            scrypt_wrap_key(passphrase, &salt, work_factor)?
+        }
+        _ => return Ok(None),
+    };
+
+    if stanza.body.len() != WRAPPED_KEY_SIZE {
+        return Err(malformed("invalid wrapped file key"));
+    }
+    let file_key = ChaCha20Poly1305::new(&key)?.decrypt(&[0; chacha20poly1305::NONCE_SIZE], &[], &stanza.body);
+    match (file_key, identity) {
+        (Ok(file_key), _) => Ok(Some(file_key.try_into().unwrap())),
+        // Only one scrypt stanza is allowed, so failing to open it means a wrong passphrase
+        (Err(_), Identity::Scrypt { .. }) => Err(CryptoCoreError::AuthenticationFailed(
+            "Incorrect age passphrase".to_string(),
+        )),
+        (Err(_), Identity::X25519(_)) => Ok(None),
+    }
+}
+
+fn x25519_wrap_key(shared: &[u8], share: &[u8], public: &[u8]) -> Result<Vec<u8>> {
+    let salt = [share, public].concat();
+    kdf::hkdf(KdfAlgorithm::HkdfSha256, shared, Some(&salt), X25519_LABEL, chacha20poly1305::KEY_SIZE)
+}
+
+fn scrypt_wrap_key(passphrase: &[u8], salt: &[u8], work_factor: u8) -> Result<Vec<u8>> {
+    let salt = [SCRYPT_LABEL, salt].concat();
+    kdf::scrypt(passphrase, &salt, work_factor, 8, 1, chacha20poly1305::KEY_SIZE)
+}
+
+fn header_key(file_key: &[u8]) -> Result<Vec<u8>> {
+    kdf::hkdf(KdfAlgorithm::HkdfSha256, file_key, Some(&[]), b"header", 32)
+}
+
+fn payload_key(file_key: &[u8], nonce: &[u8]) -> Result<Vec<u8>> {
+    kdf::hkdf(KdfAlgorithm::HkdfSha256, file_key, Some(nonce), b"payload", chacha20poly1305::KEY_SIZE)
+}
+
+// STREAM nonce: an 11-byte big-endian chunk counter, then 1 for the last chunk
+fn chunk_nonce(counter: u64, last: bool) -> [u8; chacha20poly1305::NONCE_SIZE] {
+    let mut nonce = [0u8; chacha20poly1305::NONCE_SIZE];
+    nonce[3..11].copy_from_slice(&counter.to_be_bytes());
+    nonce[11] = u8::from(last);
+    nonce
+}
+
+// Up to `size` bytes, fewer only at the end of the input
+fn read_chunk<R: Read>(reader: &mut R, size: usize) -> Result<Vec<u8>> {
+    let mut chunk = Vec::with_capacity(size);
+    reader.by_ref().take(size as u64).read_to_end(&mut chunk)?;
+    Ok(chunk)
+}
+
+fn decode_key(encoded: &str, hrp: &str) -> Result<[u8; x25519::KEY_SIZE]> {
+    let (decoded_hrp, key) = bech32::decode(encoded.trim())?;
+    if decoded_hrp != hrp {
+        return Err(CryptoCoreError::InvalidKey(format!(
+            "Expected an age key starting with {}1",
+            if hrp == IDENTITY_HRP { "AGE-SECRET-KEY-" } else { hrp }
+        )));
+    }
+    key.try_into()
+        .map_err(|_| CryptoCoreError::InvalidKey("age key must be 32 bytes".to_string()))
+}
+
+// Canonical unpadded base64, as the specification requires
+fn decode_base64(encoded: &[u8]) -> Result<Vec<u8>> {
+    STANDARD_NO_PAD.decode(encoded).map_err(|_| malformed("invalid base64"))
+}
+
+// The next line without its newline, also appended (with it) to `header`
+fn next_line<R: BufRead>(reader: &mut R, header: &mut Vec<u8>) -> Result<Vec<u8>> {
+    let mut line = Vec::new();
+    reader.by_ref().take(MAX_HEADER_LINE as u64 + 1).read_until(b'\n', &mut line)?;
+    if line.pop() != Some(b'\n') {
+        return Err(malformed(if line.len() >= MAX_HEADER_LINE { "header line is too long" } else { "truncated header" }));
+    }
+    header.extend_from_slice(&line);
+    header.push(b'\n');
+    Ok(line)
+}
+
+fn malformed(reason: &str) -> CryptoCoreError {
+    CryptoCoreError::FileError(format!("Malformed age file: {}", reason))
+}
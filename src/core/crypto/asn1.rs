@@ -0,0 +1,111 @@
+//! Just enough DER (X.690) to read and write key files: definite lengths and
+//! the handful of universal types that SubjectPublicKeyInfo, PKCS#1 and PKCS#8
+//! use.
+
+use crate::error::{CryptoCoreError, Result};
+
+pub(crate) const INTEGER: u8 = 0x02;
+pub(crate) const BIT_STRING: u8 = 0x03;
+pub(crate) const OCTET_STRING: u8 = 0x04;
+pub(crate) const NULL: u8 = 0x05;
+pub(crate) const OID: u8 = 0x06;
+pub(crate) const SEQUENCE: u8 = 0x30;
+
+/// Reads consecutive elements from a DER buffer.
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// The contents of the next element, which must have tag `tag`.
+    pub fn read(&mut self, tag: u8) -> Result<&'a [u8]> {
+        let (&actual, rest) = self.data.split_first().ok_or_else(|| malformed("unexpected end of data"))?;
+        if actual != tag {
+            return Err(malformed("unexpected element"));
+        }
+
+        let (&first, mut rest) = rest.split_first().ok_or_else(|| malformed("missing length"))?;
+        let length = match first {
+            0..=0x7f => usize::from(first),
+            // Long form, at most four length octets and never for short lengths
+            0x81..=0x84 => {
+                let count = usize::from(first & 0x7f);
+                let octets = rest.get(..count).ok_or_else(|| malformed("truncated length"))?;
+                rest = &rest[count..];
+                let length = octets.iter().fold(0usize, |length, &byte| (length << 8) | usize::from(byte));
+                if length < 0x80 || octets[0] == 0 {
+                    return Err(malformed("non-minimal length"));
+                }
+                length
+            }
+            _ => return Err(malformed("unsupported length")),
+        };
+
+        let contents = rest.get(..length).ok_or_else(|| malformed("truncated element"))?;
+        self.data = &rest[length..];
+        Ok(contents)
+    }
+
+    pub fn sequence(&mut self) -> Result<Reader<'a>> {
+        self.read(SEQUENCE).map(Reader::new)
+    }
+
+    /// A non-negative INTEGER as big-endian bytes, without the sign octet.
+    pub fn unsigned_integer(&mut self) -> Result<&'a [u8]> {
+        let contents = self.read(INTEGER)?;
+        match contents {
+            [] => Err(malformed("empty integer")),
+            [first, ..] if first & 0x80 != 0 => Err(malformed("negative integer")),
+            [0, rest @ ..] if !rest.is_empty() => Ok(rest),
+            _ => Ok(contents),
+        }
+    }
+
+    /// A BIT STRING that holds whole octets.
+    pub fn bit_string(&mut self) -> Result<&'a [u8]> {
+        match self.read(BIT_STRING)? {
+            [0, bytes @ ..] => Ok(bytes),
+            _ => Err(malformed("bit string with unused bits")),
+        }
+    }
+}
+
+pub(crate) fn tlv(tag: u8, contents: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    match contents.len() {
+        length @ 0..0x80 => out.push(length as u8),
+        length => {
+            let octets: Vec<u8> = length.to_be_bytes().into_iter().skip_while(|&byte| byte == 0).collect();
+            out.push(0x80 | octets.len() as u8);
+            out.extend(octets);
+        }
+    }
+    out.extend_from_slice(contents);
+    out
+}
+
+pub(crate) fn sequence(elements: &[&[u8]]) -> Vec<u8> {
+    tlv(SEQUENCE, &elements.concat())
+}
+
+/// A non-negative INTEGER from big-endian bytes.
+pub(crate) fn unsigned_integer(bytes: &[u8]) -> Vec<u8> {
+    let start = bytes.iter().position(|&byte| byte != 0).unwrap_or(bytes.len());
+    let mut contents = bytes[start..].to_vec();
+    if contents.first().is_none_or(|&byte| byte & 0x80 != 0) {
+        contents.insert(0, 0);
+    }
+    tlv(INTEGER, &contents)
+}
+
+pub(crate) fn bit_string(bytes: &[u8]) -> Vec<u8> {
+    tlv(BIT_STRING, &[&[0], bytes].concat())
+}
+
+fn malformed(reason: &str) -> CryptoCoreError {
+    CryptoCoreError::InvalidKey(format!("Malformed DER key: {}", reason))
+}
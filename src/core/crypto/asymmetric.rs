@@ -0,0 +1,378 @@
+//! Public and private keys in the PEM formats `openssl` reads and writes
+//! (SubjectPublicKeyInfo and PKCS#8). X25519 and RSA keys wrap a content key
+//! for a recipient:
+//!
+//! - X25519: `ephemeral public key (32) | AES-KW(kek, content key)`, where the
+//!   KEK is HKDF-SHA256 of the shared secret, salted with both public keys
+//! - RSA: RSAES-OAEP with SHA-256 of the content key
+//!
+//! Ed25519 and P-256 keys sign instead: Ed25519 signatures are the 64 raw
+//! bytes, ECDSA signatures the DER encoding, as with `openssl pkeyutl` and
+//! `openssl dgst -sha256 -sign`.
+
+use crate::core::crypto::asn1::{self, Reader};
+use crate::core::crypto::curve25519::{ed25519, x25519};
+use crate::core::crypto::kdf::{self, KdfAlgorithm};
+use crate::core::crypto::keywrap;
+use crate::core::crypto::p256;
+use crate::core::crypto::rsa::{RsaPrivateKey, RsaPublicKey};
+use crate::core::io::pem;
+use crate::error::{CryptoCoreError, Result};
+use std::fmt;
+
+// 1.2.840.113549.1.1.1, 1.3.101.110 and 1.3.101.112
+const OID_RSA_ENCRYPTION: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+const OID_X25519: &[u8] = &[0x2b, 0x65, 0x6e];
+const OID_ED25519: &[u8] = &[0x2b, 0x65, 0x70];
+// id-ecPublicKey (1.2.840.10045.2.1) with the named curve prime256v1 (1.2.840.10045.3.1.7)
+const OID_EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+const OID_PRIME256V1: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+
+// The optional [0] parameters and [1] public key of a SEC 1 ECPrivateKey
+const EC_PARAMETERS_TAG: u8 = 0xa0;
+const EC_PUBLIC_KEY_TAG: u8 = 0xa1;
+
+const X25519_WRAP_INFO: &[u8] = b"cryptocore x25519 key wrap";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAlgorithm {
+    X25519 = 1,
+    Rsa = 2,
+    Ed25519 = 3,
+    P256 = 4,
+}
+
+impl KeyAlgorithm {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "x25519" => Ok(Self::X25519),
+            "rsa" => Ok(Self::Rsa),
+            "ed25519" => Ok(Self::Ed25519),
+            "p256" => Ok(Self::P256),
+            _ => Err(CryptoCoreError::InvalidArgument(format!("Unsupported key algorithm: {}", name))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::X25519 => "x25519",
+            Self::Rsa => "rsa",
+            Self::Ed25519 => "ed25519",
+            Self::P256 => "p256",
+        }
+    }
+
+    /// Signature keys; the others encrypt to recipients.
+    pub fn can_sign(&self) -> bool {
+        matches!(self, Self::Ed25519 | Self::P256)
+    }
+
+    pub fn id(&self) -> u8 {
+        *self as u8
+    }
+
+    pub fn from_id(id: u8) -> Result<Self> {
+        match id {
+            1 => Ok(Self::X25519),
+            2 => Ok(Self::Rsa),
+            3 => Ok(Self::Ed25519),
+            4 => Ok(Self::P256),
+            _ => Err(CryptoCoreError::FileError(format!("Unsupported key algorithm id: {}", id))),
+        }
+    }
+}
+
+impl fmt::Display for KeyAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PublicKey {
+    X25519([u8; x25519::KEY_SIZE]),
+    Rsa(RsaPublicKey),
+    Ed25519([u8; ed25519::PUBLIC_KEY_SIZE]),
+    P256([u8; p256::PUBLIC_KEY_SIZE]),
+}
+
+impl PublicKey {
+    pub fn algorithm(&self) -> KeyAlgorithm {
+        match self {
+            Self::X25519(_) => KeyAlgorithm::X25519,
+            Self::Rsa(_) => KeyAlgorithm::Rsa,
+            Self::Ed25519(_) => KeyAlgorithm::Ed25519,
+            Self::P256(_) => KeyAlgorithm::P256,
+        }
+    }
+
+    /// Reads a `PUBLIC KEY` (SubjectPublicKeyInfo) or `RSA PUBLIC KEY` (PKCS#1) PEM file.
+    pub fn from_pem(text: &str) -> Result<Self> {
+        match pem::decode(text)? {
+            (label, der) if label == pem::PUBLIC_KEY => Self::from_spki_der(&der),
+            (label, der) if label == pem::RSA_PUBLIC_KEY => Ok(Self::Rsa(RsaPublicKey::from_pkcs1_der(&der)?)),
+            (label, _) => Err(not_a_key(&label, "public")),
+        }
+    }
+
+    pub fn from_spki_der(der: &[u8]) -> Result<Self> {
+        let mut spki = Reader::new(der).sequence()?;
+        let algorithm = read_algorithm(&mut spki)?;
+        let key = spki.bit_string()?;
+        match algorithm {
+            KeyAlgorithm::X25519 => Ok(Self::X25519(key.try_into().map_err(|_| invalid_x25519())?)),
+            KeyAlgorithm::Rsa => Ok(Self::Rsa(RsaPublicKey::from_pkcs1_der(key)?)),
+            KeyAlgorithm::Ed25519 => Ok(Self::Ed25519(key.try_into().map_err(|_| invalid_ed25519())?)),
+            KeyAlgorithm::P256 => Ok(Self::P256(p256::decode_public_key(key)?)),
+        }
+    }
+
+    pub fn to_spki_der(&self) -> Vec<u8> {
+        let key = match self {
+            Self::X25519(public) => public.to_vec(),
+            Self::Rsa(public) => public.to_pkcs1_der(),
+            Self::Ed25519(public) => public.to_vec(),
+            Self::P256(public) => public.to_vec(),
+        };
+        asn1::sequence(&[&algorithm_identifier(self.algorithm()), &asn1::bit_string(&key)])
+    }
+
+    pub fn to_pem(&self) -> String {
+        pem::encode(pem::PUBLIC_KEY, &self.to_spki_der())
+    }
+
+    /// Encrypts `key` so that only the holder of the private key can recover it.
+    pub fn wrap_key(&self, key: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::X25519(public) => {
+                let ephemeral = x25519::generate_secret()?;
+                let ephemeral_public = x25519::public_key(&ephemeral);
+                let kek = x25519_kek(&x25519::shared_secret(&ephemeral, public)?, &ephemeral_public, public)?;
+                Ok([&ephemeral_public[..], &keywrap::wrap(&kek, key)?].concat())
+            }
+            Self::Rsa(public) => public.encrypt_oaep(key),
+            Self::Ed25519(_) | Self::P256(_) => Err(cannot_encrypt(self.algorithm())),
+        }
+    }
+
+    /// Checks a signature made with [`PrivateKey::sign`] over `message`.
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<()> {
+        match self {
+            Self::Ed25519(public) => ed25519::verify(public, message, signature),
+            Self::P256(public) => p256::verify(public, message, signature),
+            Self::X25519(_) | Self::Rsa(_) => Err(cannot_sign(self.algorithm())),
+        }
+    }
+}
+
+pub enum PrivateKey {
+    X25519([u8; x25519::KEY_SIZE]),
+    Rsa(Box<RsaPrivateKey>),
+    Ed25519([u8; ed25519::SEED_SIZE]),
+    P256([u8; p256::SCALAR_SIZE]),
+}
+
+// Names the key type without printing the secret
+impl fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PrivateKey({})", self.algorithm())
+    }
+}
+
+impl PrivateKey {
+    /// A fresh key; `rsa_bits` is only used for RSA.
+    pub fn generate(algorithm: KeyAlgorithm, rsa_bits: usize) -> Result<Self> {
+        match algorithm {
+            KeyAlgorithm::X25519 => Ok(Self::X25519(x25519::generate_secret()?)),
+            KeyAlgorithm::Rsa => Ok(Self::Rsa(Box::new(RsaPrivateKey::generate(rsa_bits)?))),
+            KeyAlgorithm::Ed25519 => Ok(Self::Ed25519(ed25519::generate_seed()?)),
+            KeyAlgorithm::P256 => Ok(Self::P256(p256::generate_secret()?)),
+        }
+    }
+
+    pub fn algorithm(&self) -> KeyAlgorithm {
+        match self {
+            Self::X25519(_) => KeyAlgorithm::X25519,
+            Self::Rsa(_) => KeyAlgorithm::Rsa,
+            Self::Ed25519(_) => KeyAlgorithm::Ed25519,
+            Self::P256(_) => KeyAlgorithm::P256,
+        }
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        match self {
+            Self::X25519(secret) => PublicKey::X25519(x25519::public_key(secret)),
+            Self::Rsa(private) => PublicKey::Rsa(private.public_key().clone()),
+            Self::Ed25519(seed) => PublicKey::Ed25519(ed25519::public_key(seed)),
+            Self::P256(secret) => PublicKey::P256(p256::public_key(secret)),
+        }
+    }
+
+    /// Reads a `PRIVATE KEY` (unencrypted PKCS#8), `RSA PRIVATE KEY` (PKCS#1) or
+    /// `EC PRIVATE KEY` (SEC 1) PEM file.
+    pub fn from_pem(text: &str) -> Result<Self> {
+        match pem::decode(text)? {
+            (label, der) if label == pem::PRIVATE_KEY => Self::from_pkcs8_der(&der),
+            (label, der) if label == pem::RSA_PRIVATE_KEY => {
+                Ok(Self::Rsa(Box::new(RsaPrivateKey::from_pkcs1_der(&der)?)))
+            }
+            (label, der) if label == pem::EC_PRIVATE_KEY => Ok(Self::P256(read_ec_private_key(&der)?)),
+            (label, _) if label == "ENCRYPTED PRIVATE KEY" => Err(CryptoCoreError::InvalidKey(
+                "Encrypted private keys are not supported; decrypt it with `openssl pkey` first".to_string(),
+            )),
+            (label, _) => Err(not_a_key(&label, "private")),
+        }
+    }
+
+    pub fn from_pkcs8_der(der: &[u8]) -> Result<Self> {
+        let mut info = Reader::new(der).sequence()?;
+        // Version 1 (RFC 5958) may add the public key after the private key
+        if !matches!(info.unsigned_integer()?, [0] | [1]) {
+            return Err(CryptoCoreError::InvalidKey("Unsupported PKCS#8 version".to_string()));
+        }
+        let algorithm = read_algorithm(&mut info)?;
+        let key = info.read(asn1::OCTET_STRING)?;
+        match algorithm {
+            KeyAlgorithm::X25519 => {
+                let secret = Reader::new(key).read(asn1::OCTET_STRING)?;
+                Ok(Self::X25519(secret.try_into().map_err(|_| invalid_x25519())?))
+            }
+            KeyAlgorithm::Rsa => Ok(Self::Rsa(Box::new(RsaPrivateKey::from_pkcs1_der(key)?))),
+            KeyAlgorithm::Ed25519 => {
+                let seed = Reader::new(key).read(asn1::OCTET_STRING)?;
+                Ok(Self::Ed25519(seed.try_into().map_err(|_| invalid_ed25519())?))
+            }
+            KeyAlgorithm::P256 => Ok(Self::P256(read_ec_private_key(key)?)),
+        }
+    }
+
+    pub fn to_pkcs8_der(&self) -> Vec<u8> {
+        let key = match self {
+            Self::X25519(secret) => asn1::tlv(asn1::OCTET_STRING, secret),
+            Self::Rsa(private) => private.to_pkcs1_der(),
+            Self::Ed25519(seed) => asn1::tlv(asn1::OCTET_STRING, seed),
+            // openssl includes the public key, but not the curve that the PKCS#8 wrapper names
+            Self::P256(secret) => asn1::sequence(&[
+                &asn1::unsigned_integer(&[1]),
+                &asn1::tlv(asn1::OCTET_STRING, secret),
+                &asn1::tlv(EC_PUBLIC_KEY_TAG, &asn1::bit_string(&p256::public_key(secret))),
+            ]),
+        };
+        asn1::sequence(&[
+            &asn1::unsigned_integer(&[0]),
+            &algorithm_identifier(self.algorithm()),
+            &asn1::tlv(asn1::OCTET_STRING, &key),
+        ])
+    }
+
+    pub fn to_pem(&self) -> String {
+        pem::encode(pem::PRIVATE_KEY, &self.to_pkcs8_der())
+    }
+
+    /// Recovers a key wrapped with [`PublicKey::wrap_key`] for this key's public half.
+    pub fn unwrap_key(&self, wrapped: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::X25519(secret) => {
+                if wrapped.len() < x25519::KEY_SIZE {
+                    return Err(CryptoCoreError::FileError("Truncated X25519 wrapped key".to_string()));
+                }
+                let (ephemeral_public, wrapped) = wrapped.split_at(x25519::KEY_SIZE);
+                let ephemeral_public: [u8; x25519::KEY_SIZE] = ephemeral_public.try_into().unwrap();
+                let shared = x25519::shared_secret(secret, &ephemeral_public)?;
+                let kek = x25519_kek(&shared, &ephemeral_public, &x25519::public_key(secret))?;
+                keywrap::unwrap(&kek, wrapped)
+            }
+            Self::Rsa(private) => private.decrypt_oaep(wrapped),
+            Self::Ed25519(_) | Self::P256(_) => Err(cannot_encrypt(self.algorithm())),
+        }
+    }
+
+    pub fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::Ed25519(seed) => Ok(ed25519::sign(seed, message).to_vec()),
+            Self::P256(secret) => p256::sign(secret, message),
+            Self::X25519(_) | Self::Rsa(_) => Err(cannot_sign(self.algorithm())),
+        }
+    }
+}
+
+fn x25519_kek(shared: &[u8], ephemeral_public: &[u8], recipient_public: &[u8]) -> Result<Vec<u8>> {
+    let salt = [ephemeral_public, recipient_public].concat();
+    kdf::hkdf(KdfAlgorithm::HkdfSha256, shared, Some(&salt), X25519_WRAP_INFO, 32)
+}
+
+// The SEC 1 ECPrivateKey inside PKCS#8 or an `EC PRIVATE KEY` file; the public key is recomputed
+fn read_ec_private_key(der: &[u8]) -> Result<[u8; p256::SCALAR_SIZE]> {
+    let mut key = Reader::new(der).sequence()?;
+    if key.unsigned_integer()? != [1] {
+        return Err(CryptoCoreError::InvalidKey("Unsupported EC private key version".to_string()));
+    }
+    let secret = key.read(asn1::OCTET_STRING)?;
+    if let Ok(parameters) = key.read(EC_PARAMETERS_TAG) {
+        read_curve(&mut Reader::new(parameters))?;
+    }
+    p256::validate_secret(secret)
+}
+
+fn read_curve(reader: &mut Reader) -> Result<()> {
+    match reader.read(asn1::OID)? {
+        OID_PRIME256V1 => Ok(()),
+        _ => Err(CryptoCoreError::InvalidKey(
+            "Unsupported elliptic curve; only P-256 (prime256v1) keys are supported".to_string(),
+        )),
+    }
+}
+
+fn algorithm_identifier(algorithm: KeyAlgorithm) -> Vec<u8> {
+    match algorithm {
+        // RSA carries explicit NULL parameters; X25519 and Ed25519 have none (RFC 8410)
+        KeyAlgorithm::X25519 => asn1::sequence(&[&asn1::tlv(asn1::OID, OID_X25519)]),
+        KeyAlgorithm::Rsa => asn1::sequence(&[&asn1::tlv(asn1::OID, OID_RSA_ENCRYPTION), &asn1::tlv(asn1::NULL, &[])]),
+        KeyAlgorithm::Ed25519 => asn1::sequence(&[&asn1::tlv(asn1::OID, OID_ED25519)]),
+        KeyAlgorithm::P256 => {
+            asn1::sequence(&[&asn1::tlv(asn1::OID, OID_EC_PUBLIC_KEY), &asn1::tlv(asn1::OID, OID_PRIME256V1)])
+        }
+    }
+}
+
+fn read_algorithm(reader: &mut Reader) -> Result<KeyAlgorithm> {
+    let mut identifier = reader.sequence()?;
+    match identifier.read(asn1::OID)? {
+        OID_X25519 => Ok(KeyAlgorithm::X25519),
+        OID_RSA_ENCRYPTION => Ok(KeyAlgorithm::Rsa),
+        OID_ED25519 => Ok(KeyAlgorithm::Ed25519),
+        OID_EC_PUBLIC_KEY => {
+            read_curve(&mut identifier)?;
+            Ok(KeyAlgorithm::P256)
+        }
+        _ => Err(CryptoCoreError::InvalidKey(
+            "Unsupported key type; use an X25519, RSA, Ed25519 or P-256 key".to_string(),
+        )),
+    }
+}
+
+fn invalid_x25519() -> CryptoCoreError {
+    CryptoCoreError::InvalidKey("X25519 keys must be 32 bytes".to_string())
+}
+
+fn invalid_ed25519() -> CryptoCoreError {
+    CryptoCoreError::InvalidKey("Ed25519 keys must be 32 bytes".to_string())
+}
+
+fn cannot_encrypt(algorithm: KeyAlgorithm) -> CryptoCoreError {
+    CryptoCoreError::InvalidKey(format!(
+        "{} keys can only sign; encrypt to an X25519 or RSA key",
+        algorithm
+    ))
+}
+
+fn cannot_sign(algorithm: KeyAlgorithm) -> CryptoCoreError {
+    CryptoCoreError::InvalidKey(format!(
+        "{} keys cannot sign; use an Ed25519 or P-256 key",
+        algorithm
+    ))
+}
+
+fn not_a_key(label: &str, kind: &str) -> CryptoCoreError {
+    CryptoCoreError::InvalidKey(format!("Expected a {} key, found a PEM \"{}\" block", kind, label))
+}
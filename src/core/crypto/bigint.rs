@@ -0,0 +1,174 @@
+//! Arbitrary-precision unsigned integers for the numeral strings of
+//! format-preserving encryption.
+//!
+//! Division is bit-serial: it is only used for numeral conversion, on values
+//! no larger than the radix raised to the message length.
+
+use std::cmp::Ordering;
+
+/// Little-endian 64-bit limbs without high zero limbs, so zero has none.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct BigUint {
+    limbs: Vec<u64>,
+}
+
+impl BigUint {
+    pub fn from_u64(value: u64) -> Self {
+        Self::from_limbs(vec![value])
+    }
+
+    pub fn from_bytes_be(bytes: &[u8]) -> Self {
+        let limbs = bytes
+            .rchunks(8)
+            .map(|chunk| chunk.iter().fold(0u64, |limb, &byte| (limb << 8) | u64::from(byte)))
+            .collect();
+        Self::from_limbs(limbs)
+    }
+
+    /// Big-endian bytes left-padded to `length`; `None` if the value does not fit.
+    pub fn to_bytes_be_padded(&self, length: usize) -> Option<Vec<u8>> {
+        if self.bits().div_ceil(8) > length {
+            return None;
+        }
+        let mut bytes = vec![0u8; length];
+        for index in 0..length.min(self.limbs.len() * 8) {
+            bytes[length - 1 - index] = (self.limbs[index / 8] >> (8 * (index % 8))) as u8;
+        }
+        Some(bytes)
+    }
+
+    fn from_limbs(mut limbs: Vec<u64>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        Self { limbs }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn bits(&self) -> usize {
+        match self.limbs.last() {
+            Some(top) => self.limbs.len() * 64 - top.leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    pub fn bit(&self, index: usize) -> bool {
+        self.limbs.get(index / 64).is_some_and(|limb| (limb >> (index % 64)) & 1 == 1)
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        let (long, short) = if self.limbs.len() >= other.limbs.len() { (self, other) } else { (other, self) };
+        let mut limbs = Vec::with_capacity(long.limbs.len() + 1);
+        let mut carry = false;
+        for (i, &limb) in long.limbs.iter().enumerate() {
+            let (sum, overflow1) = limb.overflowing_add(short.limbs.get(i).copied().unwrap_or(0));
+            let (sum, overflow2) = sum.overflowing_add(u64::from(carry));
+            limbs.push(sum);
+            carry = overflow1 || overflow2;
+        }
+        limbs.push(u64::from(carry));
+        Self::from_limbs(limbs)
+    }
+
+    /// `self - other`; the caller guarantees `self >= other`.
+    pub fn sub(&self, other: &Self) -> Self {
+        debug_assert!(*self >= *other);
+        let mut limbs = self.limbs.clone();
+        sub_in_place(&mut limbs, &other.limbs);
+        Self::from_limbs(limbs)
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        let mut limbs = vec![0u64; self.limbs.len() + other.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u128;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let product = u128::from(a) * u128::from(b) + u128::from(limbs[i + j]) + carry;
+                limbs[i + j] = product as u64;
+                carry = product >> 64;
+            }
+            limbs[i + other.limbs.len()] = carry as u64;
+        }
+        Self::from_limbs(limbs)
+    }
+
+    /// Quotient and remainder; `divisor` must not be zero.
+    pub fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+        assert!(!divisor.is_zero(), "division by zero");
+        if self < divisor {
+            return (Self::default(), self.clone());
+        }
+
+        // Shift-and-subtract on a fixed-width remainder, one extra limb for the shifted-out bit
+        let width = divisor.limbs.len() + 1;
+        let mut quotient = vec![0u64; self.limbs.len()];
+        let mut remainder = vec![0u64; width];
+        for index in (0..self.bits()).rev() {
+            let mut carry = u64::from(self.bit(index));
+            for limb in remainder.iter_mut() {
+                let shifted_out = *limb >> 63;
+                *limb = (*limb << 1) | carry;
+                carry = shifted_out;
+            }
+            if !less_than(&remainder, &divisor.limbs) {
+                sub_in_place(&mut remainder, &divisor.limbs);
+                quotient[index / 64] |= 1 << (index % 64);
+            }
+        }
+        (Self::from_limbs(quotient), Self::from_limbs(remainder))
+    }
+
+    pub fn rem(&self, divisor: &Self) -> Self {
+        self.div_rem(divisor).1
+    }
+
+    /// Limb-at-a-time division by a small divisor, for digit extraction.
+    pub fn div_rem_u64(&self, divisor: u64) -> (Self, u64) {
+        let mut quotient = vec![0u64; self.limbs.len()];
+        let mut remainder = 0u128;
+        for (index, &limb) in self.limbs.iter().enumerate().rev() {
+            let current = (remainder << 64) | u128::from(limb);
+            quotient[index] = (current / u128::from(divisor)) as u64;
+            remainder = current % u128::from(divisor);
+        }
+        (Self::from_limbs(quotient), remainder as u64)
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// a < b, for a at least as long as b
+fn less_than(a: &[u64], b: &[u64]) -> bool {
+    if a[b.len()..].iter().any(|&limb| limb != 0) {
+        return false;
+    }
+    a[..b.len()].iter().rev().lt(b.iter().rev())
+}
+
+// a -= b over a's length, returning the borrow out
+fn sub_in_place(a: &mut [u64], b: &[u64]) -> bool {
+    let mut borrow = false;
+    for (i, limb) in a.iter_mut().enumerate() {
+        let (difference, borrow1) = limb.overflowing_sub(b.get(i).copied().unwrap_or(0));
+        let (difference, borrow2) = difference.overflowing_sub(u64::from(borrow));
+        *limb = difference;
+        borrow = borrow1 || borrow2;
+    }
+    borrow
+}
@@ -0,0 +1,40 @@
+//! ChaCha20-Poly1305 AEAD (RFC 8439), on top of the `chacha20poly1305`
+//! crate. The tag is appended to the ciphertext.
+
+use crate::error::{CryptoCoreError, Result};
+use ::chacha20poly1305::aead::{Aead, KeyInit, Payload};
+
+pub const KEY_SIZE: usize = 32;
+pub const NONCE_SIZE: usize = 12;
+pub const TAG_SIZE: usize = 16;
+
+pub struct ChaCha20Poly1305 {
+    aead: ::chacha20poly1305::ChaCha20Poly1305,
+}
+
+impl ChaCha20Poly1305 {
+    pub fn new(key: &[u8]) -> Result<Self> {
+        let aead = ::chacha20poly1305::ChaCha20Poly1305::new_from_slice(key).map_err(|_| {
+            CryptoCoreError::InvalidKey("ChaCha20-Poly1305 key must be 32 bytes".to_string())
+        })?;
+        Ok(Self { aead })
+    }
+
+    pub fn encrypt(&self, nonce: &[u8; NONCE_SIZE], aad: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+        self.aead
+            .encrypt(nonce.into(), Payload { msg: data, aad })
+            .map_err(|_| CryptoCoreError::InvalidArgument("ChaCha20-Poly1305 input is too long".to_string()))
+    }
+
+    /// Verifies the tag before decrypting, so no unauthenticated plaintext is released.
+    pub fn decrypt(&self, nonce: &[u8; NONCE_SIZE], aad: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+        if data.len() < TAG_SIZE {
+            return Err(CryptoCoreError::Crypto(
+                "Ciphertext is too short to contain the Poly1305 tag".to_string(),
+            ));
+        }
+        self.aead
+            .decrypt(nonce.into(), Payload { msg: data, aad })
+            .map_err(|_| CryptoCoreError::AuthenticationFailed("Poly1305 tag mismatch".to_string()))
+    }
+}
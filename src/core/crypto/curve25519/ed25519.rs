@@ -0,0 +1,32 @@
+//! Ed25519 signatures (RFC 8032), the pure variant that signs the message
+//! itself rather than its digest, on top of `ed25519-dalek`.
+
+use crate::core::io;
+use crate::error::{CryptoCoreError, Result};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+
+pub const SEED_SIZE: usize = 32;
+pub const PUBLIC_KEY_SIZE: usize = 32;
+pub const SIGNATURE_SIZE: usize = 64;
+
+pub fn generate_seed() -> Result<[u8; SEED_SIZE]> {
+    Ok(io::generate_key(SEED_SIZE)?.try_into().unwrap())
+}
+
+pub fn public_key(seed: &[u8; SEED_SIZE]) -> [u8; PUBLIC_KEY_SIZE] {
+    SigningKey::from_bytes(seed).verifying_key().to_bytes()
+}
+
+pub fn sign(seed: &[u8; SEED_SIZE], message: &[u8]) -> [u8; SIGNATURE_SIZE] {
+    SigningKey::from_bytes(seed).sign(message).to_bytes()
+}
+
+/// Checks `[S]B = R + [k]A` (the cofactorless equation of section 5.1.7),
+/// refusing an S that is not reduced and small-order keys or R.
+pub fn verify(public: &[u8; PUBLIC_KEY_SIZE], message: &[u8], signature: &[u8]) -> Result<()> {
+    let failed = || CryptoCoreError::AuthenticationFailed("Signature verification failed".to_string());
+    let signature = Signature::from_slice(signature).map_err(|_| failed())?;
+    VerifyingKey::from_bytes(public)
+        .and_then(|key| key.verify_strict(message, &signature))
+        .map_err(|_| failed())
+}
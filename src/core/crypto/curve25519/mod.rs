@@ -0,0 +1,4 @@
+//! Curve25519: X25519 key agreement and Ed25519 signatures.
+
+pub mod ed25519;
+pub mod x25519;
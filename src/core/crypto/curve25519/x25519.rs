@@ -0,0 +1,35 @@
+//! X25519 Diffie-Hellman (RFC 7748), on top of `x25519-dalek`.
+
+use crate::core::io;
+use crate::error::{CryptoCoreError, Result};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+pub const KEY_SIZE: usize = 32;
+
+/// The u-coordinate of the base point.
+pub const BASE_POINT: [u8; KEY_SIZE] = x25519_dalek::X25519_BASEPOINT_BYTES;
+
+/// Computes `scalar * point`, clamping the scalar as RFC 7748 requires.
+pub fn x25519(scalar: &[u8; KEY_SIZE], point: &[u8; KEY_SIZE]) -> [u8; KEY_SIZE] {
+    x25519_dalek::x25519(*scalar, *point)
+}
+
+pub fn public_key(secret: &[u8; KEY_SIZE]) -> [u8; KEY_SIZE] {
+    PublicKey::from(&StaticSecret::from(*secret)).to_bytes()
+}
+
+pub fn generate_secret() -> Result<[u8; KEY_SIZE]> {
+    Ok(io::generate_key(KEY_SIZE)?.try_into().unwrap())
+}
+
+/// The shared secret with `their_public`, rejecting the all-zero result that
+/// low-order points produce.
+pub fn shared_secret(secret: &[u8; KEY_SIZE], their_public: &[u8; KEY_SIZE]) -> Result<[u8; KEY_SIZE]> {
+    let shared = StaticSecret::from(*secret).diffie_hellman(&PublicKey::from(*their_public));
+    if !shared.was_contributory() {
+        return Err(CryptoCoreError::InvalidKey(
+            "X25519 public key is a low-order point".to_string(),
+        ));
+    }
+    Ok(shared.to_bytes())
+}
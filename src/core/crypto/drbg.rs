@@ -0,0 +1,238 @@
+//! CTR_DRBG (NIST SP 800-90A Rev. 1, section 10.2) over AES with the block
+//! cipher derivation function.
+//!
+//! A generator made with `CtrDrbg::new` seeds itself from the operating
+//! system and can reseed on its own, including before every request when
+//! prediction resistance is on. One made with `CtrDrbg::instantiate` is
+//! fully determined by its inputs: it has no entropy source, so it only
+//! reseeds with entropy handed to `reseed_with`.
+
+use crate::core::crypto::aes::AesCipher;
+use crate::error::{CryptoCoreError, Result};
+
+pub const BLOCK_SIZE: usize = 16;
+/// Bytes per `generate` call (2^19 bits)
+pub const MAX_REQUEST_SIZE: usize = 1 << 16;
+/// Requests between reseeds (2^48)
+pub const RESEED_INTERVAL: u64 = 1 << 48;
+/// Entropy, nonce, personalization and additional input are capped at 2^32 bits
+pub const MAX_INPUT_SIZE: usize = 1 << 29;
+
+pub struct CtrDrbg {
+    key_size: usize,
+    cipher: AesCipher,
+    v: u128,
+    reseed_counter: u64,
+    prediction_resistance: bool,
+    // Whether the generator may fetch entropy from the operating system
+    system_entropy: bool,
+}
+
+impl CtrDrbg {
+    /// Instantiates from system entropy at the strength of the AES key size
+    /// (16, 24 or 32 bytes).
+    pub fn new(key_size: usize, personalization: &[u8]) -> Result<Self> {
+        check_key_size(key_size)?;
+        let entropy = system_entropy(key_size)?;
+        let nonce = system_entropy(key_size / 2)?;
+        let mut drbg = Self::instantiate(key_size, &entropy, &nonce, personalization)?;
+        drbg.system_entropy = true;
+        Ok(drbg)
+    }
+
+    /// Instantiates from caller-supplied entropy and nonce. The output is
+    /// reproducible, so this is for known-answer tests and seeded runs.
+    pub fn instantiate(key_size: usize, entropy: &[u8], nonce: &[u8], personalization: &[u8]) -> Result<Self> {
+        check_key_size(key_size)?;
+        if entropy.len() < key_size {
+            return Err(CryptoCoreError::InvalidArgument(format!(
+                "CTR_DRBG needs at least {} bytes of entropy",
+                key_size
+            )));
+        }
+        check_input_size(&[entropy, nonce, personalization])?;
+
+        let mut drbg = Self {
+            key_size,
+            cipher: AesCipher::new(&vec![0u8; key_size])?,
+            v: 0,
+            reseed_counter: 1,
+            prediction_resistance: false,
+            system_entropy: false,
+        };
+        let seed = drbg.derive(&[entropy, nonce, personalization]);
+        drbg.update(&seed)?;
+        Ok(drbg)
+    }
+
+    /// Reseeds from system entropy before every request. Only generators
+    /// seeded by the system can do this.
+    pub fn set_prediction_resistance(&mut self, enabled: bool) -> Result<()> {
+        if enabled && !self.system_entropy {
+            return Err(CryptoCoreError::InvalidArgument(
+                "Prediction resistance needs a CTR_DRBG seeded from the system".to_string()
+            ));
+        }
+        self.prediction_resistance = enabled;
+        Ok(())
+    }
+
+    pub fn prediction_resistance(&self) -> bool {
+        self.prediction_resistance
+    }
+
+    pub fn key_size(&self) -> usize {
+        self.key_size
+    }
+
+    /// Reseeds from system entropy, mixing in `additional` input.
+    pub fn reseed(&mut self, additional: &[u8]) -> Result<()> {
+        if !self.system_entropy {
+            return Err(CryptoCoreError::Crypto(
+                "A seeded CTR_DRBG can only be reseeded with explicit entropy".to_string()
+            ));
+        }
+        let entropy = system_entropy(self.key_size)?;
+        self.reseed_with(&entropy, additional)
+    }
+
+    pub fn reseed_with(&mut self, entropy: &[u8], additional: &[u8]) -> Result<()> {
+        if entropy.len() < self.key_size {
+            return Err(CryptoCoreError::InvalidArgument(format!(
+                "CTR_DRBG needs at least {} bytes of entropy",
+                self.key_size
+            )));
+        }
+        check_input_size(&[entropy, additional])?;
+        let seed = self.derive(&[entropy, additional]);
+        self.update(&seed)?;
+        self.reseed_counter = 1;
+        Ok(())
+    }
+
+    /// Fills `output` (at most `MAX_REQUEST_SIZE` bytes) as one request.
+    pub fn generate(&mut self, output: &mut [u8], additional: &[u8]) -> Result<()> {
+        if output.len() > MAX_REQUEST_SIZE {
+            return Err(CryptoCoreError::InvalidArgument(format!(
+                "CTR_DRBG requests are limited to {} bytes",
+                MAX_REQUEST_SIZE
+            )));
+        }
+        check_input_size(&[additional])?;
+
+        // With a fresh reseed the additional input goes there instead
+        let mut additional = additional;
+        if self.prediction_resistance || self.reseed_counter > RESEED_INTERVAL {
+            self.reseed(additional)?;
+            additional = &[];
+        }
+
+        let additional = if additional.is_empty() {
+            vec![0u8; self.seed_size()]
+        } else {
+            let derived = self.derive(&[additional]);
+            self.update(&derived)?;
+            derived
+        };
+
+        for chunk in output.chunks_mut(BLOCK_SIZE) {
+            let block = self.next_block();
+            chunk.copy_from_slice(&block[..chunk.len()]);
+        }
+        self.update(&additional)?;
+        self.reseed_counter += 1;
+        Ok(())
+    }
+
+    /// Fills any amount of output, one request per `MAX_REQUEST_SIZE` bytes.
+    pub fn fill(&mut self, output: &mut [u8]) -> Result<()> {
+        output.chunks_mut(MAX_REQUEST_SIZE).try_for_each(|chunk| self.generate(chunk, &[]))
+    }
+
+    fn seed_size(&self) -> usize {
+        self.key_size + BLOCK_SIZE
+    }
+
+    fn next_block(&mut self) -> [u8; BLOCK_SIZE] {
+        self.v = self.v.wrapping_add(1);
+        let mut block = self.v.to_be_bytes();
+        self.cipher.encrypt_block(&mut block);
+        block
+    }
+
+    // CTR_DRBG_Update: new Key || V from the keystream xor provided data
+    fn update(&mut self, provided: &[u8]) -> Result<()> {
+        let mut temp = Vec::with_capacity(self.seed_size() + BLOCK_SIZE);
+        while temp.len() < self.seed_size() {
+            temp.extend_from_slice(&self.next_block());
+        }
+        temp.truncate(self.seed_size());
+        temp.iter_mut().zip(provided).for_each(|(byte, input)| *byte ^= input);
+
+        self.cipher = AesCipher::new(&temp[..self.key_size])?;
+        self.v = u128::from_be_bytes(temp[self.key_size..].try_into().unwrap());
+        Ok(())
+    }
+
+    // Block_Cipher_df (section 10.3.2) of the concatenated inputs, to seed size
+    fn derive(&self, inputs: &[&[u8]]) -> Vec<u8> {
+        let input_length: usize = inputs.iter().map(|input| input.len()).sum();
+        let mut s = Vec::with_capacity(8 + input_length + BLOCK_SIZE);
+        s.extend_from_slice(&(input_length as u32).to_be_bytes());
+        s.extend_from_slice(&(self.seed_size() as u32).to_be_bytes());
+        inputs.iter().for_each(|input| s.extend_from_slice(input));
+        s.push(0x80);
+        s.resize(s.len().next_multiple_of(BLOCK_SIZE), 0);
+
+        let df_key: Vec<u8> = (0..self.key_size as u8).collect();
+        let df_cipher = AesCipher::new(&df_key).unwrap();
+        let mut temp = Vec::with_capacity(self.seed_size() + BLOCK_SIZE);
+        for i in 0u32.. {
+            if temp.len() >= self.seed_size() {
+                break;
+            }
+            // BCC over IV || S, where IV is the counter padded to a block
+            let mut chain = [0u8; BLOCK_SIZE];
+            chain[..4].copy_from_slice(&i.to_be_bytes());
+            df_cipher.encrypt_block(&mut chain);
+            for block in s.chunks_exact(BLOCK_SIZE) {
+                chain.iter_mut().zip(block).for_each(|(byte, input)| *byte ^= input);
+                df_cipher.encrypt_block(&mut chain);
+            }
+            temp.extend_from_slice(&chain);
+        }
+
+        let cipher = AesCipher::new(&temp[..self.key_size]).unwrap();
+        let mut x: [u8; BLOCK_SIZE] = temp[self.key_size..self.seed_size()].try_into().unwrap();
+        let mut output = Vec::with_capacity(self.seed_size() + BLOCK_SIZE);
+        while output.len() < self.seed_size() {
+            cipher.encrypt_block(&mut x);
+            output.extend_from_slice(&x);
+        }
+        output.truncate(self.seed_size());
+        output
+    }
+}
+
+fn check_key_size(key_size: usize) -> Result<()> {
+    if ![16, 24, 32].contains(&key_size) {
+        return Err(CryptoCoreError::InvalidKey(
+            "CTR_DRBG uses AES with a 16, 24 or 32 bytes key".to_string()
+        ));
+    }
+    Ok(())
+}
+
+fn check_input_size(inputs: &[&[u8]]) -> Result<()> {
+    if inputs.iter().any(|input| input.len() > MAX_INPUT_SIZE) {
+        return Err(CryptoCoreError::InvalidArgument("CTR_DRBG input is too long".to_string()));
+    }
+    Ok(())
+}
+
+fn system_entropy(length: usize) -> Result<Vec<u8>> {
+    let mut entropy = vec![0u8; length];
+    getrandom::fill(&mut entropy)
+        .map_err(|e| CryptoCoreError::Crypto(format!("Failed to gather entropy: {}", e)))?;
+    Ok(entropy)
+}
@@ -0,0 +1,117 @@
+//! Fernet tokens, as produced by Python's `cryptography.fernet`.
+//!
+//! Token: `base64url(0x80 | timestamp (u64, big-endian) | IV | ciphertext | HMAC)`,
+//! where the ciphertext is AES-128-CBC with PKCS#7 padding and the HMAC is
+//! HMAC-SHA256 over everything before it. The 32-byte key is the signing key
+//! followed by the encryption key.
+
+use crate::core::crypto::mac::{self, MacAlgorithm};
+use crate::core::crypto::modes::cbc::CbcMode;
+use crate::core::crypto::traits::Cipher;
+use crate::core::io;
+use crate::error::{CryptoCoreError, Result};
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, Engine, GeneralPurpose, GeneralPurposeConfig};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const KEY_SIZE: usize = 32;
+
+const VERSION: u8 = 0x80;
+const IV_SIZE: usize = 16;
+const HMAC_SIZE: usize = 32;
+// version + timestamp + IV + one ciphertext block + HMAC
+const MIN_TOKEN_SIZE: usize = 1 + 8 + IV_SIZE + 16 + HMAC_SIZE;
+/// Tokens stamped further than this in the future are rejected, as in Python.
+const MAX_CLOCK_SKEW: u64 = 60;
+
+// Writes padded base64url like Python; accepts it with or without padding
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::URL_SAFE,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+pub struct Fernet {
+    signing_key: [u8; 16],
+    encryption_key: [u8; 16],
+}
+
+impl Fernet {
+    pub fn new(key: &[u8]) -> Result<Self> {
+        if key.len() != KEY_SIZE {
+            return Err(CryptoCoreError::InvalidKey(
+                "Fernet key must be 32 bytes".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            signing_key: key[..16].try_into().unwrap(),
+            encryption_key: key[16..].try_into().unwrap(),
+        })
+    }
+
+    /// Parses a key in the base64url form `Fernet.generate_key()` returns.
+    pub fn from_base64(key: &str) -> Result<Self> {
+        let key = BASE64.decode(key.trim()).map_err(|_| {
+            CryptoCoreError::InvalidKey("Fernet key must be base64url-encoded".to_string())
+        })?;
+        Self::new(&key)
+    }
+
+    pub fn generate_key() -> Result<String> {
+        Ok(BASE64.encode(io::generate_key(KEY_SIZE)?))
+    }
+
+    pub fn encrypt(&self, data: &[u8]) -> Result<String> {
+        self.encrypt_with(data, now(), &io::generate_iv()?)
+    }
+
+    /// Encrypts with an explicit timestamp and IV; only useful for reproducible output.
+    pub fn encrypt_with(&self, data: &[u8], timestamp: u64, iv: &[u8; 16]) -> Result<String> {
+        let mut token = vec![VERSION];
+        token.extend_from_slice(&timestamp.to_be_bytes());
+        token.extend_from_slice(iv);
+        token.extend(CbcMode::new(&self.encryption_key, iv)?.encrypt(data)?);
+
+        let hmac = mac::compute(MacAlgorithm::HmacSha256, &self.signing_key, &token)?;
+        token.extend(hmac);
+        Ok(BASE64.encode(token))
+    }
+
+    /// Decrypts a token, rejecting it if it is older than `ttl` seconds.
+    pub fn decrypt(&self, token: &str, ttl: Option<u64>) -> Result<Vec<u8>> {
+        self.decrypt_at_time(token, ttl, now())
+    }
+
+    pub fn decrypt_at_time(&self, token: &str, ttl: Option<u64>, now: u64) -> Result<Vec<u8>> {
+        let token = BASE64.decode(token.trim()).map_err(|_| invalid_token("not base64url"))?;
+        if token.len() < MIN_TOKEN_SIZE || token[0] != VERSION {
+            return Err(invalid_token("malformed token"));
+        }
+
+        let (signed, hmac) = token.split_at(token.len() - HMAC_SIZE);
+        mac::verify(MacAlgorithm::HmacSha256, &self.signing_key, signed, hmac)
+            .map_err(|_| invalid_token("signature mismatch"))?;
+
+        // As in Python, the timestamp only matters when a TTL is given
+        let timestamp = u64::from_be_bytes(signed[1..9].try_into().unwrap());
+        if let Some(ttl) = ttl {
+            if timestamp.saturating_add(ttl) < now {
+                return Err(invalid_token("token has expired"));
+            }
+            if timestamp > now.saturating_add(MAX_CLOCK_SKEW) {
+                return Err(invalid_token("timestamp is in the future"));
+            }
+        }
+
+        let (iv, ciphertext) = signed[9..].split_at(IV_SIZE);
+        CbcMode::new(&self.encryption_key, iv)?.decrypt(ciphertext)
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs())
+}
+
+fn invalid_token(reason: &str) -> CryptoCoreError {
+    CryptoCoreError::AuthenticationFailed(format!("Invalid Fernet token: {}", reason))
+}
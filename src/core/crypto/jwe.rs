@@ -0,0 +1,372 @@
+//! JSON Web Encryption (RFC 7516) in the compact serialization.
+//!
+//! Token: `header.encrypted_key.iv.ciphertext.tag`, each part base64url without
+//! padding. The header is authenticated as additional data in its encoded form.
+//! Supported key management: `dir`, `A128KW`, `A256KW`; content encryption:
+//! `A128CBC-HS256` and `A128GCM`/`A192GCM`/`A256GCM` (RFC 7518).
+
+use crate::core::crypto::keywrap;
+use crate::core::crypto::mac::{self, MacAlgorithm};
+use crate::core::crypto::modes::cbc::CbcMode;
+use crate::core::crypto::modes::gcm::{self, GcmMode};
+use crate::core::crypto::traits::Cipher;
+use crate::core::io;
+use crate::error::{CryptoCoreError, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::de::{self, Deserialize, Deserializer, MapAccess, Visitor};
+use serde_json::Value;
+use std::fmt;
+
+/// How the content encryption key reaches the recipient (`alg`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyManagement {
+    /// The shared key is the content encryption key
+    Direct,
+    /// The content encryption key is wrapped with AES key wrap
+    A128Kw,
+    A256Kw,
+}
+
+impl KeyManagement {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "dir" => Ok(KeyManagement::Direct),
+            "A128KW" => Ok(KeyManagement::A128Kw),
+            "A256KW" => Ok(KeyManagement::A256Kw),
+            _ => Err(CryptoCoreError::InvalidArgument(format!(
+                "Unsupported JWE key management algorithm: {}",
+                name
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeyManagement::Direct => "dir",
+            KeyManagement::A128Kw => "A128KW",
+            KeyManagement::A256Kw => "A256KW",
+        }
+    }
+
+    /// Size of the shared key; `None` when it is the content key itself.
+    pub fn key_size(&self) -> Option<usize> {
+        match self {
+            KeyManagement::Direct => None,
+            KeyManagement::A128Kw => Some(16),
+            KeyManagement::A256Kw => Some(32),
+        }
+    }
+}
+
+impl fmt::Display for KeyManagement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The authenticated encryption applied to the payload (`enc`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentEncryption {
+    /// AES-128-CBC with an HMAC-SHA256 tag truncated to 16 bytes (RFC 7518, section 5.2)
+    A128CbcHs256,
+    A128Gcm,
+    A192Gcm,
+    A256Gcm,
+}
+
+impl ContentEncryption {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "A128CBC-HS256" => Ok(ContentEncryption::A128CbcHs256),
+            "A128GCM" => Ok(ContentEncryption::A128Gcm),
+            "A192GCM" => Ok(ContentEncryption::A192Gcm),
+            "A256GCM" => Ok(ContentEncryption::A256Gcm),
+            _ => Err(CryptoCoreError::InvalidArgument(format!(
+                "Unsupported JWE content encryption algorithm: {}",
+                name
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ContentEncryption::A128CbcHs256 => "A128CBC-HS256",
+            ContentEncryption::A128Gcm => "A128GCM",
+            ContentEncryption::A192Gcm => "A192GCM",
+            ContentEncryption::A256Gcm => "A256GCM",
+        }
+    }
+
+    pub fn key_size(&self) -> usize {
+        match self {
+            ContentEncryption::A128CbcHs256 => 32,
+            ContentEncryption::A128Gcm => 16,
+            ContentEncryption::A192Gcm => 24,
+            ContentEncryption::A256Gcm => 32,
+        }
+    }
+
+    pub fn iv_size(&self) -> usize {
+        match self {
+            ContentEncryption::A128CbcHs256 => 16,
+            _ => gcm::NONCE_SIZE,
+        }
+    }
+
+    /// Encrypts `data`, returning the ciphertext and the authentication tag.
+    pub fn encrypt(&self, key: &[u8], iv: &[u8], aad: &[u8], data: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+        self.check_sizes(key, iv)?;
+        match self {
+            ContentEncryption::A128CbcHs256 => {
+                let (mac_key, enc_key) = key.split_at(16);
+                let ciphertext = CbcMode::new(enc_key, iv)?.encrypt(data)?;
+                let tag = cbc_hmac_tag(mac_key, iv, aad, &ciphertext)?;
+                Ok((ciphertext, tag))
+            }
+            _ => {
+                let mut ciphertext = GcmMode::new(key, iv)?.encrypt_with_aad(aad, data)?;
+                let tag = ciphertext.split_off(ciphertext.len() - gcm::TAG_SIZE);
+                Ok((ciphertext, tag))
+            }
+        }
+    }
+
+    pub fn decrypt(&self, key: &[u8], iv: &[u8], aad: &[u8], ciphertext: &[u8], tag: &[u8]) -> Result<Vec<u8>> {
+        self.check_sizes(key, iv)?;
+        match self {
+            ContentEncryption::A128CbcHs256 => {
+                let (mac_key, enc_key) = key.split_at(16);
+                // Verify before decrypting so padding errors cannot act as an oracle
+                if !mac::constant_time_eq(&cbc_hmac_tag(mac_key, iv, aad, ciphertext)?, tag) {
+                    return Err(invalid_token("authentication tag mismatch"));
+                }
+                CbcMode::new(enc_key, iv)?.decrypt(ciphertext)
+            }
+            _ => GcmMode::new(key, iv)?
+                .decrypt_with_aad(aad, &[ciphertext, tag].concat())
+                .map_err(|_| invalid_token("authentication tag mismatch")),
+        }
+    }
+
+    fn check_sizes(&self, key: &[u8], iv: &[u8]) -> Result<()> {
+        if key.len() != self.key_size() {
+            return Err(CryptoCoreError::InvalidKey(format!(
+                "{} needs a {}-byte content encryption key",
+                self,
+                self.key_size()
+            )));
+        }
+        if iv.len() != self.iv_size() {
+            return Err(invalid_token("initialization vector has the wrong size"));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ContentEncryption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The protected header of a token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    pub alg: KeyManagement,
+    pub enc: ContentEncryption,
+    /// Key ID, passed through for the recipient to pick a key
+    pub kid: Option<String>,
+}
+
+impl Header {
+    pub fn new(alg: KeyManagement, enc: ContentEncryption) -> Self {
+        Self { alg, enc, kid: None }
+    }
+
+    /// Serializes the header as compact JSON, members in a fixed order.
+    pub fn to_json(&self) -> String {
+        let mut json = format!(r#"{{"alg":"{}","enc":"{}""#, self.alg, self.enc);
+        if let Some(kid) = &self.kid {
+            json.push_str(&format!(r#","kid":{}"#, Value::from(kid.as_str())));
+        }
+        json.push('}');
+        json
+    }
+
+    pub fn parse(json: &str) -> Result<Self> {
+        let members = parse_object(json)?;
+        let member = |name: &str| members.iter().find(|(key, _)| key == name).map(|(_, value)| value);
+
+        // Extensions we would have to understand, and compression we do not implement
+        for name in ["crit", "zip"] {
+            if member(name).is_some() {
+                return Err(CryptoCoreError::InvalidArgument(format!(
+                    "Unsupported JWE header parameter: {}",
+                    name
+                )));
+            }
+        }
+
+        let string = |name: &str| match member(name) {
+            Some(Value::String(value)) => Ok(Some(value.as_str())),
+            Some(_) => Err(invalid_token(&format!("header parameter \"{}\" must be a string", name))),
+            None => Ok(None),
+        };
+        let required = |name: &str| {
+            string(name)?.ok_or_else(|| invalid_token(&format!("header has no \"{}\" parameter", name)))
+        };
+
+        Ok(Self {
+            alg: KeyManagement::from_name(required("alg")?)?,
+            enc: ContentEncryption::from_name(required("enc")?)?,
+            kid: string("kid")?.map(str::to_string),
+        })
+    }
+}
+
+/// Encrypts `data` into a compact token with a random content key and IV.
+pub fn encrypt(key: &[u8], header: &Header, data: &[u8]) -> Result<String> {
+    let cek = match header.alg {
+        KeyManagement::Direct => key.to_vec(),
+        _ => io::generate_key(header.enc.key_size())?,
+    };
+    encrypt_with(key, header, data, &cek, &io::generate_nonce(header.enc.iv_size())?)
+}
+
+/// Encrypts with an explicit content key and IV; only useful for reproducible output.
+pub fn encrypt_with(key: &[u8], header: &Header, data: &[u8], cek: &[u8], iv: &[u8]) -> Result<String> {
+    check_key(key, header)?;
+    let encrypted_key = match header.alg {
+        KeyManagement::Direct => {
+            if cek != key {
+                return Err(CryptoCoreError::InvalidArgument(
+                    "With \"dir\" the content encryption key is the shared key".to_string(),
+                ));
+            }
+            Vec::new()
+        }
+        KeyManagement::A128Kw | KeyManagement::A256Kw => keywrap::wrap(key, cek)?,
+    };
+
+    let protected = URL_SAFE_NO_PAD.encode(header.to_json());
+    let (ciphertext, tag) = header.enc.encrypt(cek, iv, protected.as_bytes(), data)?;
+
+    Ok([
+        protected,
+        URL_SAFE_NO_PAD.encode(encrypted_key),
+        URL_SAFE_NO_PAD.encode(iv),
+        URL_SAFE_NO_PAD.encode(ciphertext),
+        URL_SAFE_NO_PAD.encode(tag),
+    ]
+    .join("."))
+}
+
+/// Decrypts a compact token. The algorithms come from its header and are
+/// returned with the plaintext so the caller can check them.
+pub fn decrypt(key: &[u8], token: &str) -> Result<(Header, Vec<u8>)> {
+    let [protected, encrypted_key, iv, ciphertext, tag] = split(token)?;
+    let header = parse_protected(protected)?;
+    check_key(key, &header)?;
+
+    let encrypted_key = decode_part(encrypted_key)?;
+    let cek = match header.alg {
+        KeyManagement::Direct if encrypted_key.is_empty() => key.to_vec(),
+        KeyManagement::Direct => return Err(invalid_token("\"dir\" tokens carry no encrypted key")),
+        KeyManagement::A128Kw | KeyManagement::A256Kw => keywrap::unwrap(key, &encrypted_key)
+            .map_err(|_| invalid_token("the encrypted key does not unwrap under this key"))?,
+    };
+    if cek.len() != header.enc.key_size() {
+        return Err(invalid_token("content encryption key has the wrong size"));
+    }
+
+    let plaintext = header.enc.decrypt(
+        &cek,
+        &decode_part(iv)?,
+        protected.as_bytes(),
+        &decode_part(ciphertext)?,
+        &decode_part(tag)?,
+    )?;
+    Ok((header, plaintext))
+}
+
+/// Reads the protected header of a token without decrypting anything, so
+/// its algorithms can be checked before the key is used.
+pub fn header(token: &str) -> Result<Header> {
+    parse_protected(split(token)?[0])
+}
+
+fn split(token: &str) -> Result<[&str; 5]> {
+    let parts: Vec<&str> = token.trim().split('.').collect();
+    parts.try_into().map_err(|_| invalid_token("compact serialization has five parts"))
+}
+
+fn parse_protected(protected: &str) -> Result<Header> {
+    let json = String::from_utf8(decode_part(protected)?)
+        .map_err(|_| invalid_token("header is not UTF-8"))?;
+    Header::parse(&json)
+}
+
+fn check_key(key: &[u8], header: &Header) -> Result<()> {
+    let expected = header.alg.key_size().unwrap_or(header.enc.key_size());
+    if key.len() != expected {
+        return Err(CryptoCoreError::InvalidKey(format!(
+            "{} with {} needs a {}-byte key",
+            header.alg, header.enc, expected
+        )));
+    }
+    Ok(())
+}
+
+// RFC 7518, section 5.2.2.1: HMAC over AAD || IV || ciphertext || AAD length in bits
+fn cbc_hmac_tag(mac_key: &[u8], iv: &[u8], aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+    let aad_bits = (aad.len() as u64 * 8).to_be_bytes();
+    let mut tag = mac::compute(MacAlgorithm::HmacSha256, mac_key, &[aad, iv, ciphertext, &aad_bits].concat())?;
+    tag.truncate(16);
+    Ok(tag)
+}
+
+fn decode_part(part: &str) -> Result<Vec<u8>> {
+    URL_SAFE_NO_PAD.decode(part).map_err(|_| invalid_token("part is not base64url"))
+}
+
+fn invalid_token(reason: &str) -> CryptoCoreError {
+    CryptoCoreError::AuthenticationFailed(format!("Invalid JWE: {}", reason))
+}
+
+// The header's members in order, refusing a name that appears twice (which
+// `serde_json::Map` would silently resolve to the last value)
+struct Members(Vec<(String, Value)>);
+
+impl<'de> Deserialize<'de> for Members {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct MembersVisitor;
+
+        impl<'de> Visitor<'de> for MembersVisitor {
+            type Value = Members;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a JSON object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<Members, A::Error> {
+                let mut members: Vec<(String, Value)> = Vec::new();
+                while let Some((name, value)) = map.next_entry::<String, Value>()? {
+                    if members.iter().any(|(key, _)| *key == name) {
+                        return Err(de::Error::custom(format!("header repeats \"{}\"", name)));
+                    }
+                    members.push((name, value));
+                }
+                Ok(Members(members))
+            }
+        }
+
+        deserializer.deserialize_map(MembersVisitor)
+    }
+}
+
+fn parse_object(json: &str) -> Result<Vec<(String, Value)>> {
+    serde_json::from_str::<Members>(json)
+        .map(|members| members.0)
+        .map_err(|e| invalid_token(&format!("header is not a valid JSON object ({})", e)))
+}
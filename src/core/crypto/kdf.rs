@@ -0,0 +1,94 @@
+use crate::error::{CryptoCoreError, Result};
+use hkdf::Hkdf;
+use sha2::{Sha256, Sha512};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KdfAlgorithm {
+    HkdfSha256,
+    HkdfSha512,
+}
+
+impl KdfAlgorithm {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "hkdf-sha256" => Ok(Self::HkdfSha256),
+            "hkdf-sha512" => Ok(Self::HkdfSha512),
+            _ => Err(CryptoCoreError::InvalidArgument(
+                format!("Unsupported KDF algorithm: {}", name)
+            )),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::HkdfSha256 => "hkdf-sha256",
+            Self::HkdfSha512 => "hkdf-sha512",
+        }
+    }
+
+    /// Identifier used when the algorithm is recorded in a file header.
+    pub fn id(&self) -> u8 {
+        match self {
+            Self::HkdfSha256 => 1,
+            Self::HkdfSha512 => 2,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Result<Self> {
+        match id {
+            1 => Ok(Self::HkdfSha256),
+            2 => Ok(Self::HkdfSha512),
+            _ => Err(CryptoCoreError::Crypto(format!("Unknown KDF identifier: {}", id))),
+        }
+    }
+}
+
+impl std::fmt::Display for KdfAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// HKDF (RFC 5869): extract with the optional salt, then expand to `length` bytes.
+pub fn hkdf(
+    algorithm: KdfAlgorithm,
+    ikm: &[u8],
+    salt: Option<&[u8]>,
+    info: &[u8],
+    length: usize,
+) -> Result<Vec<u8>> {
+    let mut okm = vec![0u8; length];
+    let expanded = match algorithm {
+        KdfAlgorithm::HkdfSha256 => Hkdf::<Sha256>::new(salt, ikm).expand(info, &mut okm),
+        KdfAlgorithm::HkdfSha512 => Hkdf::<Sha512>::new(salt, ikm).expand(info, &mut okm),
+    };
+
+    expanded.map_err(|_| {
+        CryptoCoreError::InvalidArgument(format!(
+            "{} cannot produce {} bytes of output",
+            algorithm, length
+        ))
+    })?;
+    Ok(okm)
+}
+
+/// scrypt (RFC 7914) with N = 2^`log_n`, on top of the `scrypt` crate.
+/// Needs 128 * r * N bytes of memory.
+pub fn scrypt(password: &[u8], salt: &[u8], log_n: u8, r: usize, p: usize, length: usize) -> Result<Vec<u8>> {
+    let invalid = || {
+        CryptoCoreError::InvalidArgument(format!(
+            "Invalid scrypt parameters: log2(N) = {}, r = {}, p = {}",
+            log_n, r, p
+        ))
+    };
+    let r = u32::try_from(r).map_err(|_| invalid())?;
+    let p = u32::try_from(p).map_err(|_| invalid())?;
+    // The length in the parameters only matters for password hash strings
+    let params = scrypt::Params::new(log_n, r, p, scrypt::Params::RECOMMENDED_LEN).map_err(|_| invalid())?;
+
+    let mut output = vec![0u8; length];
+    scrypt::scrypt(password, salt, &params, &mut output).map_err(|_| {
+        CryptoCoreError::InvalidArgument(format!("scrypt cannot produce {} bytes of output", length))
+    })?;
+    Ok(output)
+}
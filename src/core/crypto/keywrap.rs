@@ -0,0 +1,155 @@
+//! AES key wrap (RFC 3394) and AES key wrap with padding (RFC 5649).
+
+use crate::core::crypto::aes::AesCipher;
+use crate::core::crypto::mac::constant_time_eq;
+use crate::error::{CryptoCoreError, Result};
+
+// RFC 3394, section 2.2.3.1
+const DEFAULT_IV: [u8; 8] = [0xA6; 8];
+// RFC 5649, section 3: the alternative IV is this constant followed by the length
+const KWP_IV_PREFIX: [u8; 4] = [0xA6, 0x59, 0x59, 0xA6];
+
+pub fn wrap(kek: &[u8], key_data: &[u8]) -> Result<Vec<u8>> {
+    if key_data.len() < 16 || !key_data.len().is_multiple_of(8) {
+        return Err(CryptoCoreError::InvalidArgument(
+            "Key wrap input must be a multiple of 8 bytes and at least 16 bytes".to_string(),
+        ));
+    }
+
+    let cipher = AesCipher::new(kek)?;
+    Ok(wrap_blocks(&cipher, DEFAULT_IV, key_data))
+}
+
+pub fn unwrap(kek: &[u8], wrapped: &[u8]) -> Result<Vec<u8>> {
+    if wrapped.len() < 24 || !wrapped.len().is_multiple_of(8) {
+        return Err(CryptoCoreError::InvalidArgument(
+            "Wrapped key must be a multiple of 8 bytes and at least 24 bytes".to_string(),
+        ));
+    }
+
+    let cipher = AesCipher::new(kek)?;
+    let (iv, key_data) = unwrap_blocks(&cipher, wrapped);
+    if !constant_time_eq(&iv, &DEFAULT_IV) {
+        return Err(integrity_error());
+    }
+
+    Ok(key_data)
+}
+
+pub fn wrap_with_padding(kek: &[u8], key_data: &[u8]) -> Result<Vec<u8>> {
+    let mli = u32::try_from(key_data.len())
+        .ok()
+        .filter(|&len| len > 0)
+        .ok_or_else(|| {
+            CryptoCoreError::InvalidArgument(
+                "Key wrap with padding input must be between 1 and 2^32 - 1 bytes".to_string(),
+            )
+        })?;
+
+    let mut iv = [0u8; 8];
+    iv[..4].copy_from_slice(&KWP_IV_PREFIX);
+    iv[4..].copy_from_slice(&mli.to_be_bytes());
+
+    let mut padded = key_data.to_vec();
+    padded.resize(key_data.len().next_multiple_of(8), 0);
+
+    let cipher = AesCipher::new(kek)?;
+    if padded.len() == 8 {
+        // A single padded block is encrypted directly in ECB mode
+        let mut block = [0u8; 16];
+        block[..8].copy_from_slice(&iv);
+        block[8..].copy_from_slice(&padded);
+        cipher.encrypt_block(&mut block);
+        return Ok(block.to_vec());
+    }
+
+    Ok(wrap_blocks(&cipher, iv, &padded))
+}
+
+pub fn unwrap_with_padding(kek: &[u8], wrapped: &[u8]) -> Result<Vec<u8>> {
+    if wrapped.len() < 16 || !wrapped.len().is_multiple_of(8) {
+        return Err(CryptoCoreError::InvalidArgument(
+            "Wrapped key must be a multiple of 8 bytes and at least 16 bytes".to_string(),
+        ));
+    }
+
+    let cipher = AesCipher::new(kek)?;
+    let (iv, padded) = if wrapped.len() == 16 {
+        let mut block: [u8; 16] = wrapped.try_into().unwrap();
+        cipher.decrypt_block(&mut block);
+        (block[..8].try_into().unwrap(), block[8..].to_vec())
+    } else {
+        unwrap_blocks(&cipher, wrapped)
+    };
+
+    // Checks the constant, the message length indicator and the zero padding
+    let mli = u32::from_be_bytes(iv[4..].try_into().unwrap()) as usize;
+    let prefix_ok = constant_time_eq(&iv[..4], &KWP_IV_PREFIX);
+    let length_ok = mli <= padded.len() && mli + 8 > padded.len();
+    if !prefix_ok || !length_ok || padded[mli..].iter().any(|&byte| byte != 0) {
+        return Err(integrity_error());
+    }
+
+    Ok(padded[..mli].to_vec())
+}
+
+// The wrapping process W from RFC 3394, section 2.2.1 (index-based form)
+fn wrap_blocks(cipher: &AesCipher, iv: [u8; 8], plaintext: &[u8]) -> Vec<u8> {
+    let n = plaintext.len() / 8;
+    let mut a = iv;
+    let mut r: Vec<[u8; 8]> = plaintext
+        .chunks(8)
+        .map(|chunk| chunk.try_into().unwrap())
+        .collect();
+
+    for j in 0..6 {
+        for (i, r_i) in r.iter_mut().enumerate() {
+            let mut block = [0u8; 16];
+            block[..8].copy_from_slice(&a);
+            block[8..].copy_from_slice(r_i);
+            cipher.encrypt_block(&mut block);
+
+            let t = (n * j + i + 1) as u64;
+            a = (u64::from_be_bytes(block[..8].try_into().unwrap()) ^ t).to_be_bytes();
+            r_i.copy_from_slice(&block[8..]);
+        }
+    }
+
+    let mut result = Vec::with_capacity(plaintext.len() + 8);
+    result.extend_from_slice(&a);
+    for r_i in &r {
+        result.extend_from_slice(r_i);
+    }
+    result
+}
+
+// The unwrapping process W^-1, returning the recovered IV and key data
+fn unwrap_blocks(cipher: &AesCipher, ciphertext: &[u8]) -> ([u8; 8], Vec<u8>) {
+    let n = ciphertext.len() / 8 - 1;
+    let mut a: [u8; 8] = ciphertext[..8].try_into().unwrap();
+    let mut r: Vec<[u8; 8]> = ciphertext[8..]
+        .chunks(8)
+        .map(|chunk| chunk.try_into().unwrap())
+        .collect();
+
+    for j in (0..6).rev() {
+        for (i, r_i) in r.iter_mut().enumerate().rev() {
+            let t = (n * j + i + 1) as u64;
+            let mut block = [0u8; 16];
+            block[..8].copy_from_slice(&(u64::from_be_bytes(a) ^ t).to_be_bytes());
+            block[8..].copy_from_slice(r_i);
+            cipher.decrypt_block(&mut block);
+
+            a.copy_from_slice(&block[..8]);
+            r_i.copy_from_slice(&block[8..]);
+        }
+    }
+
+    (a, r.concat())
+}
+
+fn integrity_error() -> CryptoCoreError {
+    CryptoCoreError::IntegrityCheckFailed(
+        "unwrapped key failed the IV check (wrong key-encryption key or corrupted data)".to_string(),
+    )
+}
@@ -0,0 +1,87 @@
+use crate::core::crypto::aes::AesCipher;
+use crate::error::Result;
+
+// Constant R_128 from RFC 4493, section 2.3
+const RB: u8 = 0x87;
+
+/// AES-CMAC (RFC 4493), computed incrementally.
+pub struct Cmac {
+    cipher: AesCipher,
+    k1: [u8; 16],
+    k2: [u8; 16],
+    state: [u8; 16],
+    buffer: Vec<u8>,
+}
+
+impl Cmac {
+    pub fn new(key: &[u8]) -> Result<Self> {
+        let cipher = AesCipher::new(key)?;
+
+        // Subkey generation: L = AES-K(0^128), K1 = L << 1, K2 = K1 << 1
+        let mut l = [0u8; 16];
+        cipher.encrypt_block(&mut l);
+        let k1 = double(&l);
+        let k2 = double(&k1);
+
+        Ok(Self {
+            cipher,
+            k1,
+            k2,
+            state: [0u8; 16],
+            buffer: Vec::with_capacity(16),
+        })
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            // The last block is processed differently, so a full buffer is
+            // only flushed once more data is known to follow it
+            if self.buffer.len() == 16 {
+                let block: [u8; 16] = self.buffer[..].try_into().unwrap();
+                self.process_block(&block);
+                self.buffer.clear();
+            }
+
+            let take = (16 - self.buffer.len()).min(data.len());
+            self.buffer.extend_from_slice(&data[..take]);
+            data = &data[take..];
+        }
+    }
+
+    pub fn finalize(mut self) -> [u8; 16] {
+        let mut last = [0u8; 16];
+
+        if self.buffer.len() == 16 {
+            for ((out, byte), k) in last.iter_mut().zip(&self.buffer).zip(&self.k1) {
+                *out = byte ^ k;
+            }
+        } else {
+            // Incomplete (or empty) last block: pad with 10^i and use K2
+            last[..self.buffer.len()].copy_from_slice(&self.buffer);
+            last[self.buffer.len()] = 0x80;
+            for (out, k) in last.iter_mut().zip(&self.k2) {
+                *out ^= k;
+            }
+        }
+
+        self.process_block(&last);
+        self.state
+    }
+
+    fn process_block(&mut self, block: &[u8; 16]) {
+        for (state, byte) in self.state.iter_mut().zip(block) {
+            *state ^= byte;
+        }
+        self.cipher.encrypt_block(&mut self.state);
+    }
+}
+
+// Multiplication by x in GF(2^128), as used for the CMAC subkeys
+pub(crate) fn double(block: &[u8; 16]) -> [u8; 16] {
+    let value = u128::from_be_bytes(*block);
+    let mut doubled = value << 1;
+    if value >> 127 == 1 {
+        doubled ^= RB as u128;
+    }
+    doubled.to_be_bytes()
+}
@@ -0,0 +1,139 @@
+pub mod cmac;
+
+use crate::error::{CryptoCoreError, Result};
+use hmac::{Hmac, Mac as _};
+use sha2::{Sha256, Sha384, Sha512};
+use std::io::Read;
+use subtle::ConstantTimeEq;
+
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MacAlgorithm {
+    HmacSha256,
+    HmacSha384,
+    HmacSha512,
+    AesCmac,
+}
+
+impl MacAlgorithm {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "hmac-sha256" => Ok(Self::HmacSha256),
+            "hmac-sha384" => Ok(Self::HmacSha384),
+            "hmac-sha512" => Ok(Self::HmacSha512),
+            "cmac" | "aes-cmac" => Ok(Self::AesCmac),
+            _ => Err(CryptoCoreError::InvalidArgument(
+                format!("Unsupported MAC algorithm: {}", name)
+            )),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::HmacSha256 => "hmac-sha256",
+            Self::HmacSha384 => "hmac-sha384",
+            Self::HmacSha512 => "hmac-sha512",
+            Self::AesCmac => "aes-cmac",
+        }
+    }
+
+    pub fn tag_size(&self) -> usize {
+        match self {
+            Self::HmacSha256 => 32,
+            Self::HmacSha384 => 48,
+            Self::HmacSha512 => 64,
+            Self::AesCmac => 16,
+        }
+    }
+}
+
+impl std::fmt::Display for MacAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+pub enum Mac {
+    HmacSha256(Hmac<Sha256>),
+    HmacSha384(Hmac<Sha384>),
+    HmacSha512(Hmac<Sha512>),
+    AesCmac(Box<cmac::Cmac>),
+}
+
+impl Mac {
+    pub fn new(algorithm: MacAlgorithm, key: &[u8]) -> Result<Self> {
+        // HMAC accepts keys of any length, so only CMAC can reject a key
+        let invalid_key = |_| CryptoCoreError::InvalidKey("Invalid HMAC key".to_string());
+        match algorithm {
+            MacAlgorithm::HmacSha256 => Ok(Self::HmacSha256(
+                Hmac::new_from_slice(key).map_err(invalid_key)?,
+            )),
+            MacAlgorithm::HmacSha384 => Ok(Self::HmacSha384(
+                Hmac::new_from_slice(key).map_err(invalid_key)?,
+            )),
+            MacAlgorithm::HmacSha512 => Ok(Self::HmacSha512(
+                Hmac::new_from_slice(key).map_err(invalid_key)?,
+            )),
+            MacAlgorithm::AesCmac => Ok(Self::AesCmac(Box::new(cmac::Cmac::new(key)?))),
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::HmacSha256(mac) => mac.update(data),
+            Self::HmacSha384(mac) => mac.update(data),
+            Self::HmacSha512(mac) => mac.update(data),
+            Self::AesCmac(mac) => mac.update(data),
+        }
+    }
+
+    pub fn finalize(self) -> Vec<u8> {
+        match self {
+            Self::HmacSha256(mac) => mac.finalize().into_bytes().to_vec(),
+            Self::HmacSha384(mac) => mac.finalize().into_bytes().to_vec(),
+            Self::HmacSha512(mac) => mac.finalize().into_bytes().to_vec(),
+            Self::AesCmac(mac) => mac.finalize().to_vec(),
+        }
+    }
+
+    pub fn verify(self, tag: &[u8]) -> Result<()> {
+        if constant_time_eq(&self.finalize(), tag) {
+            Ok(())
+        } else {
+            Err(CryptoCoreError::AuthenticationFailed("MAC mismatch".to_string()))
+        }
+    }
+}
+
+pub fn compute(algorithm: MacAlgorithm, key: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+    let mut mac = Mac::new(algorithm, key)?;
+    mac.update(data);
+    Ok(mac.finalize())
+}
+
+pub fn verify(algorithm: MacAlgorithm, key: &[u8], data: &[u8], tag: &[u8]) -> Result<()> {
+    let mut mac = Mac::new(algorithm, key)?;
+    mac.update(data);
+    mac.verify(tag)
+}
+
+pub fn mac_reader<R: Read>(algorithm: MacAlgorithm, key: &[u8], reader: &mut R) -> Result<Mac> {
+    let mut mac = Mac::new(algorithm, key)?;
+    let mut buffer = vec![0u8; READ_BUFFER_SIZE];
+
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        mac.update(&buffer[..read]);
+    }
+
+    Ok(mac)
+}
+
+/// Compares two tags without leaking the position of the first mismatch.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
@@ -1,5 +1,26 @@
+pub mod aead_stream;
 pub mod aes;
+pub mod age;
+pub(crate) mod asn1;
+pub mod asymmetric;
+pub(crate) mod bigint;
+pub mod chacha20poly1305;
+pub mod curve25519;
+pub mod drbg;
+pub mod fernet;
+pub mod fpe;
+pub mod hash;
+pub mod jwe;
+pub mod kdf;
+pub mod keywrap;
+pub mod mac;
 pub mod modes;
+pub mod openpgp;
+pub mod p256;
+pub mod random;
+pub mod rsa;
+pub mod selftest;
+pub mod streaming;
 pub mod traits;
 use crate::core::crypto::traits::Cipher;
 use crate::error::Result;
@@ -15,7 +36,7 @@ impl CipherInstance {
     pub fn new(algorithm: &str, mode: &str, key: &[u8], iv: Option<&[u8]>) -> Result<Self> {
         match (algorithm.to_lowercase().as_str(), mode.to_lowercase().as_str()) {
             ("aes", "ecb") => Ok(Self::AesEcb(aes::AesCipher::new(key)?)),
-            ("aes", mode_name) if ["cbc", "cfb", "ofb", "ctr"].contains(&mode_name) => {
+            ("aes", mode_name) if ["cbc", "cfb", "ofb", "ctr", "gcm", "ocb"].contains(&mode_name) => {
                 let iv = iv.ok_or_else(|| {
                     crate::error::CryptoCoreError::InvalidArgument(
                         "IV is required for this mode".to_string()
@@ -43,6 +64,83 @@ impl CipherInstance {
             Self::BlockMode(mode) => mode.decrypt(data),
         }
     }
+
+    /// As `encrypt`, authenticating `aad` along with the data. Only GCM and
+    /// OCB take associated data; other modes accept just an empty `aad`.
+    pub fn encrypt_with_aad(&self, aad: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::BlockMode(BlockMode::Gcm(mode)) => mode.encrypt_with_aad(aad, data),
+            Self::BlockMode(BlockMode::Ocb(mode)) => mode.encrypt_with_aad(aad, data),
+            _ => {
+                check_no_aad(aad)?;
+                self.encrypt(data)
+            }
+        }
+    }
+
+    pub fn decrypt_with_aad(&self, aad: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::BlockMode(BlockMode::Gcm(mode)) => mode.decrypt_with_aad(aad, data),
+            Self::BlockMode(BlockMode::Ocb(mode)) => mode.decrypt_with_aad(aad, data),
+            _ => {
+                check_no_aad(aad)?;
+                self.decrypt(data)
+            }
+        }
+    }
+
+    // Без паддинга: позволяет обрабатывать поток целыми блоками по частям
+    pub fn encrypt_blocks(&self, data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::AesEcb(cipher) => cipher.encrypt_blocks(data),
+            Self::BlockMode(BlockMode::Cbc(mode)) => mode.encrypt_blocks(data),
+            Self::BlockMode(mode) => mode.encrypt(data),
+        }
+    }
+
+    pub fn decrypt_blocks(&self, data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::AesEcb(cipher) => cipher.decrypt_blocks(data),
+            Self::BlockMode(BlockMode::Cbc(mode)) => mode.decrypt_blocks(data),
+            Self::BlockMode(mode) => mode.decrypt(data),
+        }
+    }
+
+    pub fn requires_padding(&self) -> bool {
+        match self {
+            Self::AesEcb(cipher) => cipher.requires_padding(),
+            Self::BlockMode(mode) => mode.requires_padding(),
+        }
+    }
+
+    /// Whether the mode appends an authentication tag (and so cannot be processed piecewise).
+    pub fn is_authenticated(&self) -> bool {
+        matches!(self, Self::BlockMode(BlockMode::Gcm(_) | BlockMode::Ocb(_)))
+    }
+}
+
+pub(crate) fn check_no_aad(aad: &[u8]) -> Result<()> {
+    if !aad.is_empty() {
+        return Err(crate::error::CryptoCoreError::InvalidArgument(
+            "Associated data needs an authenticated mode (gcm or ocb)".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Whether a mode authenticates its input (and so can also authenticate associated data).
+pub fn is_authenticated_mode(mode: &str) -> bool {
+    matches!(mode.to_lowercase().as_str(), "gcm" | "ocb")
+}
+
+/// Size of the IV or nonce a mode expects; 0 for modes that use none.
+pub fn iv_size(mode: &str) -> usize {
+    match mode.to_lowercase().as_str() {
+        "ecb" => 0,
+        "gcm" => modes::gcm::NONCE_SIZE,
+        "ocb" => modes::ocb::NONCE_SIZE,
+        _ => 16,
+    }
 }
 
 pub fn create_cipher(algorithm: &str, mode: &str, key: &[u8], iv: Option<&[u8]>) -> Result<CipherInstance> {
@@ -1,8 +1,6 @@
 use crate::core::crypto::aes::AesCipher;
 use crate::core::crypto::traits::Cipher;
 use crate::error::{CryptoCoreError, Result};
-use cipher::{BlockDecrypt, BlockEncrypt};
-use cipher::generic_array::GenericArray;
 
 pub struct CbcMode {
     cipher: AesCipher,
@@ -25,6 +23,11 @@ impl CbcMode {
             iv: iv_array,
         })
     }
+
+    /// Continues from `iv`, for a message processed in pieces.
+    pub(crate) fn set_iv(&mut self, iv: [u8; 16]) {
+        self.iv = iv;
+    }
 }
 
 impl Cipher for CbcMode {
@@ -36,10 +39,42 @@ impl Cipher for CbcMode {
             data.to_vec()
         };
 
-        let mut result = Vec::with_capacity(padded_data.len());
+        self.encrypt_blocks(&padded_data)
+    }
+
+    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let result = self.decrypt_blocks(data)?;
+
+        if self.requires_padding() {
+            self.unpad_data(&result)
+        } else {
+            Ok(result)
+        }
+    }
+
+    fn block_size(&self) -> usize {
+        16
+    }
+
+    fn requires_padding(&self) -> bool {
+        true
+    }
+}
+
+impl CbcMode {
+    /// CBC encryption without padding; `data` must be a whole number of blocks.
+    pub fn encrypt_blocks(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let block_size = self.block_size();
+        if !data.len().is_multiple_of(block_size) {
+            return Err(CryptoCoreError::Crypto(
+                "Plaintext length must be a multiple of block size".to_string()
+            ));
+        }
+
+        let mut result = Vec::with_capacity(data.len());
         let mut previous_block = self.iv;
 
-        for chunk in padded_data.chunks(block_size) {
+        for chunk in data.chunks(block_size) {
             let mut block = [0u8; 16];
             block.copy_from_slice(chunk);
 
@@ -49,9 +84,8 @@ impl Cipher for CbcMode {
             }
 
             // Encrypt the block
-            let mut generic_block = GenericArray::from(block);
-            self.cipher.cipher.encrypt_block(&mut generic_block);
-            let encrypted_block = generic_block.as_slice().to_vec();
+            self.cipher.encrypt_block(&mut block);
+            let encrypted_block = block;
 
             result.extend_from_slice(&encrypted_block);
             previous_block.copy_from_slice(&encrypted_block);
@@ -60,8 +94,9 @@ impl Cipher for CbcMode {
         Ok(result)
     }
 
-    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
-        if data.len() % self.block_size() != 0 {
+    /// CBC decryption without removing padding.
+    pub fn decrypt_blocks(&self, data: &[u8]) -> Result<Vec<u8>> {
+        if !data.len().is_multiple_of(self.block_size()) {
             return Err(CryptoCoreError::Crypto(
                 "Ciphertext length must be a multiple of block size".to_string()
             ));
@@ -75,10 +110,8 @@ impl Cipher for CbcMode {
             block.copy_from_slice(chunk);
 
             // Decrypt the block
-            let mut generic_block = GenericArray::from(block);
-            self.cipher.cipher.decrypt_block(&mut generic_block);
-            let mut decrypted_block = [0u8; 16];
-            decrypted_block.copy_from_slice(generic_block.as_slice());
+            self.cipher.decrypt_block(&mut block);
+            let decrypted_block = block;
 
             // XOR with previous ciphertext block (or IV for first block)
             let mut plaintext_block = vec![0u8; self.block_size()];
@@ -90,27 +123,13 @@ impl Cipher for CbcMode {
             previous_block.copy_from_slice(chunk);
         }
 
-        if self.requires_padding() {
-            self.unpad_data(&result)
-        } else {
-            Ok(result)
-        }
-    }
-
-    fn block_size(&self) -> usize {
-        16
-    }
-
-    fn requires_padding(&self) -> bool {
-        true
+        Ok(result)
     }
-}
 
-impl CbcMode {
     fn pad_data(&self, data: &[u8], block_size: usize) -> Result<Vec<u8>> {
         let mut padded = data.to_vec();
         let pad_len = block_size - (data.len() % block_size);
-        padded.extend(std::iter::repeat(pad_len as u8).take(pad_len));
+        padded.extend(std::iter::repeat_n(pad_len as u8, pad_len));
         Ok(padded)
     }
 
@@ -127,8 +146,8 @@ impl CbcMode {
         }
         
         let mut padding_valid = true;
-        for i in (data.len() - pad_len)..data.len() {
-            padding_valid &= data[i] == pad_byte;
+        for &byte in &data[data.len() - pad_len..] {
+            padding_valid &= byte == pad_byte;
         }
         
         if !padding_valid {
@@ -0,0 +1,141 @@
+//! AES-CCM (RFC 3610, NIST SP 800-38C): CBC-MAC over the formatted input,
+//! then counter mode. The tag size M and the length field size L are chosen
+//! per protocol; the nonce is 15 - L bytes. The tag is appended to the
+//! ciphertext.
+
+use super::ctr::keystream_xor;
+use crate::core::crypto::aes::AesCipher;
+use crate::core::crypto::mac::constant_time_eq;
+use crate::error::{CryptoCoreError, Result};
+
+pub struct CcmMode {
+    cipher: AesCipher,
+    tag_size: usize,
+    length_size: usize,
+}
+
+impl CcmMode {
+    /// `tag_size` is an even number from 4 to 16, `length_size` is 2 to 8.
+    /// Bluetooth LE and IEEE 802.15.4 use an 8-byte tag with a 2-byte length
+    /// field, that is a 13-byte nonce.
+    pub fn new(key: &[u8], tag_size: usize, length_size: usize) -> Result<Self> {
+        if !(4..=16).contains(&tag_size) || !tag_size.is_multiple_of(2) {
+            return Err(CryptoCoreError::InvalidArgument(
+                "CCM tag size must be 4, 6, 8, 10, 12, 14 or 16 bytes".to_string(),
+            ));
+        }
+        if !(2..=8).contains(&length_size) {
+            return Err(CryptoCoreError::InvalidArgument(
+                "CCM length field size must be 2 to 8 bytes".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            cipher: AesCipher::new(key)?,
+            tag_size,
+            length_size,
+        })
+    }
+
+    pub fn nonce_size(&self) -> usize {
+        15 - self.length_size
+    }
+
+    pub fn tag_size(&self) -> usize {
+        self.tag_size
+    }
+
+    pub fn encrypt(&self, nonce: &[u8], aad: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+        self.check_input(nonce, data.len())?;
+        let tag = self.cbc_mac(nonce, aad, data);
+        let mut result = self.ctr(nonce, 1, data);
+        result.extend(self.ctr(nonce, 0, &tag[..self.tag_size]));
+        Ok(result)
+    }
+
+    pub fn decrypt(&self, nonce: &[u8], aad: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+        if data.len() < self.tag_size {
+            return Err(CryptoCoreError::Crypto(
+                "Ciphertext is too short to contain the CCM tag".to_string(),
+            ));
+        }
+
+        let (ciphertext, encrypted_tag) = data.split_at(data.len() - self.tag_size);
+        self.check_input(nonce, ciphertext.len())?;
+        let plaintext = self.ctr(nonce, 1, ciphertext);
+        let tag = self.cbc_mac(nonce, aad, &plaintext);
+        // The tag covers the plaintext, which is dropped unless it matches
+        if !constant_time_eq(&self.ctr(nonce, 0, &tag[..self.tag_size]), encrypted_tag) {
+            return Err(CryptoCoreError::AuthenticationFailed("CCM tag mismatch".to_string()));
+        }
+
+        Ok(plaintext)
+    }
+
+    fn check_input(&self, nonce: &[u8], length: usize) -> Result<()> {
+        if nonce.len() != self.nonce_size() {
+            return Err(CryptoCoreError::InvalidArgument(format!(
+                "Nonce must be {} bytes for CCM with a {}-byte length field",
+                self.nonce_size(),
+                self.length_size
+            )));
+        }
+        if self.length_size < 8 && length as u64 >> (8 * self.length_size) != 0 {
+            return Err(CryptoCoreError::InvalidArgument(format!(
+                "Message is too long for a {}-byte CCM length field",
+                self.length_size
+            )));
+        }
+        Ok(())
+    }
+
+    // Block B_0 and the CBC-MAC of RFC 3610, section 2.2
+    fn cbc_mac(&self, nonce: &[u8], aad: &[u8], data: &[u8]) -> [u8; 16] {
+        let mut b0 = [0u8; 16];
+        b0[0] = (u8::from(!aad.is_empty()) << 6)
+            | (((self.tag_size as u8 - 2) / 2) << 3)
+            | (self.length_size as u8 - 1);
+        b0[1..16 - self.length_size].copy_from_slice(nonce);
+        let length = (data.len() as u64).to_be_bytes();
+        b0[16 - self.length_size..].copy_from_slice(&length[8 - self.length_size..]);
+
+        let mut mac = b0;
+        self.cipher.encrypt_block(&mut mac);
+        if !aad.is_empty() {
+            let mut encoded = encode_aad_length(aad.len());
+            encoded.extend_from_slice(aad);
+            self.absorb_padded(&mut mac, &encoded);
+        }
+        self.absorb_padded(&mut mac, data);
+        mac
+    }
+
+    fn absorb_padded(&self, mac: &mut [u8; 16], data: &[u8]) {
+        for chunk in data.chunks(16) {
+            for (state, byte) in mac.iter_mut().zip(chunk) {
+                *state ^= byte;
+            }
+            self.cipher.encrypt_block(mac);
+        }
+    }
+
+    // Counter blocks A_i: flags (L - 1), the nonce and i in L bytes
+    fn ctr(&self, nonce: &[u8], counter: u8, data: &[u8]) -> Vec<u8> {
+        let mut a = [0u8; 16];
+        a[0] = self.length_size as u8 - 1;
+        a[1..16 - self.length_size].copy_from_slice(nonce);
+        a[15] = counter;
+        keystream_xor(&self.cipher, a, data)
+    }
+}
+
+// The length prefix of the associated data (RFC 3610, section 2.2)
+fn encode_aad_length(length: usize) -> Vec<u8> {
+    if length < 0xff00 {
+        (length as u16).to_be_bytes().to_vec()
+    } else if let Ok(length) = u32::try_from(length) {
+        [&[0xff, 0xfe][..], &length.to_be_bytes()].concat()
+    } else {
+        [&[0xff, 0xff][..], &(length as u64).to_be_bytes()].concat()
+    }
+}
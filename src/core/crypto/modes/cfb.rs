@@ -1,12 +1,14 @@
 use crate::core::crypto::aes::AesCipher;
 use crate::core::crypto::traits::Cipher;
 use crate::error::{CryptoCoreError, Result};
-use cipher::{BlockEncrypt};
-use cipher::generic_array::GenericArray;
+
+// Prefix of random data plus its two repeated check bytes, after which OpenPGP resyncs
+const OPENPGP_PREFIX_SIZE: usize = 18;
 
 pub struct CfbMode {
     cipher: AesCipher,
     iv: [u8; 16],
+    resync: bool,
 }
 
 impl CfbMode {
@@ -23,20 +25,68 @@ impl CfbMode {
         Ok(Self {
             cipher: AesCipher::new(key)?,
             iv: iv_array,
+            resync: false,
         })
     }
+
+    /// OpenPGP CFB (RFC 4880, section 13.9): a zero IV, with the data starting
+    /// with a random prefix. With `resync` (legacy packets without an MDC) the
+    /// feedback register restarts from ciphertext bytes 2..18 once the prefix and
+    /// its check bytes are done; without it this is plain CFB.
+    pub fn openpgp(key: &[u8], resync: bool) -> Result<Self> {
+        Ok(Self {
+            cipher: AesCipher::new(key)?,
+            iv: [0u8; 16],
+            resync,
+        })
+    }
+
+    /// Continues from `iv`, for a message processed in pieces.
+    pub(crate) fn set_iv(&mut self, iv: [u8; 16]) {
+        self.iv = iv;
+    }
 }
 
 impl Cipher for CfbMode {
     fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        if self.resync && data.len() > OPENPGP_PREFIX_SIZE {
+            let (prefix, rest) = data.split_at(OPENPGP_PREFIX_SIZE);
+            let mut result = self.encrypt_from(self.iv, prefix);
+            let resynced = result[2..OPENPGP_PREFIX_SIZE].try_into().unwrap();
+            result.extend(self.encrypt_from(resynced, rest));
+            return Ok(result);
+        }
+        Ok(self.encrypt_from(self.iv, data))
+    }
+
+    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        if self.resync && data.len() > OPENPGP_PREFIX_SIZE {
+            let (prefix, rest) = data.split_at(OPENPGP_PREFIX_SIZE);
+            let mut result = self.decrypt_from(self.iv, prefix);
+            result.extend(self.decrypt_from(prefix[2..].try_into().unwrap(), rest));
+            return Ok(result);
+        }
+        Ok(self.decrypt_from(self.iv, data))
+    }
+
+    fn block_size(&self) -> usize {
+        16
+    }
+
+    fn requires_padding(&self) -> bool {
+        false
+    }
+}
+
+impl CfbMode {
+    fn encrypt_from(&self, iv: [u8; 16], data: &[u8]) -> Vec<u8> {
         let mut result = Vec::with_capacity(data.len());
-        let mut feedback = self.iv;
+        let mut feedback = iv;
 
         for chunk in data.chunks(16) {
             // Encrypt the feedback register
-            let mut feedback_block = GenericArray::from(feedback);
-            self.cipher.cipher.encrypt_block(&mut feedback_block);
-            let encrypted_feedback = feedback_block.as_slice();
+            let mut encrypted_feedback = feedback;
+            self.cipher.encrypt_block(&mut encrypted_feedback);
             
             // XOR plaintext with encrypted feedback
             let mut output_block = vec![0u8; chunk.len()];
@@ -57,18 +107,17 @@ impl Cipher for CfbMode {
             }
         }
 
-        Ok(result)
+        result
     }
 
-    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+    fn decrypt_from(&self, iv: [u8; 16], data: &[u8]) -> Vec<u8> {
         let mut result = Vec::with_capacity(data.len());
-        let mut feedback = self.iv;
+        let mut feedback = iv;
 
         for chunk in data.chunks(16) {
             // Encrypt the feedback register
-            let mut feedback_block = GenericArray::from(feedback);
-            self.cipher.cipher.encrypt_block(&mut feedback_block);
-            let encrypted_feedback = feedback_block.as_slice();
+            let mut encrypted_feedback = feedback;
+            self.cipher.encrypt_block(&mut encrypted_feedback);
             
             // XOR ciphertext with encrypted feedback
             let mut output_block = vec![0u8; chunk.len()];
@@ -88,14 +137,6 @@ impl Cipher for CfbMode {
             }
         }
 
-        Ok(result)
+        result
     }
-
-    fn block_size(&self) -> usize {
-        16
-    }
-
-    fn requires_padding(&self) -> bool {
-        false
-    }
-}
\ No newline at end of file
+}
@@ -1,8 +1,6 @@
 use crate::core::crypto::aes::AesCipher;
 use crate::core::crypto::traits::Cipher;
 use crate::error::{CryptoCoreError, Result};
-use cipher::{BlockEncrypt};
-use cipher::generic_array::GenericArray;
 
 pub struct CtrMode {
     cipher: AesCipher,
@@ -25,31 +23,16 @@ impl CtrMode {
             nonce,
         })
     }
+
+    /// Continues from `iv`, for a message processed in pieces.
+    pub(crate) fn set_iv(&mut self, iv: [u8; 16]) {
+        self.nonce = iv;
+    }
 }
 
 impl Cipher for CtrMode {
     fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
-        let mut result = Vec::with_capacity(data.len());
-        let mut counter = u128::from_be_bytes(self.nonce);
-
-        for chunk in data.chunks(16) {
-            // Encrypt counter value
-            let counter_bytes = counter.to_be_bytes();
-            let mut counter_block = GenericArray::from(counter_bytes);
-            self.cipher.cipher.encrypt_block(&mut counter_block);
-            let encrypted_counter = counter_block.as_slice();
-            
-            // XOR plaintext with encrypted counter
-            let mut output_block = vec![0u8; chunk.len()];
-            for i in 0..chunk.len() {
-                output_block[i] = chunk[i] ^ encrypted_counter[i];
-            }
-            
-            result.extend_from_slice(&output_block);
-            counter = counter.wrapping_add(1);
-        }
-
-        Ok(result)
+        Ok(keystream_xor(&self.cipher, self.nonce, data))
     }
 
     fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
@@ -64,4 +47,22 @@ impl Cipher for CtrMode {
     fn requires_padding(&self) -> bool {
         false
     }
-}
\ No newline at end of file
+}
+/// XORs `data` with the keystream of `cipher` in counter mode, treating the
+/// whole 16-byte `initial_counter` block as a big-endian counter.
+pub(crate) fn keystream_xor(cipher: &AesCipher, initial_counter: [u8; 16], data: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(data.len());
+    let mut counter = u128::from_be_bytes(initial_counter);
+
+    for chunk in data.chunks(16) {
+        // Encrypt counter value
+        let mut encrypted_counter = counter.to_be_bytes();
+        cipher.encrypt_block(&mut encrypted_counter);
+
+        // XOR plaintext with encrypted counter
+        result.extend(chunk.iter().zip(encrypted_counter).map(|(byte, key)| byte ^ key));
+        counter = counter.wrapping_add(1);
+    }
+
+    result
+}
@@ -0,0 +1,79 @@
+//! AES-EAX (Bellare, Rogaway and Wagner): counter mode keyed by an OMAC of the
+//! nonce, with a tag combining the OMACs of nonce, header and ciphertext.
+//! Nonces may have any length. The tag is appended to the ciphertext.
+
+use super::ctr::keystream_xor;
+use crate::core::crypto::aes::AesCipher;
+use crate::core::crypto::mac::cmac::Cmac;
+use crate::core::crypto::mac::constant_time_eq;
+use crate::error::{CryptoCoreError, Result};
+
+pub const TAG_SIZE: usize = 16;
+
+pub struct EaxMode {
+    key: Vec<u8>,
+    cipher: AesCipher,
+    tag_size: usize,
+}
+
+impl EaxMode {
+    /// `tag_size` truncates the 16-byte tag and must be at least 1.
+    pub fn new(key: &[u8], tag_size: usize) -> Result<Self> {
+        if !(1..=TAG_SIZE).contains(&tag_size) {
+            return Err(CryptoCoreError::InvalidArgument(
+                "EAX tag size must be 1 to 16 bytes".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            key: key.to_vec(),
+            cipher: AesCipher::new(key)?,
+            tag_size,
+        })
+    }
+
+    pub fn tag_size(&self) -> usize {
+        self.tag_size
+    }
+
+    pub fn encrypt(&self, nonce: &[u8], aad: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+        let n = self.omac(0, nonce)?;
+        let mut result = keystream_xor(&self.cipher, n, data);
+        let tag = self.tag(&n, aad, &result)?;
+        result.extend_from_slice(&tag[..self.tag_size]);
+        Ok(result)
+    }
+
+    pub fn decrypt(&self, nonce: &[u8], aad: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+        if data.len() < self.tag_size {
+            return Err(CryptoCoreError::Crypto(
+                "Ciphertext is too short to contain the EAX tag".to_string(),
+            ));
+        }
+
+        let (ciphertext, tag) = data.split_at(data.len() - self.tag_size);
+        let n = self.omac(0, nonce)?;
+        // Verify before decrypting so no unauthenticated plaintext is released
+        if !constant_time_eq(&self.tag(&n, aad, ciphertext)?[..self.tag_size], tag) {
+            return Err(CryptoCoreError::AuthenticationFailed("EAX tag mismatch".to_string()));
+        }
+
+        Ok(keystream_xor(&self.cipher, n, ciphertext))
+    }
+
+    fn tag(&self, n: &[u8; 16], aad: &[u8], ciphertext: &[u8]) -> Result<[u8; 16]> {
+        let h = u128::from_be_bytes(self.omac(1, aad)?);
+        let c = u128::from_be_bytes(self.omac(2, ciphertext)?);
+        Ok((u128::from_be_bytes(*n) ^ h ^ c).to_be_bytes())
+    }
+
+    // OMAC^t: CMAC over the block [t] followed by the data
+    fn omac(&self, t: u8, data: &[u8]) -> Result<[u8; 16]> {
+        let mut tweak = [0u8; 16];
+        tweak[15] = t;
+        let mut mac = Cmac::new(&self.key)?;
+        mac.update(&tweak);
+        mac.update(data);
+        Ok(mac.finalize())
+    }
+}
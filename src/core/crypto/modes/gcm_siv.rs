@@ -0,0 +1,148 @@
+//! AES-GCM-SIV (RFC 8452), nonce misuse-resistant authenticated encryption.
+//!
+//! Each nonce derives its own authentication and encryption keys, and the tag
+//! doubles as the CTR IV. Reusing a nonce only reveals whether two messages
+//! are equal, so a fixed nonce gives deterministic encryption. The tag is
+//! appended to the ciphertext.
+
+use super::gcm::{GHash, R};
+use crate::core::crypto::aes::AesCipher;
+use crate::core::crypto::mac::constant_time_eq;
+use crate::error::{CryptoCoreError, Result};
+
+pub const NONCE_SIZE: usize = 12;
+pub const TAG_SIZE: usize = 16;
+
+/// Plaintext and associated data are limited to 2^36 bytes (section 6)
+const MAX_LENGTH: u64 = 1 << 36;
+
+pub struct AesGcmSiv {
+    key_generating: AesCipher,
+}
+
+impl AesGcmSiv {
+    /// `key` is 16 or 32 bytes.
+    pub fn new(key: &[u8]) -> Result<Self> {
+        if !matches!(key.len(), 16 | 32) {
+            return Err(CryptoCoreError::InvalidKey(
+                "AES-GCM-SIV key must be 16 or 32 bytes".to_string(),
+            ));
+        }
+        Ok(Self { key_generating: AesCipher::new(key)? })
+    }
+
+    pub fn encrypt(&self, nonce: &[u8; NONCE_SIZE], aad: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+        check_lengths(aad, data)?;
+        let (auth_key, cipher) = self.derive_keys(nonce)?;
+        let tag = tag(&cipher, &auth_key, nonce, aad, data);
+        let mut result = ctr(&cipher, &tag, data);
+        result.extend_from_slice(&tag);
+        Ok(result)
+    }
+
+    pub fn decrypt(&self, nonce: &[u8; NONCE_SIZE], aad: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+        if data.len() < TAG_SIZE {
+            return Err(CryptoCoreError::Crypto(
+                "Ciphertext is too short to contain the GCM-SIV tag".to_string(),
+            ));
+        }
+
+        let (ciphertext, expected) = data.split_at(data.len() - TAG_SIZE);
+        check_lengths(aad, ciphertext)?;
+        let (auth_key, cipher) = self.derive_keys(nonce)?;
+        let plaintext = ctr(&cipher, expected.try_into().unwrap(), ciphertext);
+        // The tag covers the plaintext, which is dropped unless it matches
+        if !constant_time_eq(&tag(&cipher, &auth_key, nonce, aad, &plaintext), expected) {
+            return Err(CryptoCoreError::AuthenticationFailed("GCM-SIV tag mismatch".to_string()));
+        }
+
+        Ok(plaintext)
+    }
+
+    // Section 4: the first 8 bytes of AES(le32(i) || nonce) for each half block
+    fn derive_keys(&self, nonce: &[u8; NONCE_SIZE]) -> Result<([u8; 16], AesCipher)> {
+        let blocks = if self.key_generating.key_size() == 32 { 6 } else { 4 };
+        let mut derived = Vec::with_capacity(blocks * 8);
+        for i in 0..blocks as u32 {
+            let mut block = [0u8; 16];
+            block[..4].copy_from_slice(&i.to_le_bytes());
+            block[4..].copy_from_slice(nonce);
+            self.key_generating.encrypt_block(&mut block);
+            derived.extend_from_slice(&block[..8]);
+        }
+
+        let (auth_key, encryption_key) = derived.split_at(16);
+        Ok((auth_key.try_into().unwrap(), AesCipher::new(encryption_key)?))
+    }
+}
+
+fn check_lengths(aad: &[u8], data: &[u8]) -> Result<()> {
+    if aad.len() as u64 > MAX_LENGTH || data.len() as u64 > MAX_LENGTH {
+        return Err(CryptoCoreError::InvalidArgument(
+            "AES-GCM-SIV input exceeds 2^36 bytes".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn tag(cipher: &AesCipher, auth_key: &[u8; 16], nonce: &[u8; NONCE_SIZE], aad: &[u8], data: &[u8]) -> [u8; TAG_SIZE] {
+    let mut polyval = Polyval::new(auth_key);
+    polyval.update_padded(aad);
+    polyval.update_padded(data);
+    let mut lengths = [0u8; 16];
+    lengths[..8].copy_from_slice(&(aad.len() as u64 * 8).to_le_bytes());
+    lengths[8..].copy_from_slice(&(data.len() as u64 * 8).to_le_bytes());
+    polyval.update_padded(&lengths);
+
+    let mut s = polyval.finalize();
+    for (byte, n) in s.iter_mut().zip(nonce) {
+        *byte ^= n;
+    }
+    s[15] &= 0x7f;
+    cipher.encrypt_block(&mut s);
+    s
+}
+
+// Counter mode with a 32-bit little-endian counter in the first word of the
+// tag, whose top bit is set (section 4)
+fn ctr(cipher: &AesCipher, tag: &[u8; TAG_SIZE], data: &[u8]) -> Vec<u8> {
+    let mut block = *tag;
+    block[15] |= 0x80;
+    let mut counter = u32::from_le_bytes(block[..4].try_into().unwrap());
+    let mut result = Vec::with_capacity(data.len());
+
+    for chunk in data.chunks(16) {
+        block[..4].copy_from_slice(&counter.to_le_bytes());
+        let mut keystream = block;
+        cipher.encrypt_block(&mut keystream);
+        result.extend(chunk.iter().zip(keystream).map(|(byte, key)| byte ^ key));
+        counter = counter.wrapping_add(1);
+    }
+
+    result
+}
+
+/// POLYVAL, computed through GHASH as in RFC 8452, appendix A: byte-reversed
+/// blocks and the key multiplied by x.
+pub(crate) struct Polyval(GHash);
+
+impl Polyval {
+    pub(crate) fn new(key: &[u8; 16]) -> Self {
+        // Reading little-endian reverses the bytes; mulX_GHASH is a right shift
+        let h = u128::from_le_bytes(*key);
+        Self(GHash::new((h >> 1) ^ (R & 0u128.wrapping_sub(h & 1))))
+    }
+
+    /// Absorbs `data`, zero-padding the final partial block.
+    pub(crate) fn update_padded(&mut self, data: &[u8]) {
+        for chunk in data.chunks(16) {
+            let mut block = [0u8; 16];
+            block[..chunk.len()].copy_from_slice(chunk);
+            self.0.update_block(u128::from_le_bytes(block));
+        }
+    }
+
+    pub(crate) fn finalize(self) -> [u8; 16] {
+        self.0.finalize().to_le_bytes()
+    }
+}
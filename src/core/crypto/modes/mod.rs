@@ -2,6 +2,12 @@ pub mod cbc;
 pub mod cfb;
 pub mod ofb;
 pub mod ctr;
+pub mod gcm;
+pub mod ocb;
+pub mod siv;
+pub mod gcm_siv;
+pub mod ccm;
+pub mod eax;
 
 use crate::error::Result;
 use super::traits::Cipher;  // Импортируем трейт
@@ -11,6 +17,8 @@ pub enum BlockMode {
     Cfb(cfb::CfbMode),
     Ofb(ofb::OfbMode),
     Ctr(ctr::CtrMode),
+    Gcm(gcm::GcmMode),
+    Ocb(ocb::OcbMode),
 }
 
 impl BlockMode {
@@ -20,11 +28,25 @@ impl BlockMode {
             "cfb" => Ok(Self::Cfb(cfb::CfbMode::new(key, iv)?)),
             "ofb" => Ok(Self::Ofb(ofb::OfbMode::new(key, iv)?)),
             "ctr" => Ok(Self::Ctr(ctr::CtrMode::new(key, iv)?)),
+            "gcm" => Ok(Self::Gcm(gcm::GcmMode::new(key, iv)?)),
+            "ocb" => Ok(Self::Ocb(ocb::OcbMode::new(key, iv)?)),
             _ => Err(crate::error::CryptoCoreError::InvalidArgument(
                 format!("Unsupported mode: {}", mode_name)
             )),
         }
     }
+
+    /// Moves a chained mode on to the IV of the next piece of the message;
+    /// the authenticated modes are never chained and keep their nonce.
+    pub(crate) fn set_iv(&mut self, iv: [u8; 16]) {
+        match self {
+            Self::Cbc(cipher) => cipher.set_iv(iv),
+            Self::Cfb(cipher) => cipher.set_iv(iv),
+            Self::Ofb(cipher) => cipher.set_iv(iv),
+            Self::Ctr(cipher) => cipher.set_iv(iv),
+            Self::Gcm(_) | Self::Ocb(_) => {}
+        }
+    }
 }
 
 // Реализуем трейт Cipher для BlockMode
@@ -35,6 +57,8 @@ impl Cipher for BlockMode {
             Self::Cfb(cipher) => cipher.encrypt(data),
             Self::Ofb(cipher) => cipher.encrypt(data),
             Self::Ctr(cipher) => cipher.encrypt(data),
+            Self::Gcm(cipher) => cipher.encrypt(data),
+            Self::Ocb(cipher) => cipher.encrypt(data),
         }
     }
 
@@ -44,6 +68,8 @@ impl Cipher for BlockMode {
             Self::Cfb(cipher) => cipher.decrypt(data),
             Self::Ofb(cipher) => cipher.decrypt(data),
             Self::Ctr(cipher) => cipher.decrypt(data),
+            Self::Gcm(cipher) => cipher.decrypt(data),
+            Self::Ocb(cipher) => cipher.decrypt(data),
         }
     }
 
@@ -54,7 +80,7 @@ impl Cipher for BlockMode {
     fn requires_padding(&self) -> bool {
         match self {
             Self::Cbc(_) => true,
-            Self::Cfb(_) | Self::Ofb(_) | Self::Ctr(_) => false,
+            Self::Cfb(_) | Self::Ofb(_) | Self::Ctr(_) | Self::Gcm(_) | Self::Ocb(_) => false,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,221 @@
+//! AES-OCB3 (RFC 7253): single-pass authenticated encryption, one block
+//! cipher call per block. The tag is appended to the ciphertext.
+
+use crate::core::crypto::aes::AesCipher;
+use crate::core::crypto::mac::cmac::double;
+use crate::core::crypto::mac::constant_time_eq;
+use crate::core::crypto::traits::Cipher;
+use crate::error::{CryptoCoreError, Result};
+
+/// The nonce the CLI uses; RFC 7253 accepts 1 to 15 bytes
+pub const NONCE_SIZE: usize = 12;
+pub const TAG_SIZE: usize = 16;
+
+// L_i for ntz(i) up to 63 covers any message that fits in memory
+const L_TABLE_SIZE: usize = 64;
+
+pub struct OcbMode {
+    cipher: AesCipher,
+    tag_size: usize,
+    l_star: u128,
+    l_dollar: u128,
+    l: Vec<u128>,
+    initial_offset: u128,
+}
+
+impl OcbMode {
+    pub fn new(key: &[u8], nonce: &[u8]) -> Result<Self> {
+        Self::with_tag_size(key, nonce, TAG_SIZE)
+    }
+
+    /// OCB with the tag truncated to `tag_size` bytes, which also changes
+    /// the ciphertext.
+    pub fn with_tag_size(key: &[u8], nonce: &[u8], tag_size: usize) -> Result<Self> {
+        check_nonce(nonce)?;
+        if !(1..=TAG_SIZE).contains(&tag_size) {
+            return Err(CryptoCoreError::InvalidArgument(
+                "OCB tag size must be 1 to 16 bytes".to_string()
+            ));
+        }
+
+        let cipher = AesCipher::new(key)?;
+        let mut l_star = [0u8; 16];
+        cipher.encrypt_block(&mut l_star);
+        let l_dollar = double(&l_star);
+        let mut l = Vec::with_capacity(L_TABLE_SIZE);
+        let mut current = double(&l_dollar);
+        for _ in 0..L_TABLE_SIZE {
+            l.push(u128::from_be_bytes(current));
+            current = double(&current);
+        }
+
+        let mut mode = Self {
+            cipher,
+            tag_size,
+            l_star: u128::from_be_bytes(l_star),
+            l_dollar: u128::from_be_bytes(l_dollar),
+            l,
+            initial_offset: 0,
+        };
+        mode.initial_offset = mode.nonce_offset(nonce);
+        Ok(mode)
+    }
+
+    /// Switches to another nonce under the same key, keeping the key schedule
+    /// and the L table.
+    pub fn set_nonce(&mut self, nonce: &[u8]) -> Result<()> {
+        check_nonce(nonce)?;
+        self.initial_offset = self.nonce_offset(nonce);
+        Ok(())
+    }
+
+    pub fn encrypt_with_aad(&self, aad: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+        let mut result = Vec::with_capacity(data.len() + self.tag_size);
+        let mut offset = self.initial_offset;
+        let mut checksum = 0u128;
+
+        let mut blocks = data.chunks_exact(16);
+        for (i, block) in (1u64..).zip(&mut blocks) {
+            let block = u128::from_be_bytes(block.try_into().unwrap());
+            offset ^= self.l[i.trailing_zeros() as usize];
+            checksum ^= block;
+            result.extend_from_slice(&(self.encrypt_u128(block ^ offset) ^ offset).to_be_bytes());
+        }
+
+        let last = blocks.remainder();
+        if !last.is_empty() {
+            offset ^= self.l_star;
+            let pad = self.encrypt_u128(offset).to_be_bytes();
+            result.extend(last.iter().zip(pad).map(|(byte, key)| byte ^ key));
+            checksum ^= pad_block(last);
+        }
+
+        let tag = self.encrypt_u128(checksum ^ offset ^ self.l_dollar) ^ self.hash(aad);
+        result.extend_from_slice(&tag.to_be_bytes()[..self.tag_size]);
+        Ok(result)
+    }
+
+    pub fn decrypt_with_aad(&self, aad: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+        if data.len() < self.tag_size {
+            return Err(CryptoCoreError::Crypto(
+                "Ciphertext is too short to contain the OCB tag".to_string()
+            ));
+        }
+
+        let (ciphertext, expected) = data.split_at(data.len() - self.tag_size);
+        let mut result = Vec::with_capacity(ciphertext.len());
+        let mut offset = self.initial_offset;
+        let mut checksum = 0u128;
+
+        let mut blocks = ciphertext.chunks_exact(16);
+        for (i, block) in (1u64..).zip(&mut blocks) {
+            let block = u128::from_be_bytes(block.try_into().unwrap());
+            offset ^= self.l[i.trailing_zeros() as usize];
+            let plain = self.decrypt_u128(block ^ offset) ^ offset;
+            checksum ^= plain;
+            result.extend_from_slice(&plain.to_be_bytes());
+        }
+
+        let last = blocks.remainder();
+        if !last.is_empty() {
+            offset ^= self.l_star;
+            let pad = self.encrypt_u128(offset).to_be_bytes();
+            let start = result.len();
+            result.extend(last.iter().zip(pad).map(|(byte, key)| byte ^ key));
+            checksum ^= pad_block(&result[start..]);
+        }
+
+        let tag = self.encrypt_u128(checksum ^ offset ^ self.l_dollar) ^ self.hash(aad);
+        // The tag covers the plaintext, which is dropped unless it matches
+        if !constant_time_eq(&tag.to_be_bytes()[..self.tag_size], expected) {
+            return Err(CryptoCoreError::AuthenticationFailed("OCB tag mismatch".to_string()));
+        }
+
+        Ok(result)
+    }
+
+    // Offset_0 from the nonce (section 4.2): the bottom six bits select
+    // 128 bits of Stretch = Ktop || (Ktop[1..64] xor Ktop[9..72])
+    fn nonce_offset(&self, nonce: &[u8]) -> u128 {
+        let mut block = [0u8; 16];
+        block[0] = ((self.tag_size * 8 % 128) as u8) << 1;
+        block[16 - nonce.len()..].copy_from_slice(nonce);
+        block[15 - nonce.len()] |= 1;
+        let bottom = u32::from(block[15] & 0x3f);
+        block[15] &= 0xc0;
+
+        let ktop = self.encrypt_u128(u128::from_be_bytes(block));
+        let extension = ((ktop >> 64) as u64) ^ ((ktop >> 56) as u64);
+        if bottom == 0 {
+            ktop
+        } else {
+            (ktop << bottom) | u128::from(extension >> (64 - bottom))
+        }
+    }
+
+    // HASH(K, A) of section 4.1
+    fn hash(&self, aad: &[u8]) -> u128 {
+        let mut offset = 0u128;
+        let mut sum = 0u128;
+
+        let mut blocks = aad.chunks_exact(16);
+        for (i, block) in (1u64..).zip(&mut blocks) {
+            offset ^= self.l[i.trailing_zeros() as usize];
+            sum ^= self.encrypt_u128(u128::from_be_bytes(block.try_into().unwrap()) ^ offset);
+        }
+
+        let last = blocks.remainder();
+        if !last.is_empty() {
+            offset ^= self.l_star;
+            sum ^= self.encrypt_u128(pad_block(last) ^ offset);
+        }
+        sum
+    }
+
+    fn encrypt_u128(&self, block: u128) -> u128 {
+        let mut bytes = block.to_be_bytes();
+        self.cipher.encrypt_block(&mut bytes);
+        u128::from_be_bytes(bytes)
+    }
+
+    fn decrypt_u128(&self, block: u128) -> u128 {
+        let mut bytes = block.to_be_bytes();
+        self.cipher.decrypt_block(&mut bytes);
+        u128::from_be_bytes(bytes)
+    }
+}
+
+fn check_nonce(nonce: &[u8]) -> Result<()> {
+    if nonce.is_empty() || nonce.len() > 15 {
+        return Err(CryptoCoreError::InvalidArgument(
+            "Nonce must be 1 to 15 bytes for OCB mode".to_string()
+        ));
+    }
+    Ok(())
+}
+
+impl Cipher for OcbMode {
+    fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        self.encrypt_with_aad(&[], data)
+    }
+
+    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        self.decrypt_with_aad(&[], data)
+    }
+
+    fn block_size(&self) -> usize {
+        16
+    }
+
+    fn requires_padding(&self) -> bool {
+        false
+    }
+}
+
+// A partial block followed by a single 1 bit and zeros
+fn pad_block(data: &[u8]) -> u128 {
+    let mut block = [0u8; 16];
+    block[..data.len()].copy_from_slice(data);
+    block[data.len()] = 0x80;
+    u128::from_be_bytes(block)
+}
@@ -1,8 +1,6 @@
 use crate::core::crypto::aes::AesCipher;
 use crate::core::crypto::traits::Cipher;
 use crate::error::{CryptoCoreError, Result};
-use cipher::{BlockEncrypt};
-use cipher::generic_array::GenericArray;
 
 pub struct OfbMode {
     cipher: AesCipher,
@@ -25,6 +23,11 @@ impl OfbMode {
             iv: iv_array,
         })
     }
+
+    /// Continues from `iv`, for a message processed in pieces.
+    pub(crate) fn set_iv(&mut self, iv: [u8; 16]) {
+        self.iv = iv;
+    }
 }
 
 impl Cipher for OfbMode {
@@ -34,9 +37,7 @@ impl Cipher for OfbMode {
 
         for chunk in data.chunks(16) {
             // Generate keystream block
-            let mut keystream_block = GenericArray::from(keystream);
-            self.cipher.cipher.encrypt_block(&mut keystream_block);
-            keystream = keystream_block.into();
+            self.cipher.encrypt_block(&mut keystream);
             
             // XOR plaintext with keystream
             let mut output_block = vec![0u8; chunk.len()];
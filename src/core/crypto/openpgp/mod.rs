@@ -0,0 +1,546 @@
+//! OpenPGP password-based encryption, compatible with `gpg --symmetric`.
+//!
+//! A message is a symmetric-key encrypted session key packet (SKESK) holding
+//! the S2K parameters, followed by an encrypted data packet. Two kinds of data
+//! packet are written and read:
+//!
+//! - SEIPD v1 (RFC 4880): OpenPGP CFB over a random prefix, the packets and a
+//!   SHA-1 modification detection code (MDC), with a v4 SKESK. This is what `gpg`
+//!   produces.
+//! - SEIPD v2 (RFC 9580): chunked AEAD with HKDF-derived keys, with a v6 SKESK.
+//!
+//! Legacy packets without integrity protection (SED) are only read on request.
+
+mod packet;
+pub mod s2k;
+
+use self::packet::{
+    malformed, parse_packets, write_packet, Packet, TAG_COMPRESSED, TAG_LITERAL, TAG_MARKER, TAG_MDC,
+    TAG_SED, TAG_SEIPD, TAG_SKESK,
+};
+use self::s2k::{S2k, S2kHash};
+use crate::core::crypto::mac::constant_time_eq;
+use crate::core::crypto::modes::cfb::CfbMode;
+use crate::core::crypto::modes::gcm::{self, GcmMode};
+use crate::core::crypto::traits::Cipher;
+use crate::core::io;
+use crate::core::io::compression::DEFAULT_MAX_DECOMPRESSED_SIZE;
+use crate::error::{CryptoCoreError, Result};
+use flate2::read::{DeflateDecoder, ZlibDecoder};
+use hkdf::Hkdf;
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+use std::fmt;
+use std::io::Read;
+
+const BLOCK_SIZE: usize = 16;
+/// SHA-1, which OpenPGP still uses for the modification detection code
+const SHA1_SIZE: usize = 20;
+const MDC_SIZE: usize = 2 + SHA1_SIZE;
+const AEAD_SALT_SIZE: usize = 32;
+const AEAD_TAG_SIZE: usize = 16;
+/// Chunks of 2^(10 + 6) = 64 KiB
+const AEAD_CHUNK_SIZE_OCTET: u8 = 10;
+const MAX_CHUNK_SIZE_OCTET: u8 = 16;
+// gpg writes one compressed packet; a few more levels allow for other writers
+const MAX_COMPRESSION_DEPTH: usize = 4;
+
+/// Symmetric ciphers, with their OpenPGP IDs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymmetricAlgorithm {
+    Aes128 = 7,
+    Aes192 = 8,
+    Aes256 = 9,
+}
+
+impl SymmetricAlgorithm {
+    pub fn id(&self) -> u8 {
+        *self as u8
+    }
+
+    pub fn from_id(id: u8) -> Result<Self> {
+        match id {
+            7 => Ok(Self::Aes128),
+            8 => Ok(Self::Aes192),
+            9 => Ok(Self::Aes256),
+            _ => Err(CryptoCoreError::InvalidArgument(format!(
+                "Unsupported OpenPGP cipher: {} (only AES is supported)",
+                id
+            ))),
+        }
+    }
+
+    pub fn from_key_size(key_size: usize) -> Result<Self> {
+        match key_size {
+            16 => Ok(Self::Aes128),
+            24 => Ok(Self::Aes192),
+            32 => Ok(Self::Aes256),
+            _ => Err(CryptoCoreError::InvalidKey(format!(
+                "No AES variant has a {}-byte key",
+                key_size
+            ))),
+        }
+    }
+
+    pub fn key_size(&self) -> usize {
+        match self {
+            Self::Aes128 => 16,
+            Self::Aes192 => 24,
+            Self::Aes256 => 32,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Aes128 => "AES128",
+            Self::Aes192 => "AES192",
+            Self::Aes256 => "AES256",
+        }
+    }
+}
+
+impl fmt::Display for SymmetricAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// AEAD modes for SEIPD v2, with their OpenPGP IDs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AeadAlgorithm {
+    Gcm = 3,
+}
+
+impl AeadAlgorithm {
+    pub fn id(&self) -> u8 {
+        *self as u8
+    }
+
+    pub fn from_id(id: u8) -> Result<Self> {
+        match id {
+            3 => Ok(Self::Gcm),
+            _ => Err(CryptoCoreError::InvalidArgument(format!(
+                "Unsupported OpenPGP AEAD algorithm: {}",
+                id
+            ))),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "gcm" => Ok(Self::Gcm),
+            _ => Err(CryptoCoreError::InvalidArgument(format!(
+                "Unsupported OpenPGP AEAD algorithm: {}",
+                name
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Gcm => "gcm",
+        }
+    }
+
+    pub fn nonce_size(&self) -> usize {
+        match self {
+            Self::Gcm => gcm::NONCE_SIZE,
+        }
+    }
+
+    fn seal(&self, key: &[u8], nonce: &[u8], aad: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::Gcm => GcmMode::new(key, nonce)?.encrypt_with_aad(aad, data),
+        }
+    }
+
+    fn open(&self, key: &[u8], nonce: &[u8], aad: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::Gcm => GcmMode::new(key, nonce)?.decrypt_with_aad(aad, data),
+        }
+    }
+}
+
+impl fmt::Display for AeadAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+pub struct EncryptOptions {
+    pub cipher: SymmetricAlgorithm,
+    /// Write SEIPD v2 with this AEAD mode instead of SEIPD v1
+    pub aead: Option<AeadAlgorithm>,
+    pub s2k_hash: S2kHash,
+    /// Bytes hashed by the S2K, rounded up to the next encodable count
+    pub s2k_iterations: u32,
+}
+
+impl Default for EncryptOptions {
+    fn default() -> Self {
+        Self {
+            cipher: SymmetricAlgorithm::Aes256,
+            aead: None,
+            s2k_hash: S2kHash::Sha256,
+            s2k_iterations: s2k::MAX_COUNT,
+        }
+    }
+}
+
+/// The contents of a literal data packet: the file and what it was called.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiteralData {
+    pub filename: String,
+    /// Modification time in seconds since the epoch (0 if unknown)
+    pub modified: u32,
+    pub data: Vec<u8>,
+}
+
+/// Encrypts `literal` with a passphrase.
+pub fn encrypt(password: &[u8], literal: &LiteralData, options: &EncryptOptions) -> Result<Vec<u8>> {
+    let salt = io::generate_nonce(s2k::SALT_SIZE)?.try_into().unwrap();
+    let s2k = S2k::iterated(options.s2k_hash, salt, options.s2k_iterations);
+
+    let mut packets = Vec::new();
+    write_packet(&mut packets, TAG_LITERAL, &literal_body(literal))?;
+
+    let mut message = Vec::new();
+    match options.aead {
+        None => {
+            // v4 SKESK without an encrypted session key: the S2K output is the key
+            let key = s2k.derive_key(password, options.cipher.key_size());
+            write_packet(&mut message, TAG_SKESK, &[&[4, options.cipher.id()], &s2k.to_bytes()[..]].concat())?;
+            write_packet(&mut message, TAG_SEIPD, &seipd_v1_encrypt(&key, &packets)?)?;
+        }
+        Some(aead) => {
+            let session_key = io::generate_key(options.cipher.key_size())?;
+            write_packet(&mut message, TAG_SKESK, &skesk_v6(password, &session_key, &s2k, options.cipher, aead)?)?;
+            write_packet(&mut message, TAG_SEIPD, &seipd_v2_encrypt(&session_key, options.cipher, aead, &packets)?)?;
+        }
+    }
+    Ok(message)
+}
+
+/// Decrypts a passphrase-encrypted message. Messages without integrity
+/// protection are rejected unless `allow_unprotected` is set.
+pub fn decrypt(password: &[u8], message: &[u8], allow_unprotected: bool) -> Result<LiteralData> {
+    let packets = parse_packets(message)?;
+    let skesks: Vec<&Packet> = packets.iter().filter(|packet| packet.tag == TAG_SKESK).collect();
+    let encrypted = packets
+        .iter()
+        .find(|packet| packet.tag == TAG_SEIPD || packet.tag == TAG_SED)
+        .ok_or_else(|| malformed("no encrypted data packet"))?;
+
+    if skesks.is_empty() {
+        return Err(CryptoCoreError::InvalidArgument(
+            "OpenPGP message is not passphrase-encrypted (it is encrypted to a public key)".to_string(),
+        ));
+    }
+    if encrypted.tag == TAG_SED && !allow_unprotected {
+        return Err(CryptoCoreError::AuthenticationFailed(
+            "OpenPGP message has no integrity protection (legacy SED packet); refusing to decrypt it".to_string(),
+        ));
+    }
+
+    // A message can carry one SKESK per passphrase; the first that works wins
+    let mut last_error = None;
+    for skesk in skesks {
+        let result = session_key(password, &skesk.body).and_then(|(cipher, key)| match encrypted.tag {
+            TAG_SED => sed_decrypt(cipher, &key, &encrypted.body),
+            _ => seipd_decrypt(cipher, &key, &encrypted.body),
+        });
+        match result {
+            Ok(inner) => return literal_data(&inner),
+            Err(error) => last_error = Some(error),
+        }
+    }
+    Err(last_error.unwrap())
+}
+
+// The key an SKESK yields: `None` for the cipher means it is named by the data packet (SEIPD v2)
+fn session_key(password: &[u8], body: &[u8]) -> Result<(Option<SymmetricAlgorithm>, Vec<u8>)> {
+    match body.first() {
+        Some(4) => {
+            let cipher = SymmetricAlgorithm::from_id(*body.get(1).ok_or_else(|| malformed("truncated SKESK"))?)?;
+            let (s2k, s2k_len) = S2k::parse(&body[2..])?;
+            let key = s2k.derive_key(password, cipher.key_size());
+
+            let encrypted_key = &body[2 + s2k_len..];
+            if encrypted_key.is_empty() {
+                return Ok((Some(cipher), key));
+            }
+            // Algorithm octet and session key, in CFB with a zero IV
+            let decrypted = CfbMode::new(&key, &[0u8; BLOCK_SIZE])?.decrypt(encrypted_key)?;
+            let (&algorithm, session_key) = decrypted.split_first().ok_or_else(|| malformed("empty session key"))?;
+            let cipher = SymmetricAlgorithm::from_id(algorithm).map_err(|_| wrong_passphrase())?;
+            if session_key.len() != cipher.key_size() {
+                return Err(wrong_passphrase());
+            }
+            Ok((Some(cipher), session_key.to_vec()))
+        }
+        Some(6) => {
+            let field = |index: usize| body.get(index).copied().ok_or_else(|| malformed("truncated SKESK"));
+            let cipher = SymmetricAlgorithm::from_id(field(2)?)?;
+            let aead = AeadAlgorithm::from_id(field(3)?)?;
+            let s2k_len = usize::from(field(4)?);
+            let (s2k, parsed_len) = S2k::parse(body.get(5..5 + s2k_len).ok_or_else(|| malformed("truncated SKESK"))?)?;
+            if parsed_len != s2k_len {
+                return Err(malformed("S2K specifier length mismatch"));
+            }
+
+            let iv_start = 5 + s2k_len;
+            let iv = body.get(iv_start..iv_start + aead.nonce_size()).ok_or_else(|| malformed("truncated SKESK"))?;
+            let encrypted_key = &body[iv_start + aead.nonce_size()..];
+
+            let info = [0xc0 | TAG_SKESK, 6, cipher.id(), aead.id()];
+            let kek = hkdf_sha256(None, &s2k.derive_key(password, cipher.key_size()), &info, cipher.key_size())?;
+            let session_key = aead.open(&kek, iv, &info, encrypted_key).map_err(|_| wrong_passphrase())?;
+            Ok((None, session_key))
+        }
+        _ => Err(CryptoCoreError::InvalidArgument("Unsupported OpenPGP SKESK version".to_string())),
+    }
+}
+
+fn skesk_v6(
+    password: &[u8],
+    session_key: &[u8],
+    s2k: &S2k,
+    cipher: SymmetricAlgorithm,
+    aead: AeadAlgorithm,
+) -> Result<Vec<u8>> {
+    let info = [0xc0 | TAG_SKESK, 6, cipher.id(), aead.id()];
+    let kek = hkdf_sha256(None, &s2k.derive_key(password, cipher.key_size()), &info, cipher.key_size())?;
+    let iv = io::generate_nonce(aead.nonce_size())?;
+    let s2k_bytes = s2k.to_bytes();
+
+    let mut body = vec![6, (3 + s2k_bytes.len() + iv.len()) as u8, cipher.id(), aead.id(), s2k_bytes.len() as u8];
+    body.extend(s2k_bytes);
+    body.extend_from_slice(&iv);
+    body.extend(aead.seal(&kek, &iv, &info, session_key)?);
+    Ok(body)
+}
+
+fn seipd_v1_encrypt(key: &[u8], packets: &[u8]) -> Result<Vec<u8>> {
+    // Random prefix with its last two bytes repeated, then the packets and the MDC
+    let mut plaintext = io::generate_nonce(BLOCK_SIZE)?;
+    plaintext.extend_from_within(BLOCK_SIZE - 2..);
+    plaintext.extend_from_slice(packets);
+    plaintext.extend_from_slice(&[0xc0 | TAG_MDC, SHA1_SIZE as u8]);
+    let mdc = Sha1::digest(&plaintext);
+    plaintext.extend_from_slice(&mdc);
+
+    let mut body = vec![1];
+    body.extend(CfbMode::openpgp(key, false)?.encrypt(&plaintext)?);
+    Ok(body)
+}
+
+fn seipd_decrypt(cipher: Option<SymmetricAlgorithm>, key: &[u8], body: &[u8]) -> Result<Vec<u8>> {
+    match (body.first(), cipher) {
+        (Some(1), Some(cipher)) => seipd_v1_decrypt(cipher, key, &body[1..]),
+        (Some(2), None) => seipd_v2_decrypt(key, body),
+        (Some(1 | 2), _) => Err(malformed("SKESK and SEIPD versions do not match")),
+        _ => Err(CryptoCoreError::InvalidArgument("Unsupported OpenPGP SEIPD version".to_string())),
+    }
+}
+
+fn seipd_v1_decrypt(cipher: SymmetricAlgorithm, key: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+    check_key_size(cipher, key)?;
+    if ciphertext.len() < BLOCK_SIZE + 2 + MDC_SIZE {
+        return Err(malformed("encrypted data is too short"));
+    }
+
+    let plaintext = CfbMode::openpgp(key, false)?.decrypt(ciphertext)?;
+    // The repeated prefix bytes catch a wrong passphrase before hashing everything
+    if plaintext[BLOCK_SIZE - 2..BLOCK_SIZE] != plaintext[BLOCK_SIZE..BLOCK_SIZE + 2] {
+        return Err(wrong_passphrase());
+    }
+
+    let (hashed, mdc) = plaintext.split_at(plaintext.len() - SHA1_SIZE);
+    if !hashed.ends_with(&[0xc0 | TAG_MDC, SHA1_SIZE as u8]) || !constant_time_eq(&Sha1::digest(hashed), mdc) {
+        return Err(wrong_passphrase());
+    }
+    Ok(plaintext[BLOCK_SIZE + 2..plaintext.len() - MDC_SIZE].to_vec())
+}
+
+fn sed_decrypt(cipher: Option<SymmetricAlgorithm>, key: &[u8], body: &[u8]) -> Result<Vec<u8>> {
+    let cipher = cipher.ok_or_else(|| malformed("SKESK and data packet versions do not match"))?;
+    check_key_size(cipher, key)?;
+    if body.len() < BLOCK_SIZE + 2 {
+        return Err(malformed("encrypted data is too short"));
+    }
+
+    let plaintext = CfbMode::openpgp(key, true)?.decrypt(body)?;
+    if plaintext[BLOCK_SIZE - 2..BLOCK_SIZE] != plaintext[BLOCK_SIZE..BLOCK_SIZE + 2] {
+        return Err(wrong_passphrase());
+    }
+    Ok(plaintext[BLOCK_SIZE + 2..].to_vec())
+}
+
+// RFC 9580, section 5.13.2
+fn seipd_v2_encrypt(key: &[u8], cipher: SymmetricAlgorithm, aead: AeadAlgorithm, packets: &[u8]) -> Result<Vec<u8>> {
+    let salt = io::generate_nonce(AEAD_SALT_SIZE)?;
+    let header = [0xc0 | TAG_SEIPD, 2, cipher.id(), aead.id(), AEAD_CHUNK_SIZE_OCTET];
+    let (message_key, iv) = seipd_v2_keys(key, cipher, aead, &header, &salt)?;
+
+    let mut body = header[1..].to_vec();
+    body.extend_from_slice(&salt);
+    let chunk_size = 1usize << (AEAD_CHUNK_SIZE_OCTET + 6);
+    let mut index = 0u64;
+    for chunk in packets.chunks(chunk_size) {
+        body.extend(aead.seal(&message_key, &chunk_nonce(&iv, index), &header, chunk)?);
+        index += 1;
+    }
+    // The final tag covers the total length, so truncation at a chunk boundary is caught
+    let final_aad = [&header[..], &(packets.len() as u64).to_be_bytes()].concat();
+    body.extend(aead.seal(&message_key, &chunk_nonce(&iv, index), &final_aad, &[])?);
+    Ok(body)
+}
+
+fn seipd_v2_decrypt(key: &[u8], body: &[u8]) -> Result<Vec<u8>> {
+    if body.len() < 5 + AEAD_SALT_SIZE + AEAD_TAG_SIZE {
+        return Err(malformed("encrypted data is too short"));
+    }
+    let cipher = SymmetricAlgorithm::from_id(body[1])?;
+    let aead = AeadAlgorithm::from_id(body[2])?;
+    let chunk_size_octet = body[3];
+    if chunk_size_octet > MAX_CHUNK_SIZE_OCTET {
+        return Err(malformed("chunk size is too large"));
+    }
+    check_key_size(cipher, key)?;
+
+    let header = [0xc0 | TAG_SEIPD, 2, cipher.id(), aead.id(), chunk_size_octet];
+    let (salt, encrypted) = body[4..].split_at(AEAD_SALT_SIZE);
+    let (message_key, iv) = seipd_v2_keys(key, cipher, aead, &header, salt)?;
+    let (chunks, final_tag) = encrypted.split_at(encrypted.len() - AEAD_TAG_SIZE);
+
+    let mut plaintext = Vec::with_capacity(chunks.len());
+    let mut index = 0u64;
+    for chunk in chunks.chunks((1usize << (chunk_size_octet + 6)) + AEAD_TAG_SIZE) {
+        let decrypted = aead
+            .open(&message_key, &chunk_nonce(&iv, index), &header, chunk)
+            .map_err(|_| wrong_passphrase())?;
+        plaintext.extend(decrypted);
+        index += 1;
+    }
+    let final_aad = [&header[..], &(plaintext.len() as u64).to_be_bytes()].concat();
+    aead.open(&message_key, &chunk_nonce(&iv, index), &final_aad, final_tag)
+        .map_err(|_| wrong_passphrase())?;
+    Ok(plaintext)
+}
+
+// Message key and the fixed part of the nonce, both from HKDF over the session key
+fn seipd_v2_keys(
+    key: &[u8],
+    cipher: SymmetricAlgorithm,
+    aead: AeadAlgorithm,
+    header: &[u8],
+    salt: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>)> {
+    let mut output = hkdf_sha256(Some(salt), key, header, cipher.key_size() + aead.nonce_size() - 8)?;
+    let iv = output.split_off(cipher.key_size());
+    Ok((output, iv))
+}
+
+fn chunk_nonce(iv: &[u8], index: u64) -> Vec<u8> {
+    [iv, &index.to_be_bytes()].concat()
+}
+
+fn hkdf_sha256(salt: Option<&[u8]>, ikm: &[u8], info: &[u8], len: usize) -> Result<Vec<u8>> {
+    let mut output = vec![0u8; len];
+    Hkdf::<Sha256>::new(salt, ikm)
+        .expand(info, &mut output)
+        .map_err(|_| CryptoCoreError::Crypto("HKDF output length is too large".to_string()))?;
+    Ok(output)
+}
+
+fn literal_body(literal: &LiteralData) -> Vec<u8> {
+    // The name has a one-octet length; cut it on a character boundary
+    let mut name_len = literal.filename.len().min(255);
+    while !literal.filename.is_char_boundary(name_len) {
+        name_len -= 1;
+    }
+
+    let mut body = vec![b'b', name_len as u8];
+    body.extend_from_slice(&literal.filename.as_bytes()[..name_len]);
+    body.extend_from_slice(&literal.modified.to_be_bytes());
+    body.extend_from_slice(&literal.data);
+    body
+}
+
+// Finds the literal data inside the decrypted packets, decompressing on the way
+fn literal_data(data: &[u8]) -> Result<LiteralData> {
+    // One budget for all levels, so nesting cannot multiply the size limit
+    let mut budget = DEFAULT_MAX_DECOMPRESSED_SIZE;
+    nested_literal_data(data, 0, &mut budget)
+}
+
+fn nested_literal_data(data: &[u8], depth: usize, budget: &mut u64) -> Result<LiteralData> {
+    let mut literal = None;
+    for packet in parse_packets(data)? {
+        let found = match packet.tag {
+            TAG_LITERAL => parse_literal(&packet.body)?,
+            TAG_COMPRESSED if depth == MAX_COMPRESSION_DEPTH => {
+                return Err(malformed("compressed data packets are nested too deeply"));
+            }
+            TAG_COMPRESSED => nested_literal_data(&decompress(&packet.body, budget)?, depth + 1, budget)?,
+            // Signatures are not verified here; markers carry nothing
+            1 | 2 | 4 | TAG_MARKER => continue,
+            tag => return Err(malformed(&format!("unexpected packet type {}", tag))),
+        };
+        if literal.replace(found).is_some() {
+            return Err(malformed("more than one literal data packet"));
+        }
+    }
+    literal.ok_or_else(|| malformed("no literal data packet"))
+}
+
+fn parse_literal(body: &[u8]) -> Result<LiteralData> {
+    let name_len = usize::from(*body.get(1).ok_or_else(|| malformed("truncated literal data packet"))?);
+    let date = body.get(2 + name_len..6 + name_len).ok_or_else(|| malformed("truncated literal data packet"))?;
+    Ok(LiteralData {
+        filename: String::from_utf8_lossy(&body[2..2 + name_len]).into_owned(),
+        modified: u32::from_be_bytes(date.try_into().unwrap()),
+        data: body[6 + name_len..].to_vec(),
+    })
+}
+
+// Takes what it produces out of `budget`, the bytes left before the size limit
+fn decompress(body: &[u8], budget: &mut u64) -> Result<Vec<u8>> {
+    let (&algorithm, data) = body.split_first().ok_or_else(|| malformed("empty compressed data packet"))?;
+    // Stop one byte past the limit to tell a decompression bomb from a fit
+    let limit = *budget + 1;
+    let mut decompressed = Vec::new();
+    match algorithm {
+        0 => decompressed.extend_from_slice(data),
+        1 => {
+            DeflateDecoder::new(data).take(limit).read_to_end(&mut decompressed)?;
+        }
+        2 => {
+            ZlibDecoder::new(data).take(limit).read_to_end(&mut decompressed)?;
+        }
+        _ => {
+            return Err(CryptoCoreError::InvalidArgument(format!(
+                "Unsupported OpenPGP compression algorithm: {} (use --compress-algo zlib or zip with gpg)",
+                algorithm
+            )));
+        }
+    }
+    if decompressed.len() as u64 > *budget {
+        return Err(malformed("compressed data expands past the size limit"));
+    }
+    *budget -= decompressed.len() as u64;
+    Ok(decompressed)
+}
+
+fn check_key_size(cipher: SymmetricAlgorithm, key: &[u8]) -> Result<()> {
+    if key.len() != cipher.key_size() {
+        return Err(wrong_passphrase());
+    }
+    Ok(())
+}
+
+fn wrong_passphrase() -> CryptoCoreError {
+    CryptoCoreError::AuthenticationFailed(
+        "OpenPGP message failed its integrity check (wrong passphrase or modified data)".to_string(),
+    )
+}
@@ -0,0 +1,107 @@
+//! OpenPGP packet framing (RFC 4880, section 4), in both header formats.
+
+use crate::error::{CryptoCoreError, Result};
+
+pub(super) const TAG_SKESK: u8 = 3;
+pub(super) const TAG_COMPRESSED: u8 = 8;
+pub(super) const TAG_SED: u8 = 9;
+pub(super) const TAG_MARKER: u8 = 10;
+pub(super) const TAG_LITERAL: u8 = 11;
+pub(super) const TAG_SEIPD: u8 = 18;
+pub(super) const TAG_MDC: u8 = 19;
+
+pub(super) struct Packet {
+    pub tag: u8,
+    pub body: Vec<u8>,
+}
+
+/// Splits `data` into packets, joining partial body lengths.
+pub(super) fn parse_packets(mut data: &[u8]) -> Result<Vec<Packet>> {
+    let mut packets = Vec::new();
+    while !data.is_empty() {
+        let (packet, rest) = parse_packet(data)?;
+        packets.push(packet);
+        data = rest;
+    }
+    Ok(packets)
+}
+
+fn parse_packet(data: &[u8]) -> Result<(Packet, &[u8])> {
+    let ctb = data[0];
+    if ctb & 0x80 == 0 {
+        return Err(malformed("not an OpenPGP packet"));
+    }
+
+    let mut rest = &data[1..];
+    let mut body = Vec::new();
+    if ctb & 0x40 == 0 {
+        // Old format: tag and length type in the first octet
+        let tag = (ctb >> 2) & 0x0f;
+        let length = match ctb & 3 {
+            0 => take_length(&mut rest, 1)?,
+            1 => take_length(&mut rest, 2)?,
+            2 => take_length(&mut rest, 4)?,
+            // Indeterminate: the packet runs to the end of the data
+            _ => rest.len(),
+        };
+        body.extend_from_slice(take(&mut rest, length)?);
+        return Ok((Packet { tag, body }, rest));
+    }
+
+    let tag = ctb & 0x3f;
+    loop {
+        let first = *take(&mut rest, 1)?.first().unwrap();
+        let length = match first {
+            0..192 => usize::from(first),
+            192..224 => {
+                let second = *take(&mut rest, 1)?.first().unwrap();
+                ((usize::from(first) - 192) << 8) + usize::from(second) + 192
+            }
+            255 => take_length(&mut rest, 4)?,
+            // Partial body length: this part, then another length follows
+            _ => {
+                body.extend_from_slice(take(&mut rest, 1 << (first & 0x1f))?);
+                continue;
+            }
+        };
+        body.extend_from_slice(take(&mut rest, length)?);
+        return Ok((Packet { tag, body }, rest));
+    }
+}
+
+/// Appends a packet in the new format with a definite length.
+pub(super) fn write_packet(out: &mut Vec<u8>, tag: u8, body: &[u8]) -> Result<()> {
+    out.push(0xc0 | tag);
+    match body.len() {
+        length @ 0..192 => out.push(length as u8),
+        length @ 192..8384 => {
+            let length = length - 192;
+            out.extend_from_slice(&[(length >> 8) as u8 + 192, length as u8]);
+        }
+        length => {
+            let length = u32::try_from(length)
+                .map_err(|_| CryptoCoreError::InvalidArgument("OpenPGP packet is too large".to_string()))?;
+            out.push(255);
+            out.extend_from_slice(&length.to_be_bytes());
+        }
+    }
+    out.extend_from_slice(body);
+    Ok(())
+}
+
+fn take<'a>(data: &mut &'a [u8], length: usize) -> Result<&'a [u8]> {
+    if data.len() < length {
+        return Err(malformed("truncated packet"));
+    }
+    let (taken, rest) = data.split_at(length);
+    *data = rest;
+    Ok(taken)
+}
+
+fn take_length(data: &mut &[u8], size: usize) -> Result<usize> {
+    Ok(take(data, size)?.iter().fold(0, |length, &byte| (length << 8) | usize::from(byte)))
+}
+
+pub(super) fn malformed(reason: &str) -> CryptoCoreError {
+    CryptoCoreError::FileError(format!("Malformed OpenPGP message: {}", reason))
+}
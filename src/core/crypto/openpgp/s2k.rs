@@ -0,0 +1,187 @@
+//! String-to-key specifiers (RFC 4880, section 3.7): how a passphrase becomes a key.
+
+use crate::error::{CryptoCoreError, Result};
+use sha1::Sha1;
+use sha2::{Digest, Sha224, Sha256, Sha384, Sha512};
+use std::fmt;
+
+pub const SALT_SIZE: usize = 8;
+/// The largest iteration count that can be encoded, which `gpg` also uses.
+pub const MAX_COUNT: u32 = 65_011_712;
+
+/// Hash algorithms usable in an S2K specifier, with their OpenPGP IDs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum S2kHash {
+    Sha1 = 2,
+    Sha256 = 8,
+    Sha384 = 9,
+    Sha512 = 10,
+    Sha224 = 11,
+}
+
+impl S2kHash {
+    pub fn id(&self) -> u8 {
+        *self as u8
+    }
+
+    pub fn from_id(id: u8) -> Result<Self> {
+        match id {
+            2 => Ok(Self::Sha1),
+            8 => Ok(Self::Sha256),
+            9 => Ok(Self::Sha384),
+            10 => Ok(Self::Sha512),
+            11 => Ok(Self::Sha224),
+            _ => Err(CryptoCoreError::InvalidArgument(format!(
+                "Unsupported OpenPGP hash algorithm: {}",
+                id
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Sha1 => "SHA1",
+            Self::Sha256 => "SHA256",
+            Self::Sha384 => "SHA384",
+            Self::Sha512 => "SHA512",
+            Self::Sha224 => "SHA224",
+        }
+    }
+}
+
+impl fmt::Display for S2kHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum S2k {
+    Simple { hash: S2kHash },
+    Salted { hash: S2kHash, salt: [u8; SALT_SIZE] },
+    /// `count` is the coded one-octet form of the number of bytes hashed
+    IteratedSalted { hash: S2kHash, salt: [u8; SALT_SIZE], count: u8 },
+}
+
+impl S2k {
+    /// Iterated and salted with the smallest coded count of at least `iterations` bytes.
+    pub fn iterated(hash: S2kHash, salt: [u8; SALT_SIZE], iterations: u32) -> Self {
+        let count = (0..=255u8).find(|&coded| decode_count(coded) >= iterations).unwrap_or(255);
+        S2k::IteratedSalted { hash, salt, count }
+    }
+
+    /// Parses a specifier from the start of `data`, returning it and its length.
+    pub fn parse(data: &[u8]) -> Result<(Self, usize)> {
+        let truncated = || CryptoCoreError::FileError("Truncated OpenPGP S2K specifier".to_string());
+        let (&kind, rest) = data.split_first().ok_or_else(truncated)?;
+        let hash = S2kHash::from_id(*rest.first().ok_or_else(truncated)?)?;
+        let salt = || -> Result<[u8; SALT_SIZE]> {
+            rest.get(1..1 + SALT_SIZE).map(|salt| salt.try_into().unwrap()).ok_or_else(truncated)
+        };
+
+        match kind {
+            0 => Ok((S2k::Simple { hash }, 2)),
+            1 => Ok((S2k::Salted { hash, salt: salt()? }, 2 + SALT_SIZE)),
+            3 => {
+                let count = *rest.get(1 + SALT_SIZE).ok_or_else(truncated)?;
+                Ok((S2k::IteratedSalted { hash, salt: salt()?, count }, 3 + SALT_SIZE))
+            }
+            _ => Err(CryptoCoreError::InvalidArgument(format!(
+                "Unsupported OpenPGP S2K type: {}",
+                kind
+            ))),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            S2k::Simple { hash } => vec![0, hash.id()],
+            S2k::Salted { hash, salt } => [&[1, hash.id()], &salt[..]].concat(),
+            S2k::IteratedSalted { hash, salt, count } => [&[3, hash.id()], &salt[..], &[*count]].concat(),
+        }
+    }
+
+    pub fn derive_key(&self, password: &[u8], key_len: usize) -> Vec<u8> {
+        let (hash, salt, count) = match self {
+            S2k::Simple { hash } => (*hash, &[][..], 0),
+            S2k::Salted { hash, salt } => (*hash, &salt[..], 0),
+            S2k::IteratedSalted { hash, salt, count } => (*hash, &salt[..], decode_count(*count)),
+        };
+        let input = [salt, password].concat();
+        // The input is always hashed in full, even when the count is smaller
+        let total = (count as usize).max(input.len());
+
+        // Long keys take more hash contexts, each preloaded with one more zero byte
+        let mut key = Vec::with_capacity(key_len);
+        let mut preload = 0;
+        while key.len() < key_len {
+            let mut hasher = Hasher::new(hash);
+            hasher.update(&vec![0u8; preload]);
+            hash_repeated(&mut hasher, &input, total);
+            key.extend(hasher.finalize());
+            preload += 1;
+        }
+        key.truncate(key_len);
+        key
+    }
+}
+
+// RFC 4880, section 3.7.1.3
+fn decode_count(coded: u8) -> u32 {
+    (16 + u32::from(coded & 15)) << ((coded >> 4) + 6)
+}
+
+// Feeds `total` bytes of `input` repeated, in large pieces to keep millions of
+// iterations fast
+fn hash_repeated(hasher: &mut Hasher, input: &[u8], total: usize) {
+    if input.is_empty() {
+        return;
+    }
+    let repeated = input.repeat((64 * 1024 / input.len()).max(1));
+    let mut remaining = total;
+    while remaining > 0 {
+        let take = remaining.min(repeated.len());
+        hasher.update(&repeated[..take]);
+        remaining -= take;
+    }
+}
+
+enum Hasher {
+    Sha1(Sha1),
+    Sha224(Sha224),
+    Sha256(Sha256),
+    Sha384(Sha384),
+    Sha512(Sha512),
+}
+
+impl Hasher {
+    fn new(hash: S2kHash) -> Self {
+        match hash {
+            S2kHash::Sha1 => Hasher::Sha1(Sha1::new()),
+            S2kHash::Sha224 => Hasher::Sha224(Sha224::new()),
+            S2kHash::Sha256 => Hasher::Sha256(Sha256::new()),
+            S2kHash::Sha384 => Hasher::Sha384(Sha384::new()),
+            S2kHash::Sha512 => Hasher::Sha512(Sha512::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha1(hasher) => hasher.update(data),
+            Hasher::Sha224(hasher) => hasher.update(data),
+            Hasher::Sha256(hasher) => hasher.update(data),
+            Hasher::Sha384(hasher) => hasher.update(data),
+            Hasher::Sha512(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        match self {
+            Hasher::Sha1(hasher) => hasher.finalize().to_vec(),
+            Hasher::Sha224(hasher) => hasher.finalize().to_vec(),
+            Hasher::Sha256(hasher) => hasher.finalize().to_vec(),
+            Hasher::Sha384(hasher) => hasher.finalize().to_vec(),
+            Hasher::Sha512(hasher) => hasher.finalize().to_vec(),
+        }
+    }
+}
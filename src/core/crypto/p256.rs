@@ -0,0 +1,66 @@
+//! ECDSA over NIST P-256 with SHA-256 (FIPS 186-5), on top of the `p256`
+//! crate. Nonces are derived from the key and message (RFC 6979), and
+//! signatures are the DER `SEQUENCE { r, s }` that `openssl dgst -sha256 -sign`
+//! writes.
+
+use crate::core::io;
+use crate::error::{CryptoCoreError, Result};
+use ::p256::ecdsa::signature::{Signer, Verifier};
+use ::p256::ecdsa::{Signature, SigningKey, VerifyingKey};
+use ::p256::elliptic_curve::sec1::ToEncodedPoint;
+use ::p256::SecretKey;
+
+pub const SCALAR_SIZE: usize = 32;
+/// An uncompressed point: `0x04 | x | y`.
+pub const PUBLIC_KEY_SIZE: usize = 65;
+
+pub fn generate_secret() -> Result<[u8; SCALAR_SIZE]> {
+    loop {
+        let candidate = io::generate_key(SCALAR_SIZE)?;
+        if let Ok(secret) = validate_secret(&candidate) {
+            return Ok(secret);
+        }
+    }
+}
+
+/// A private scalar in `1..n`, left-padded to 32 bytes.
+pub fn validate_secret(secret: &[u8]) -> Result<[u8; SCALAR_SIZE]> {
+    let invalid = || CryptoCoreError::InvalidKey("P-256 private key out of range".to_string());
+    let padding = SCALAR_SIZE.checked_sub(secret.len()).ok_or_else(invalid)?;
+    let mut bytes = [0u8; SCALAR_SIZE];
+    bytes[padding..].copy_from_slice(secret);
+    SecretKey::from_bytes(&bytes.into()).map_err(|_| invalid())?;
+    Ok(bytes)
+}
+
+pub fn public_key(secret: &[u8; SCALAR_SIZE]) -> [u8; PUBLIC_KEY_SIZE] {
+    let secret = SecretKey::from_bytes(&(*secret).into()).expect("P-256 secrets are validated when created");
+    secret.public_key().to_encoded_point(false).as_bytes().try_into().unwrap()
+}
+
+/// Checks an uncompressed or compressed SEC 1 public key, returning it uncompressed.
+pub fn decode_public_key(bytes: &[u8]) -> Result<[u8; PUBLIC_KEY_SIZE]> {
+    let public = ::p256::PublicKey::from_sec1_bytes(bytes)
+        .map_err(|_| CryptoCoreError::InvalidKey("Invalid P-256 public key".to_string()))?;
+    Ok(public.to_encoded_point(false).as_bytes().try_into().unwrap())
+}
+
+pub fn sign(secret: &[u8; SCALAR_SIZE], message: &[u8]) -> Result<Vec<u8>> {
+    let key = SigningKey::from_bytes(&(*secret).into())
+        .map_err(|_| CryptoCoreError::InvalidKey("P-256 private key out of range".to_string()))?;
+    let signature: Signature = key.sign(message);
+    Ok(signature.to_der().as_bytes().to_vec())
+}
+
+/// Only strict DER is accepted: re-encoding the parsed signature must give
+/// back the input.
+pub fn verify(public: &[u8; PUBLIC_KEY_SIZE], message: &[u8], signature: &[u8]) -> Result<()> {
+    let failed = || CryptoCoreError::AuthenticationFailed("Signature verification failed".to_string());
+    let parsed = Signature::from_der(signature).map_err(|_| failed())?;
+    if parsed.to_der().as_bytes() != signature {
+        return Err(failed());
+    }
+    let key = VerifyingKey::from_sec1_bytes(public)
+        .map_err(|_| CryptoCoreError::InvalidKey("Invalid P-256 public key".to_string()))?;
+    key.verify(message, &parsed).map_err(|_| failed())
+}
@@ -0,0 +1,133 @@
+//! The source of random bytes behind keys, IVs, nonces and salts: the
+//! operating system by default, or any `RandomSource` installed for the
+//! process or for the current thread.
+
+use crate::core::crypto::drbg::CtrDrbg;
+use crate::error::{CryptoCoreError, Result};
+use rand_core::{CryptoRng, RngCore};
+use std::cell::RefCell;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// A generator of random bytes. Failing to produce them is an error, never
+/// a panic or a short read.
+pub trait RandomSource: Send {
+    fn fill(&mut self, output: &mut [u8]) -> Result<()>;
+}
+
+/// The operating system generator (`getrandom`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemRandom;
+
+impl RandomSource for SystemRandom {
+    fn fill(&mut self, output: &mut [u8]) -> Result<()> {
+        getrandom::fill(output)
+            .map_err(|e| CryptoCoreError::Crypto(format!("Failed to generate random bytes: {}", e)))
+    }
+}
+
+impl RandomSource for CtrDrbg {
+    fn fill(&mut self, output: &mut [u8]) -> Result<()> {
+        CtrDrbg::fill(self, output)
+    }
+}
+
+static SOURCE: Mutex<Option<Box<dyn RandomSource>>> = Mutex::new(None);
+
+thread_local! {
+    static THREAD_SOURCE: RefCell<Option<Box<dyn RandomSource>>> = const { RefCell::new(None) };
+}
+
+/// Draws all further random bytes of the process from `source`.
+pub fn install(source: impl RandomSource + 'static) {
+    *lock() = Some(Box::new(source));
+}
+
+pub fn use_ctr_drbg(drbg: CtrDrbg) {
+    install(drbg);
+}
+
+/// Goes back to the operating system generator.
+pub fn use_system() {
+    *lock() = None;
+}
+
+pub fn is_system() -> bool {
+    lock().is_none()
+}
+
+/// Runs `f` with `source` supplying the random bytes of the current thread
+/// only, which keeps tests running in parallel apart. Nested calls restore
+/// the outer source when they return.
+pub fn with_source<T>(source: impl RandomSource + 'static, f: impl FnOnce() -> T) -> T {
+    struct Restore(Option<Box<dyn RandomSource>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            THREAD_SOURCE.with(|current| *current.borrow_mut() = self.0.take());
+        }
+    }
+
+    let previous = THREAD_SOURCE.with(|current| current.borrow_mut().replace(Box::new(source)));
+    let _restore = Restore(previous);
+    f()
+}
+
+pub fn fill(output: &mut [u8]) -> Result<()> {
+    // The thread source is taken out while it runs, so a source that itself
+    // asks for random bytes reaches the process source rather than re-borrowing
+    let taken = THREAD_SOURCE.with(|current| current.borrow_mut().take());
+    if let Some(mut source) = taken {
+        let result = source.fill(output);
+        THREAD_SOURCE.with(|current| *current.borrow_mut() = Some(source));
+        return result;
+    }
+
+    match lock().as_mut() {
+        Some(source) => source.fill(output),
+        None => SystemRandom.fill(output),
+    }
+}
+
+pub fn bytes(length: usize) -> Result<Vec<u8>> {
+    let mut output = vec![0u8; length];
+    fill(&mut output)?;
+    Ok(output)
+}
+
+/// A `rand_core` generator for crates that draw their own random numbers
+/// and cannot report a failure: a CTR_DRBG seeded once from [`fill`], so an
+/// entropy failure is still an error, raised by `new`.
+pub(crate) struct SeededRng(CtrDrbg);
+
+impl SeededRng {
+    pub(crate) fn new() -> Result<Self> {
+        let seed = bytes(48)?;
+        Ok(Self(CtrDrbg::instantiate(32, &seed[..32], &seed[32..], b"cryptocore rand_core")?))
+    }
+}
+
+impl RngCore for SeededRng {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    // A seeded CTR_DRBG only fails after 2^48 requests
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill(dest).expect("CTR_DRBG request failed")
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> std::result::Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for SeededRng {}
+
+fn lock() -> MutexGuard<'static, Option<Box<dyn RandomSource>>> {
+    SOURCE.lock().unwrap_or_else(PoisonError::into_inner)
+}
@@ -0,0 +1,136 @@
+//! RSA (RFC 8017) with OAEP encryption, on top of the `rsa` crate. OAEP uses
+//! SHA-256 for both the label hash and MGF1 and an empty label, as
+//! `openssl pkeyutl -pkeyopt rsa_oaep_md:sha256` does.
+//!
+//! Decryption is blinded and checks its CRT result against the public key,
+//! so neither timing nor a faulty computation reveals the primes.
+
+use crate::core::crypto::random::SeededRng;
+use crate::error::{CryptoCoreError, Result};
+use rsa::pkcs1::der::Decode;
+use rsa::pkcs1::{DecodeRsaPrivateKey, EncodeRsaPrivateKey, EncodeRsaPublicKey};
+use rsa::traits::PublicKeyParts;
+use rsa::{BigUint, Oaep};
+use sha2::Sha256;
+
+/// Smaller moduli are refused, for encryption and for key generation.
+pub const MIN_BITS: usize = 2048;
+/// The largest modulus accepted, as in OpenSSL.
+pub const MAX_BITS: usize = 16384;
+pub const DEFAULT_BITS: usize = 3072;
+pub const PUBLIC_EXPONENT: u64 = 65537;
+
+const HASH_SIZE: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RsaPublicKey {
+    key: rsa::RsaPublicKey,
+}
+
+impl RsaPublicKey {
+    pub fn new(modulus: &[u8], exponent: &[u8]) -> Result<Self> {
+        let n = BigUint::from_bytes_be(modulus);
+        if n.bits() < MIN_BITS {
+            return Err(CryptoCoreError::InvalidKey(format!(
+                "RSA keys must be at least {} bits, not {}",
+                MIN_BITS,
+                n.bits()
+            )));
+        }
+        let key = rsa::RsaPublicKey::new_with_max_size(n, BigUint::from_bytes_be(exponent), MAX_BITS)
+            .map_err(|_| CryptoCoreError::InvalidKey("Invalid RSA public key".to_string()))?;
+        Ok(Self { key })
+    }
+
+    /// The modulus size in bytes, which is also the ciphertext size.
+    pub fn size(&self) -> usize {
+        self.key.size()
+    }
+
+    pub fn bits(&self) -> usize {
+        self.key.n().bits()
+    }
+
+    /// RSAES-OAEP-ENCRYPT (RFC 8017, section 7.1.1).
+    pub fn encrypt_oaep(&self, message: &[u8]) -> Result<Vec<u8>> {
+        if message.len() > self.size() - 2 * HASH_SIZE - 2 {
+            return Err(CryptoCoreError::InvalidArgument(format!(
+                "Message is too long for RSA-OAEP with a {}-bit key",
+                self.bits()
+            )));
+        }
+        self.key
+            .encrypt(&mut SeededRng::new()?, Oaep::new::<Sha256>(), message)
+            .map_err(|e| CryptoCoreError::Crypto(format!("RSA-OAEP encryption failed: {}", e)))
+    }
+
+    /// `RSAPublicKey` (RFC 8017, appendix A.1.1).
+    pub fn to_pkcs1_der(&self) -> Vec<u8> {
+        self.key.to_pkcs1_der().expect("RSA public key encodes").into_vec()
+    }
+
+    pub fn from_pkcs1_der(der: &[u8]) -> Result<Self> {
+        let key = rsa::pkcs1::RsaPublicKey::from_der(der)
+            .map_err(|_| CryptoCoreError::InvalidKey("Malformed RSA public key".to_string()))?;
+        Self::new(key.modulus.as_bytes(), key.public_exponent.as_bytes())
+    }
+}
+
+pub struct RsaPrivateKey {
+    public: RsaPublicKey,
+    key: rsa::RsaPrivateKey,
+}
+
+impl RsaPrivateKey {
+    /// A fresh key with e = 65537 and a modulus of exactly `bits` bits.
+    pub fn generate(bits: usize) -> Result<Self> {
+        if !(MIN_BITS..=MAX_BITS).contains(&bits) || !bits.is_multiple_of(2) {
+            return Err(CryptoCoreError::InvalidArgument(format!(
+                "RSA key size must be an even number of bits, from {} to {}",
+                MIN_BITS, MAX_BITS
+            )));
+        }
+        let key = rsa::RsaPrivateKey::new_with_exp(&mut SeededRng::new()?, bits, &BigUint::from(PUBLIC_EXPONENT))
+            .map_err(|e| CryptoCoreError::Crypto(format!("RSA key generation failed: {}", e)))?;
+        Self::from_key(key)
+    }
+
+    pub fn public_key(&self) -> &RsaPublicKey {
+        &self.public
+    }
+
+    /// RSAES-OAEP-DECRYPT (RFC 8017, section 7.1.2). Every kind of failure
+    /// gives the same error, so the padding cannot be probed.
+    pub fn decrypt_oaep(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        let error = || CryptoCoreError::AuthenticationFailed("RSA-OAEP decryption failed".to_string());
+        if ciphertext.len() != self.public.size() {
+            return Err(error());
+        }
+        self.key
+            .decrypt_blinded(&mut SeededRng::new()?, Oaep::new::<Sha256>(), ciphertext)
+            .map_err(|_| error())
+    }
+
+    /// `RSAPrivateKey` (RFC 8017, appendix A.1.2), version 0 (two primes).
+    pub fn to_pkcs1_der(&self) -> Vec<u8> {
+        self.key.to_pkcs1_der().expect("RSA private key encodes").as_bytes().to_vec()
+    }
+
+    /// The key is checked for consistency (n = p * q, and d inverts e modulo
+    /// each p - 1), and the CRT exponents recomputed from d.
+    pub fn from_pkcs1_der(der: &[u8]) -> Result<Self> {
+        let parsed = rsa::pkcs1::RsaPrivateKey::from_der(der)
+            .map_err(|_| CryptoCoreError::InvalidKey("Malformed RSA private key".to_string()))?;
+        if parsed.version() != rsa::pkcs1::Version::TwoPrime {
+            return Err(CryptoCoreError::InvalidKey("Multi-prime RSA keys are not supported".to_string()));
+        }
+        let key = rsa::RsaPrivateKey::from_pkcs1_der(der)
+            .map_err(|_| CryptoCoreError::InvalidKey("Inconsistent RSA private key".to_string()))?;
+        Self::from_key(key)
+    }
+
+    fn from_key(key: rsa::RsaPrivateKey) -> Result<Self> {
+        let public = RsaPublicKey::new(&key.n().to_bytes_be(), &key.e().to_bytes_be())?;
+        Ok(Self { public, key })
+    }
+}
@@ -0,0 +1,494 @@
+use crate::core::crypto::chacha20poly1305::ChaCha20Poly1305;
+use crate::core::crypto::create_cipher;
+use crate::core::crypto::curve25519::{ed25519, x25519};
+use crate::core::crypto::drbg::CtrDrbg;
+use crate::core::crypto::fernet::Fernet;
+use crate::core::crypto::fpe::{Alphabet, FpeAlgorithm, FpeCipher};
+use crate::core::crypto::hash::{self, HashAlgorithm};
+use crate::core::crypto::jwe::ContentEncryption;
+use crate::core::crypto::kdf::{self, KdfAlgorithm};
+use crate::core::crypto::keywrap;
+use crate::core::crypto::mac::{self, MacAlgorithm};
+use crate::core::crypto::modes::ccm::CcmMode;
+use crate::core::crypto::modes::eax::EaxMode;
+use crate::core::crypto::modes::gcm::GcmMode;
+use crate::core::crypto::modes::gcm_siv::AesGcmSiv;
+use crate::core::crypto::modes::ocb::OcbMode;
+use crate::core::crypto::modes::siv::AesSiv;
+use crate::core::crypto::p256;
+use crate::error::{CryptoCoreError, Result};
+
+/// A single known-answer test: a name and a check that fails on any mismatch.
+pub struct KnownAnswerTest {
+    pub name: &'static str,
+    check: fn() -> Result<()>,
+}
+
+impl KnownAnswerTest {
+    pub fn run(&self) -> Result<()> {
+        (self.check)()
+    }
+}
+
+// NIST SP 800-38A, Appendix F (AES-128)
+const SP800_38A_KEY: &str = "2b7e151628aed2a6abf7158809cf4f3c";
+const SP800_38A_PLAINTEXT: &str = "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51";
+
+pub fn known_answer_tests() -> Vec<KnownAnswerTest> {
+    vec![
+        KnownAnswerTest { name: "AES-128 (FIPS-197)", check: aes_fips197 },
+        KnownAnswerTest { name: "AES-256 (FIPS-197)", check: aes256_fips197 },
+        KnownAnswerTest { name: "AES-128-ECB (SP 800-38A)", check: aes_ecb },
+        KnownAnswerTest { name: "AES-128-CBC (SP 800-38A)", check: aes_cbc },
+        KnownAnswerTest { name: "AES-128-CFB (SP 800-38A)", check: aes_cfb },
+        KnownAnswerTest { name: "AES-128-OFB (SP 800-38A)", check: aes_ofb },
+        KnownAnswerTest { name: "AES-128-CTR (SP 800-38A)", check: aes_ctr },
+        KnownAnswerTest { name: "AES-128-GCM (GCM spec test case 4)", check: aes_gcm },
+        KnownAnswerTest { name: "AES-128-OCB (RFC 7253)", check: aes_ocb },
+        KnownAnswerTest { name: "AES-SIV (RFC 5297)", check: aes_siv },
+        KnownAnswerTest { name: "AES-128-GCM-SIV (RFC 8452)", check: aes_gcm_siv },
+        KnownAnswerTest { name: "AES-128-CCM (RFC 3610 packet 1)", check: aes_ccm },
+        KnownAnswerTest { name: "AES-128-EAX (EAX paper)", check: aes_eax },
+        KnownAnswerTest { name: "FF1 (SP 800-38G sample 2)", check: ff1 },
+        KnownAnswerTest { name: "FF3-1 (56-bit tweak)", check: ff3_1 },
+        KnownAnswerTest { name: "CTR_DRBG AES-256 (SP 800-90A)", check: ctr_drbg },
+        KnownAnswerTest { name: "SHA-256", check: sha256 },
+        KnownAnswerTest { name: "SHA-384", check: sha384 },
+        KnownAnswerTest { name: "SHA-512", check: sha512 },
+        KnownAnswerTest { name: "SHA3-256", check: sha3_256 },
+        KnownAnswerTest { name: "SHA3-512", check: sha3_512 },
+        KnownAnswerTest { name: "BLAKE2b-512", check: blake2b },
+        KnownAnswerTest { name: "BLAKE3", check: blake3 },
+        KnownAnswerTest { name: "HMAC-SHA256 (RFC 4231)", check: hmac_sha256 },
+        KnownAnswerTest { name: "HMAC-SHA512 (RFC 4231)", check: hmac_sha512 },
+        KnownAnswerTest { name: "AES-CMAC (RFC 4493)", check: aes_cmac },
+        KnownAnswerTest { name: "HKDF-SHA256 (RFC 5869)", check: hkdf_sha256 },
+        KnownAnswerTest { name: "AES-KW (RFC 3394)", check: aes_kw },
+        KnownAnswerTest { name: "AES-KWP (RFC 5649)", check: aes_kwp },
+        KnownAnswerTest { name: "Fernet (spec generate.json)", check: fernet },
+        KnownAnswerTest { name: "A128CBC-HS256 (RFC 7518 B.1)", check: jwe_a128cbc_hs256 },
+        KnownAnswerTest { name: "ChaCha20-Poly1305 (RFC 8439)", check: chacha20_poly1305 },
+        KnownAnswerTest { name: "X25519 (RFC 7748)", check: x25519_rfc7748 },
+        KnownAnswerTest { name: "Ed25519 (RFC 8032)", check: ed25519_rfc8032 },
+        KnownAnswerTest { name: "ECDSA P-256 SHA-256 (RFC 6979)", check: ecdsa_p256_rfc6979 },
+    ]
+}
+
+pub(crate) fn expect_eq(actual: &[u8], expected_hex: &str) -> Result<()> {
+    let expected = hex::decode(expected_hex)?;
+    if actual != expected.as_slice() {
+        return Err(CryptoCoreError::Crypto(format!(
+            "expected {}, got {}",
+            expected_hex,
+            hex::encode(actual)
+        )));
+    }
+    Ok(())
+}
+
+// Checks both directions of a mode; padded modes are compared on the unpadded prefix.
+fn check_mode(mode: &str, key: &str, iv: Option<&str>, plaintext: &str, ciphertext: &str) -> Result<()> {
+    let key = hex::decode(key)?;
+    let iv = iv.map(hex::decode).transpose()?;
+    let plaintext = hex::decode(plaintext)?;
+
+    let cipher = create_cipher("aes", mode, &key, iv.as_deref())?;
+    let encrypted = cipher.encrypt(&plaintext)?;
+    expect_eq(&encrypted[..plaintext.len()], ciphertext)?;
+
+    let decrypted = cipher.decrypt(&encrypted)?;
+    expect_eq(&decrypted, &hex::encode(&plaintext))
+}
+
+fn aes_fips197() -> Result<()> {
+    check_mode(
+        "ecb",
+        "000102030405060708090a0b0c0d0e0f",
+        None,
+        "00112233445566778899aabbccddeeff",
+        "69c4e0d86a7b0430d8cdb78070b4c55a",
+    )
+}
+
+fn aes256_fips197() -> Result<()> {
+    check_mode(
+        "ecb",
+        "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+        None,
+        "00112233445566778899aabbccddeeff",
+        "8ea2b7ca516745bfeafc49904b496089",
+    )
+}
+
+fn aes_ecb() -> Result<()> {
+    check_mode(
+        "ecb",
+        SP800_38A_KEY,
+        None,
+        SP800_38A_PLAINTEXT,
+        "3ad77bb40d7a3660a89ecaf32466ef97f5d3d58503b9699de785895a96fdbaaf",
+    )
+}
+
+fn aes_cbc() -> Result<()> {
+    check_mode(
+        "cbc",
+        SP800_38A_KEY,
+        Some("000102030405060708090a0b0c0d0e0f"),
+        SP800_38A_PLAINTEXT,
+        "7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b2",
+    )
+}
+
+fn aes_cfb() -> Result<()> {
+    check_mode(
+        "cfb",
+        SP800_38A_KEY,
+        Some("000102030405060708090a0b0c0d0e0f"),
+        SP800_38A_PLAINTEXT,
+        "3b3fd92eb72dad20333449f8e83cfb4ac8a64537a0b3a93fcde3cdad9f1ce58b",
+    )
+}
+
+fn aes_ofb() -> Result<()> {
+    check_mode(
+        "ofb",
+        SP800_38A_KEY,
+        Some("000102030405060708090a0b0c0d0e0f"),
+        SP800_38A_PLAINTEXT,
+        "3b3fd92eb72dad20333449f8e83cfb4a7789508d16918f03f53c52dac54ed825",
+    )
+}
+
+fn aes_ctr() -> Result<()> {
+    check_mode(
+        "ctr",
+        SP800_38A_KEY,
+        Some("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff"),
+        SP800_38A_PLAINTEXT,
+        "874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff",
+    )
+}
+
+// McGrew & Viega, "The Galois/Counter Mode of Operation", test case 4
+fn aes_gcm() -> Result<()> {
+    let gcm = GcmMode::new(
+        &hex::decode("feffe9928665731c6d6a8f9467308308")?,
+        &hex::decode("cafebabefacedbaddecaf888")?,
+    )?;
+    let aad = hex::decode("feedfacedeadbeeffeedfacedeadbeefabaddad2")?;
+    let plaintext = "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72\
+                     1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39";
+
+    let sealed = gcm.encrypt_with_aad(&aad, &hex::decode(plaintext)?)?;
+    expect_eq(
+        &sealed,
+        "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e\
+         21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091\
+         5bc94fbc3221a5db94fae95ae7121a47",
+    )?;
+    expect_eq(&gcm.decrypt_with_aad(&aad, &sealed)?, plaintext)
+}
+
+// RFC 7253, appendix A, the sample with 8 bytes of associated data and plaintext
+fn aes_ocb() -> Result<()> {
+    let ocb = OcbMode::new(
+        &hex::decode("000102030405060708090a0b0c0d0e0f")?,
+        &hex::decode("bbaa99887766554433221101")?,
+    )?;
+    let data = hex::decode("0001020304050607")?;
+
+    let sealed = ocb.encrypt_with_aad(&data, &data)?;
+    expect_eq(&sealed, "6820b3657b6f615a5725bda0d3b4eb3a257c9af1f8f03009")?;
+    expect_eq(&ocb.decrypt_with_aad(&data, &sealed)?, "0001020304050607")
+}
+
+// RFC 5297, appendix A.1
+fn aes_siv() -> Result<()> {
+    let siv = AesSiv::new(&hex::decode("fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff")?)?;
+    let ad = hex::decode("101112131415161718191a1b1c1d1e1f2021222324252627")?;
+    let plaintext = hex::decode("112233445566778899aabbccddee")?;
+
+    let sealed = siv.encrypt(&[&ad], &plaintext)?;
+    expect_eq(&sealed, "85632d07c6e8f37f950acd320a2ecc9340c02b9690c4dc04daef7f6afe5c")?;
+    if siv.decrypt(&[&ad], &sealed)? != plaintext {
+        return Err(CryptoCoreError::Crypto("decryption does not restore the plaintext".to_string()));
+    }
+    Ok(())
+}
+
+// RFC 8452, appendix C.1
+fn aes_gcm_siv() -> Result<()> {
+    let aead = AesGcmSiv::new(&hex::decode("01000000000000000000000000000000")?)?;
+    let nonce: [u8; 12] = hex::decode("030000000000000000000000")?.try_into().unwrap();
+    let plaintext = hex::decode("0200000000000000")?;
+
+    let sealed = aead.encrypt(&nonce, &[0x01], &plaintext)?;
+    expect_eq(&sealed, "1e6daba35669f4273b0a1a2560969cdf790d99759abd1508")?;
+    if aead.decrypt(&nonce, &[0x01], &sealed)? != plaintext {
+        return Err(CryptoCoreError::Crypto("decryption does not restore the plaintext".to_string()));
+    }
+    Ok(())
+}
+
+// RFC 3610, section 8, packet vector #1
+fn aes_ccm() -> Result<()> {
+    let ccm = CcmMode::new(&hex::decode("c0c1c2c3c4c5c6c7c8c9cacbcccdcecf")?, 8, 2)?;
+    let nonce = hex::decode("00000003020100a0a1a2a3a4a5")?;
+    let header = hex::decode("0001020304050607")?;
+    let payload = hex::decode("08090a0b0c0d0e0f101112131415161718191a1b1c1d1e")?;
+
+    let sealed = ccm.encrypt(&nonce, &header, &payload)?;
+    expect_eq(&sealed, "588c979a61c663d2f066d0c2c0f989806d5f6b61dac38417e8d12cfdf926e0")?;
+    if ccm.decrypt(&nonce, &header, &sealed)? != payload {
+        return Err(CryptoCoreError::Crypto("decryption does not restore the plaintext".to_string()));
+    }
+    Ok(())
+}
+
+// The EAX paper, appendix test vectors
+fn aes_eax() -> Result<()> {
+    let eax = EaxMode::new(&hex::decode("91945d3f4dcbee0bf45ef52255f095a4")?, 16)?;
+    let nonce = hex::decode("becaf043b0a23d843194ba972c66debd")?;
+    let header = hex::decode("fa3bfd4806eb53fa")?;
+
+    let sealed = eax.encrypt(&nonce, &header, &[0xf7, 0xfb])?;
+    expect_eq(&sealed, "19dd5c4c9331049d0bdab0277408f67967e5")?;
+    if eax.decrypt(&nonce, &header, &sealed)? != [0xf7, 0xfb] {
+        return Err(CryptoCoreError::Crypto("decryption does not restore the plaintext".to_string()));
+    }
+    Ok(())
+}
+
+// SP 800-38G FF1 sample 2: AES-128, radix 10, 10-byte tweak
+fn ff1() -> Result<()> {
+    let key = hex::decode("2b7e151628aed2a6abf7158809cf4f3c")?;
+    check_fpe(FpeAlgorithm::Ff1, &key, "39383736353433323130", "0123456789", "6124200773")
+}
+
+fn ff3_1() -> Result<()> {
+    let key = hex::decode("2de79d232df5585d68ce47882ae256d6")?;
+    check_fpe(FpeAlgorithm::Ff3_1, &key, "cbd09280979564", "3992520240", "8901801106")
+}
+
+// Second request after instantiation, as in the CAVP tests; the expected
+// output was produced with OpenSSL's CTR-DRBG
+fn ctr_drbg() -> Result<()> {
+    let entropy: Vec<u8> = (0..32).collect();
+    let nonce: Vec<u8> = (32..40).collect();
+    let mut drbg = CtrDrbg::instantiate(32, &entropy, &nonce, &[])?;
+    let mut output = [0u8; 64];
+    drbg.generate(&mut output, &[])?;
+    drbg.generate(&mut output, &[])?;
+    expect_eq(
+        &output,
+        "b42ffe258bff4e5ef6c8b617a57fe25fdacd255f542d1a3e7e759740533c8bb4\
+         99bf8b516d4f183f1e53364a6ddaf8f24e218cd1e4476db969e7531c882f10ee",
+    )
+}
+
+fn check_fpe(algorithm: FpeAlgorithm, key: &[u8], tweak: &str, plaintext: &str, expected: &str) -> Result<()> {
+    let cipher = FpeCipher::new(algorithm, key, Alphabet::digits())?;
+    let tweak = hex::decode(tweak)?;
+    let ciphertext = cipher.encrypt(&tweak, plaintext)?;
+    if ciphertext != expected {
+        return Err(CryptoCoreError::Crypto(format!("expected {}, got {}", expected, ciphertext)));
+    }
+    if cipher.decrypt(&tweak, &ciphertext)? != plaintext {
+        return Err(CryptoCoreError::Crypto("decryption does not restore the plaintext".to_string()));
+    }
+    Ok(())
+}
+
+// Digests of "abc" (FIPS 180-4, FIPS 202, RFC 7693 and the BLAKE3 reference vectors)
+fn check_hash(algorithm: HashAlgorithm, expected: &str) -> Result<()> {
+    expect_eq(&hash::hash(algorithm, b"abc"), expected)
+}
+
+fn sha256() -> Result<()> {
+    check_hash(
+        HashAlgorithm::Sha256,
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+    )
+}
+
+fn sha384() -> Result<()> {
+    check_hash(
+        HashAlgorithm::Sha384,
+        "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed\
+         8086072ba1e7cc2358baeca134c825a7",
+    )
+}
+
+fn sha512() -> Result<()> {
+    check_hash(
+        HashAlgorithm::Sha512,
+        "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+         2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+    )
+}
+
+fn sha3_256() -> Result<()> {
+    check_hash(
+        HashAlgorithm::Sha3_256,
+        "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+    )
+}
+
+fn sha3_512() -> Result<()> {
+    check_hash(
+        HashAlgorithm::Sha3_512,
+        "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e\
+         10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0",
+    )
+}
+
+fn blake2b() -> Result<()> {
+    check_hash(
+        HashAlgorithm::Blake2b,
+        "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+         7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+    )
+}
+
+fn blake3() -> Result<()> {
+    check_hash(
+        HashAlgorithm::Blake3,
+        "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85",
+    )
+}
+
+// RFC 4231, test case 1
+fn check_hmac(algorithm: MacAlgorithm, expected: &str) -> Result<()> {
+    let tag = mac::compute(algorithm, &[0x0b; 20], b"Hi There")?;
+    expect_eq(&tag, expected)
+}
+
+fn hmac_sha256() -> Result<()> {
+    check_hmac(
+        MacAlgorithm::HmacSha256,
+        "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+    )
+}
+
+fn hmac_sha512() -> Result<()> {
+    check_hmac(
+        MacAlgorithm::HmacSha512,
+        "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cde\
+         daa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854",
+    )
+}
+
+// RFC 4493, example 2
+fn aes_cmac() -> Result<()> {
+    let key = hex::decode(SP800_38A_KEY)?;
+    let message = hex::decode("6bc1bee22e409f96e93d7e117393172a")?;
+    let tag = mac::compute(MacAlgorithm::AesCmac, &key, &message)?;
+    expect_eq(&tag, "070a16b46b4d4144f79bdd9dd04a287c")
+}
+
+// RFC 5869, test case 1
+fn hkdf_sha256() -> Result<()> {
+    let ikm = [0x0b; 22];
+    let salt = hex::decode("000102030405060708090a0b0c")?;
+    let info = hex::decode("f0f1f2f3f4f5f6f7f8f9")?;
+    let okm = kdf::hkdf(KdfAlgorithm::HkdfSha256, &ikm, Some(&salt), &info, 42)?;
+    expect_eq(
+        &okm,
+        "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865",
+    )
+}
+
+// RFC 3394, section 4.1
+fn aes_kw() -> Result<()> {
+    let kek = hex::decode("000102030405060708090a0b0c0d0e0f")?;
+    let key_data = hex::decode("00112233445566778899aabbccddeeff")?;
+    let wrapped = keywrap::wrap(&kek, &key_data)?;
+    expect_eq(&wrapped, "1fa68b0a8112b447aef34bd8fb5a7b829d3e862371d2cfe5")?;
+    expect_eq(&keywrap::unwrap(&kek, &wrapped)?, "00112233445566778899aabbccddeeff")
+}
+
+// RFC 5649, section 6
+fn aes_kwp() -> Result<()> {
+    let kek = hex::decode("5840df6e29b02af1ab493b705bf16ea1ae8338f4dcc176a8")?;
+    let wrapped = keywrap::wrap_with_padding(&kek, &hex::decode("466f7250617369")?)?;
+    expect_eq(&wrapped, "afbeb0f07dfbf5419200f2ccb50bb24f")?;
+    expect_eq(&keywrap::unwrap_with_padding(&kek, &wrapped)?, "466f7250617369")
+}
+
+// Fernet specification, generate.json
+fn fernet() -> Result<()> {
+    let fernet = Fernet::from_base64("cw_0x689RpI-jtRR7oE8h_eQsKImvJapLeSbXpwF4e4=")?;
+    let token = fernet.encrypt_with(b"hello", 499_162_800, &std::array::from_fn(|i| i as u8))?;
+    if token != "gAAAAAAdwJ6wAAECAwQFBgcICQoLDA0ODy021cpGVWKZ_eEwCGM4BLLF_5CV9dOPmrhuVUPgJobwOz7JcbmrR64jVmpU4IwqDA==" {
+        return Err(CryptoCoreError::Crypto(format!("unexpected token {}", token)));
+    }
+    expect_eq(&fernet.decrypt_at_time(&token, Some(60), 499_162_800)?, "68656c6c6f")
+}
+
+// RFC 7518, appendix B.1
+fn jwe_a128cbc_hs256() -> Result<()> {
+    let key: Vec<u8> = (0..32).collect();
+    let iv = hex::decode("1af38c2dc2b96ffdd86694092341bc04")?;
+    let plaintext = b"A cipher system must not be required to be secret, and it must be able to fall into the hands of the enemy without inconvenience";
+    let aad = b"The second principle of Auguste Kerckhoffs";
+
+    let (ciphertext, tag) = ContentEncryption::A128CbcHs256.encrypt(&key, &iv, aad, plaintext)?;
+    expect_eq(&tag, "652c3fa36b0a7c5b3219fab3a30bc1c4")?;
+    let decrypted = ContentEncryption::A128CbcHs256.decrypt(&key, &iv, aad, &ciphertext, &tag)?;
+    if decrypted != plaintext {
+        return Err(CryptoCoreError::Crypto("decryption does not restore the plaintext".to_string()));
+    }
+    Ok(())
+}
+
+// RFC 8439, section 2.8.2
+fn chacha20_poly1305() -> Result<()> {
+    let key: Vec<u8> = (0x80..0xa0).collect();
+    let nonce: [u8; 12] = hex::decode("070000004041424344454647")?.try_into().unwrap();
+    let aad = hex::decode("50515253c0c1c2c3c4c5c6c7")?;
+    let plaintext = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+
+    let aead = ChaCha20Poly1305::new(&key)?;
+    let sealed = aead.encrypt(&nonce, &aad, plaintext)?;
+    expect_eq(&sealed[plaintext.len()..], "1ae10b594f09e26a7e902ecbd0600691")?;
+    if aead.decrypt(&nonce, &aad, &sealed)? != plaintext {
+        return Err(CryptoCoreError::Crypto("decryption does not restore the plaintext".to_string()));
+    }
+    Ok(())
+}
+
+// RFC 7748, section 6.1
+fn x25519_rfc7748() -> Result<()> {
+    let alice: [u8; 32] = hex::decode("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a")?.try_into().unwrap();
+    let bob_public: [u8; 32] = hex::decode("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f")?.try_into().unwrap();
+    expect_eq(&x25519::public_key(&alice), "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a")?;
+    expect_eq(
+        &x25519::shared_secret(&alice, &bob_public)?,
+        "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742",
+    )
+}
+
+fn ed25519_rfc8032() -> Result<()> {
+    let seed: [u8; 32] = hex::decode("4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb")?.try_into().unwrap();
+    let public = ed25519::public_key(&seed);
+    expect_eq(&public, "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c")?;
+    let signature = ed25519::sign(&seed, &[0x72]);
+    expect_eq(
+        &signature,
+        "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da\
+         085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+    )?;
+    ed25519::verify(&public, &[0x72], &signature)
+}
+
+fn ecdsa_p256_rfc6979() -> Result<()> {
+    let secret: [u8; 32] = hex::decode("c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721")?.try_into().unwrap();
+    let signature = p256::sign(&secret, b"sample")?;
+    expect_eq(
+        &signature,
+        "3046022100efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716\
+         022100f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8",
+    )?;
+    p256::verify(&p256::public_key(&secret), b"sample", &signature)
+}
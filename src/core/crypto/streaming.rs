@@ -0,0 +1,176 @@
+//! Incremental encryption and decryption on top of `CipherInstance`.
+//!
+//! Input is processed a whole number of blocks at a time and the IV for the
+//! next piece is chained from the previous one, so the output is identical to
+//! a one-shot `encrypt`/`decrypt` of the whole input. The cipher is set up
+//! once per stream.
+//!
+//! GCM (with its 12-byte nonce) encrypts piece by piece and `finish` adds the
+//! tag. Decrypting GCM or OCB, and encrypting OCB, buffers the message until
+//! `finish`: no plaintext is released before the tag has been checked, so
+//! memory grows with the message. `stream` is the constant-memory alternative.
+//! Both modes can also authenticate associated data given up front.
+
+use crate::core::crypto::modes::gcm::{GcmEncryptor, GcmMode};
+use crate::core::crypto::{check_no_aad, create_cipher, CipherInstance};
+use crate::error::Result;
+use crate::Operation;
+
+const BLOCK_SIZE: usize = 16;
+
+pub struct StreamEncryptor {
+    state: StreamState,
+    // GCM output is produced as the input arrives
+    gcm: Option<GcmEncryptor>,
+}
+
+impl StreamEncryptor {
+    pub fn new(algorithm: &str, mode: &str, key: &[u8], iv: Option<&[u8]>) -> Result<Self> {
+        Self::with_aad(algorithm, mode, key, iv, &[])
+    }
+
+    /// An encryptor whose tag also covers `aad`, for GCM and OCB.
+    pub fn with_aad(algorithm: &str, mode: &str, key: &[u8], iv: Option<&[u8]>, aad: &[u8]) -> Result<Self> {
+        let state = StreamState::new(algorithm, mode, key, iv, aad)?;
+        let gcm = match (state.mode.as_str(), iv) {
+            ("gcm", Some(nonce)) => Some(GcmEncryptor::new(GcmMode::new(key, nonce)?, aad)),
+            _ => None,
+        };
+        Ok(Self { state, gcm })
+    }
+
+    pub fn update(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        match &mut self.gcm {
+            Some(gcm) => {
+                self.state.buffer.extend_from_slice(data);
+                gcm.update(&self.state.take_blocks(false))
+            }
+            None => self.state.update(Operation::Encrypt, data),
+        }
+    }
+
+    /// Encrypts what is left, adding padding or the tag where the mode needs it.
+    pub fn finish(self) -> Result<Vec<u8>> {
+        match self.gcm {
+            Some(gcm) => gcm.finish(&self.state.buffer),
+            None => self.state.cipher.encrypt_with_aad(&self.state.aad, &self.state.buffer),
+        }
+    }
+}
+
+pub struct StreamDecryptor {
+    state: StreamState,
+}
+
+impl StreamDecryptor {
+    pub fn new(algorithm: &str, mode: &str, key: &[u8], iv: Option<&[u8]>) -> Result<Self> {
+        Self::with_aad(algorithm, mode, key, iv, &[])
+    }
+
+    /// A decryptor whose tag check also covers `aad`, for GCM and OCB.
+    pub fn with_aad(algorithm: &str, mode: &str, key: &[u8], iv: Option<&[u8]>, aad: &[u8]) -> Result<Self> {
+        Ok(Self { state: StreamState::new(algorithm, mode, key, iv, aad)? })
+    }
+
+    pub fn update(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        self.state.update(Operation::Decrypt, data)
+    }
+
+    /// Decrypts what is left, removing padding or checking the tag where the mode needs it.
+    pub fn finish(self) -> Result<Vec<u8>> {
+        self.state.cipher.decrypt_with_aad(&self.state.aad, &self.state.buffer)
+    }
+}
+
+struct StreamState {
+    cipher: CipherInstance,
+    mode: String,
+    // IV of the next piece, for the chained modes
+    iv: Option<[u8; BLOCK_SIZE]>,
+    padded: bool,
+    authenticated: bool,
+    aad: Vec<u8>,
+    buffer: Vec<u8>,
+}
+
+impl StreamState {
+    fn new(algorithm: &str, mode: &str, key: &[u8], iv: Option<&[u8]>, aad: &[u8]) -> Result<Self> {
+        // Building the cipher up front rejects bad parameters before any data is read
+        let cipher = create_cipher(algorithm, mode, key, iv)?;
+        if !cipher.is_authenticated() {
+            check_no_aad(aad)?;
+        }
+
+        Ok(Self {
+            mode: mode.to_lowercase(),
+            iv: iv.and_then(|iv| iv.try_into().ok()),
+            padded: cipher.requires_padding(),
+            authenticated: cipher.is_authenticated(),
+            aad: aad.to_vec(),
+            cipher,
+            buffer: Vec::new(),
+        })
+    }
+
+    fn update(&mut self, operation: Operation, data: &[u8]) -> Result<Vec<u8>> {
+        self.buffer.extend_from_slice(data);
+        if self.authenticated {
+            return Ok(Vec::new());
+        }
+
+        // Padding is stripped from the final block, so it waits for `finish`
+        let input = self.take_blocks(operation == Operation::Decrypt && self.padded);
+        if input.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let output = match operation {
+            Operation::Encrypt => self.cipher.encrypt_blocks(&input)?,
+            Operation::Decrypt => self.cipher.decrypt_blocks(&input)?,
+        };
+
+        self.chain_iv(operation, &input, &output);
+        Ok(output)
+    }
+
+    // Takes the whole blocks off the front of the buffer, keeping back a
+    // full last block if `hold_last` is set
+    fn take_blocks(&mut self, hold_last: bool) -> Vec<u8> {
+        let mut ready = self.buffer.len() - self.buffer.len() % BLOCK_SIZE;
+        if hold_last && ready == self.buffer.len() {
+            ready = ready.saturating_sub(BLOCK_SIZE);
+        }
+        self.buffer.drain(..ready).collect()
+    }
+
+    // Sets the IV so the next piece continues exactly where this one ended
+    fn chain_iv(&mut self, operation: Operation, input: &[u8], output: &[u8]) {
+        let Some(iv) = self.iv.as_mut() else {
+            return;
+        };
+
+        let last_block = |data: &[u8]| -> [u8; BLOCK_SIZE] { data[data.len() - BLOCK_SIZE..].try_into().unwrap() };
+        let ciphertext = match operation {
+            Operation::Encrypt => output,
+            Operation::Decrypt => input,
+        };
+
+        match self.mode.as_str() {
+            "cbc" | "cfb" => *iv = last_block(ciphertext),
+            // The OFB keystream is recovered as plaintext XOR ciphertext
+            "ofb" => {
+                let (input, output) = (last_block(input), last_block(output));
+                *iv = std::array::from_fn(|i| input[i] ^ output[i]);
+            }
+            "ctr" => {
+                let blocks = (input.len() / BLOCK_SIZE) as u128;
+                *iv = u128::from_be_bytes(*iv).wrapping_add(blocks).to_be_bytes();
+            }
+            _ => return,
+        }
+
+        if let CipherInstance::BlockMode(mode) = &mut self.cipher {
+            mode.set_iv(*iv);
+        }
+    }
+}
@@ -0,0 +1,452 @@
+//! Encrypted single-file archives for `pack`/`unpack`.
+//!
+//! File layout: `header | segment...`, where the header carries the IV and
+//! each segment is up to `SEGMENT_SIZE` bytes of ciphertext followed by its
+//! HMAC-SHA256 tag. A segment's tag covers the previous tag (the first one
+//! covers the header's), a flag set only on the last segment and the
+//! segment's ciphertext, so segments cannot be altered, reordered or cut off.
+//! A reader checks each segment before decrypting it, in one pass. The
+//! encryption and MAC keys are derived from the user's key with HKDF, so one
+//! key serves both. Everything about the entries, including their names, is
+//! inside the ciphertext.
+//!
+//! Plaintext layout: `ARCHIVE_MAGIC`, then one record per entry and a final
+//! `END` byte. A record is
+//! `kind (u8) | path length (u16) | path | mode (u32) | mtime seconds (i64) | mtime nanoseconds (u32)`,
+//! followed by `size (u64) | contents` for files and
+//! `target length (u16) | target` for symbolic links. Integers are big-endian.
+
+use crate::core::crypto::kdf::{self, KdfAlgorithm};
+use crate::core::crypto::mac::{self, Mac, MacAlgorithm};
+use crate::core::crypto::streaming::{StreamDecryptor, StreamEncryptor};
+use crate::core::io::{self, header::FileHeader};
+use crate::error::{CryptoCoreError, Result};
+use std::io::{Read, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const ARCHIVE_MAGIC: &[u8; 4] = b"CCA\x02";
+/// Modes an archive can be written with; all of them can be processed as a stream.
+pub const ARCHIVE_MODES: [&str; 4] = ["cbc", "cfb", "ofb", "ctr"];
+
+/// Bytes of ciphertext per authenticated segment
+pub const SEGMENT_SIZE: usize = 64 * 1024;
+
+const TAG_SIZE: usize = 32;
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+const KIND_END: u8 = 0;
+const KIND_DIRECTORY: u8 = 1;
+const KIND_FILE: u8 = 2;
+const KIND_SYMLINK: u8 = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntryKind {
+    Directory,
+    File { size: u64 },
+    Symlink { target: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchiveEntry {
+    /// Path relative to the archive root, with `/` separators
+    pub path: String,
+    /// Unix permission bits
+    pub mode: u32,
+    pub modified: SystemTime,
+    pub kind: EntryKind,
+}
+
+pub struct ArchiveWriter<W: Write> {
+    inner: W,
+    encryptor: StreamEncryptor,
+    segments: SegmentChain,
+    /// Ciphertext not yet sealed into a segment
+    pending: Vec<u8>,
+}
+
+impl<W: Write> ArchiveWriter<W> {
+    pub fn new(mut inner: W, key: &[u8], mode: &str) -> Result<Self> {
+        check_mode(mode)?;
+        let (encryption_key, mac_key) = derive_keys(key)?;
+
+        let header = FileHeader {
+            iv: Some(io::generate_nonce(16)?),
+            ..FileHeader::default()
+        };
+        let header_bytes = header.to_bytes()?;
+        inner.write_all(&header_bytes)?;
+
+        let mut writer = Self {
+            inner,
+            encryptor: StreamEncryptor::new("aes", mode, &encryption_key, header.iv.as_deref())?,
+            segments: SegmentChain::new(mac_key, &header_bytes)?,
+            pending: Vec::new(),
+        };
+        writer.write_plaintext(ARCHIVE_MAGIC)?;
+        Ok(writer)
+    }
+
+    /// Adds a directory or symbolic link entry.
+    pub fn add_entry(&mut self, entry: &ArchiveEntry) -> Result<()> {
+        if let EntryKind::File { .. } = entry.kind {
+            return Err(CryptoCoreError::InvalidArgument(
+                "File entries must be added with add_file".to_string(),
+            ));
+        }
+        self.write_record(entry)
+    }
+
+    /// Adds a file entry followed by exactly `size` bytes read from `contents`.
+    pub fn add_file<R: Read>(&mut self, entry: &ArchiveEntry, contents: &mut R) -> Result<()> {
+        let EntryKind::File { size } = entry.kind else {
+            return Err(CryptoCoreError::InvalidArgument(
+                "Only file entries have contents".to_string(),
+            ));
+        };
+        self.write_record(entry)?;
+
+        let mut remaining = size;
+        let mut buffer = vec![0u8; READ_BUFFER_SIZE];
+        while remaining > 0 {
+            let wanted = remaining.min(buffer.len() as u64) as usize;
+            let read = contents.read(&mut buffer[..wanted])?;
+            if read == 0 {
+                return Err(CryptoCoreError::FileError(format!(
+                    "{} shrank while it was being archived",
+                    entry.path
+                )));
+            }
+            self.write_plaintext(&buffer[..read])?;
+            remaining -= read as u64;
+        }
+        Ok(())
+    }
+
+    /// Writes the end marker and the last segment, returning the underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        self.write_plaintext(&[KIND_END])?;
+
+        let Self { mut inner, encryptor, mut segments, mut pending } = self;
+        pending.extend_from_slice(&encryptor.finish()?);
+        segments.write_full(&mut inner, &mut pending)?;
+        segments.write(&mut inner, &pending, true)?;
+        inner.flush()?;
+        Ok(inner)
+    }
+
+    fn write_record(&mut self, entry: &ArchiveEntry) -> Result<()> {
+        let (seconds, nanoseconds) = to_timestamp(entry.modified);
+        let mut record = Vec::new();
+        record.push(match entry.kind {
+            EntryKind::Directory => KIND_DIRECTORY,
+            EntryKind::File { .. } => KIND_FILE,
+            EntryKind::Symlink { .. } => KIND_SYMLINK,
+        });
+        push_string(&mut record, &entry.path)?;
+        record.extend_from_slice(&entry.mode.to_be_bytes());
+        record.extend_from_slice(&seconds.to_be_bytes());
+        record.extend_from_slice(&nanoseconds.to_be_bytes());
+        match &entry.kind {
+            EntryKind::Directory => {}
+            EntryKind::File { size } => record.extend_from_slice(&size.to_be_bytes()),
+            EntryKind::Symlink { target } => push_string(&mut record, target)?,
+        }
+        self.write_plaintext(&record)
+    }
+
+    fn write_plaintext(&mut self, data: &[u8]) -> Result<()> {
+        let ciphertext = self.encryptor.update(data)?;
+        self.pending.extend_from_slice(&ciphertext);
+        self.segments.write_full(&mut self.inner, &mut self.pending)
+    }
+}
+
+pub struct ArchiveReader<R: Read> {
+    plaintext: DecryptingReader<R>,
+    /// Unread contents of the current file entry
+    remaining: u64,
+}
+
+impl<R: Read> ArchiveReader<R> {
+    /// Opens an archive read front to back. Each segment's tag is checked
+    /// before the segment is decrypted, so nothing unauthenticated is ever
+    /// returned; damage further in surfaces as an error from the entry that
+    /// reaches it.
+    pub fn open(mut inner: R, key: &[u8], mode: &str) -> Result<Self> {
+        check_mode(mode)?;
+        let (encryption_key, mac_key) = derive_keys(key)?;
+
+        // The first tag covers the header exactly as it was read
+        let mut recorded = Recording { inner: &mut inner, bytes: Vec::new() };
+        let (header, _) = FileHeader::read_from(&mut recorded)?
+            .ok_or_else(|| CryptoCoreError::FileError("Not a cryptocore archive".to_string()))?;
+        let header_bytes = recorded.bytes;
+
+        let mut reader = Self {
+            plaintext: DecryptingReader {
+                inner,
+                decryptor: Some(StreamDecryptor::new("aes", mode, &encryption_key, header.iv.as_deref())?),
+                segments: SegmentChain::new(mac_key, &header_bytes)?,
+                lookahead: None,
+                buffer: Vec::new(),
+                position: 0,
+            },
+            remaining: 0,
+        };
+
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != ARCHIVE_MAGIC {
+            return Err(CryptoCoreError::FileError("Not a cryptocore archive".to_string()));
+        }
+        Ok(reader)
+    }
+
+    /// Returns the next entry, skipping whatever is left of the previous file's contents.
+    pub fn next_entry(&mut self) -> Result<Option<ArchiveEntry>> {
+        if self.remaining > 0 {
+            let mut skipped = std::io::sink();
+            self.read_contents(&mut skipped)?;
+        }
+
+        let kind = self.read_u8()?;
+        if kind == KIND_END {
+            return Ok(None);
+        }
+
+        let path = self.read_string()?;
+        let mode = u32::from_be_bytes(self.read_array()?);
+        let seconds = i64::from_be_bytes(self.read_array()?);
+        let nanoseconds = u32::from_be_bytes(self.read_array()?);
+        let kind = match kind {
+            KIND_DIRECTORY => EntryKind::Directory,
+            KIND_FILE => {
+                let size = u64::from_be_bytes(self.read_array()?);
+                self.remaining = size;
+                EntryKind::File { size }
+            }
+            KIND_SYMLINK => EntryKind::Symlink { target: self.read_string()? },
+            _ => return Err(malformed("unknown entry kind")),
+        };
+
+        Ok(Some(ArchiveEntry {
+            path,
+            mode,
+            modified: from_timestamp(seconds, nanoseconds),
+            kind,
+        }))
+    }
+
+    /// Copies the contents of the current file entry to `out`.
+    pub fn read_contents<W: Write>(&mut self, out: &mut W) -> Result<()> {
+        let mut buffer = vec![0u8; READ_BUFFER_SIZE];
+        while self.remaining > 0 {
+            let wanted = self.remaining.min(buffer.len() as u64) as usize;
+            let read = self.plaintext.read(&mut buffer[..wanted])?;
+            if read == 0 {
+                return Err(malformed("truncated file contents"));
+            }
+            out.write_all(&buffer[..read])?;
+            self.remaining -= read as u64;
+        }
+        Ok(())
+    }
+
+    fn read_exact(&mut self, buffer: &mut [u8]) -> Result<()> {
+        let mut filled = 0;
+        while filled < buffer.len() {
+            let read = self.plaintext.read(&mut buffer[filled..])?;
+            if read == 0 {
+                return Err(malformed("unexpected end of archive"));
+            }
+            filled += read;
+        }
+        Ok(())
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut bytes = [0u8; N];
+        self.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    fn read_string(&mut self) -> Result<String> {
+        let len = u16::from_be_bytes(self.read_array()?) as usize;
+        let mut bytes = vec![0u8; len];
+        self.read_exact(&mut bytes)?;
+        String::from_utf8(bytes).map_err(|_| malformed("entry name is not valid UTF-8"))
+    }
+}
+
+// Decrypts the segments one at a time, each after its tag has been checked
+struct DecryptingReader<R: Read> {
+    inner: R,
+    decryptor: Option<StreamDecryptor>,
+    segments: SegmentChain,
+    // A byte read past a full segment to find out whether another follows
+    lookahead: Option<u8>,
+    buffer: Vec<u8>,
+    position: usize,
+}
+
+impl<R: Read> DecryptingReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> Result<usize> {
+        while self.position == self.buffer.len() {
+            if self.decryptor.is_none() {
+                return Ok(0);
+            }
+            self.buffer = self.next_segment()?;
+            self.position = 0;
+        }
+
+        let count = out.len().min(self.buffer.len() - self.position);
+        out[..count].copy_from_slice(&self.buffer[self.position..self.position + count]);
+        self.position += count;
+        Ok(count)
+    }
+
+    fn next_segment(&mut self) -> Result<Vec<u8>> {
+        let mut segment = Vec::with_capacity(SEGMENT_SIZE + TAG_SIZE);
+        segment.extend(self.lookahead.take());
+        let wanted = (SEGMENT_SIZE + TAG_SIZE - segment.len()) as u64;
+        (&mut self.inner).take(wanted).read_to_end(&mut segment)?;
+
+        // Only a full segment can have another one behind it
+        let mut next = [0u8; 1];
+        let last = segment.len() < SEGMENT_SIZE + TAG_SIZE || self.inner.read(&mut next)? == 0;
+        if !last {
+            self.lookahead = Some(next[0]);
+        }
+
+        if segment.len() < TAG_SIZE {
+            return Err(CryptoCoreError::FileError("Archive is truncated".to_string()));
+        }
+        let (ciphertext, tag) = segment.split_at(segment.len() - TAG_SIZE);
+        self.segments.verify(ciphertext, last, tag)?;
+
+        let decryptor = self.decryptor.as_mut().unwrap();
+        let mut plaintext = decryptor.update(ciphertext)?;
+        if last {
+            plaintext.extend(self.decryptor.take().unwrap().finish()?);
+        }
+        Ok(plaintext)
+    }
+}
+
+// Keeps a copy of everything read through it
+struct Recording<'a, R: Read> {
+    inner: &'a mut R,
+    bytes: Vec<u8>,
+}
+
+impl<R: Read> Read for Recording<'_, R> {
+    fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(out)?;
+        self.bytes.extend_from_slice(&out[..read]);
+        Ok(read)
+    }
+}
+
+// The running tag that links each segment to the one before it
+struct SegmentChain {
+    mac_key: Vec<u8>,
+    previous: Vec<u8>,
+}
+
+impl SegmentChain {
+    fn new(mac_key: Vec<u8>, header: &[u8]) -> Result<Self> {
+        let mut mac = Mac::new(MacAlgorithm::HmacSha256, &mac_key)?;
+        mac.update(header);
+        Ok(Self { previous: mac.finalize(), mac_key })
+    }
+
+    fn tag(&mut self, ciphertext: &[u8], last: bool) -> Result<Vec<u8>> {
+        let mut mac = Mac::new(MacAlgorithm::HmacSha256, &self.mac_key)?;
+        mac.update(&self.previous);
+        mac.update(&[u8::from(last)]);
+        mac.update(ciphertext);
+        self.previous = mac.finalize();
+        Ok(self.previous.clone())
+    }
+
+    fn write<W: Write>(&mut self, out: &mut W, ciphertext: &[u8], last: bool) -> Result<()> {
+        let tag = self.tag(ciphertext, last)?;
+        out.write_all(ciphertext)?;
+        out.write_all(&tag)?;
+        Ok(())
+    }
+
+    // Writes out the full segments of `pending`. A full segment is kept back
+    // until more ciphertext follows it, as only `finish` knows which is last
+    fn write_full<W: Write>(&mut self, out: &mut W, pending: &mut Vec<u8>) -> Result<()> {
+        while pending.len() > SEGMENT_SIZE {
+            let segment: Vec<u8> = pending.drain(..SEGMENT_SIZE).collect();
+            self.write(out, &segment, false)?;
+        }
+        Ok(())
+    }
+
+    fn verify(&mut self, ciphertext: &[u8], last: bool, tag: &[u8]) -> Result<()> {
+        let expected = self.tag(ciphertext, last)?;
+        if !mac::constant_time_eq(&expected, tag) {
+            return Err(CryptoCoreError::AuthenticationFailed("Archive tag mismatch".to_string()));
+        }
+        Ok(())
+    }
+}
+
+fn check_mode(mode: &str) -> Result<()> {
+    if !ARCHIVE_MODES.contains(&mode.to_lowercase().as_str()) {
+        return Err(CryptoCoreError::InvalidArgument(format!(
+            "Archives support the {} modes, not {}",
+            ARCHIVE_MODES.join("/"),
+            mode
+        )));
+    }
+    Ok(())
+}
+
+fn derive_keys(key: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+    Ok((
+        kdf::hkdf(KdfAlgorithm::HkdfSha256, key, None, b"cryptocore archive encryption", key.len())?,
+        kdf::hkdf(KdfAlgorithm::HkdfSha256, key, None, b"cryptocore archive authentication", 32)?,
+    ))
+}
+
+fn push_string(out: &mut Vec<u8>, value: &str) -> Result<()> {
+    let len = u16::try_from(value.len())
+        .map_err(|_| CryptoCoreError::InvalidArgument(format!("Name is too long: {}", value)))?;
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(value.as_bytes());
+    Ok(())
+}
+
+fn to_timestamp(time: SystemTime) -> (i64, u32) {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(after) => (after.as_secs() as i64, after.subsec_nanos()),
+        // Before the epoch: round down to whole seconds and count the rest forward
+        Err(before) => {
+            let before = before.duration();
+            let seconds = -(before.as_secs() as i64) - i64::from(before.subsec_nanos() > 0);
+            let nanoseconds = (1_000_000_000 - before.subsec_nanos()) % 1_000_000_000;
+            (seconds, nanoseconds)
+        }
+    }
+}
+
+fn from_timestamp(seconds: i64, nanoseconds: u32) -> SystemTime {
+    let nanoseconds = Duration::from_nanos(u64::from(nanoseconds.min(999_999_999)));
+    if seconds >= 0 {
+        UNIX_EPOCH + Duration::from_secs(seconds as u64) + nanoseconds
+    } else {
+        UNIX_EPOCH - Duration::from_secs(seconds.unsigned_abs()) + nanoseconds
+    }
+}
+
+fn malformed(reason: &str) -> CryptoCoreError {
+    CryptoCoreError::FileError(format!("Malformed archive: {}", reason))
+}
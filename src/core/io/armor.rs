@@ -0,0 +1,142 @@
+//! ASCII armor for encrypted output, in the style of OpenPGP (RFC 4880, section 6):
+//!
+//! ```text
+//! -----BEGIN CRYPTOCORE MESSAGE-----
+//! <base64, 64 characters per line>
+//! =<base64 of the CRC-24 of the data>
+//! -----END CRYPTOCORE MESSAGE-----
+//! ```
+//!
+//! The `_as` variants take another label, e.g. [`PGP_LABEL`] for OpenPGP
+//! messages, which also get the blank line that ends the (empty) armor header
+//! section OpenPGP readers require. Reading accepts either form.
+
+use crate::error::{CryptoCoreError, Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+pub const LABEL: &str = "CRYPTOCORE MESSAGE";
+pub const PGP_LABEL: &str = "PGP MESSAGE";
+
+const LINE_LENGTH: usize = 64;
+const CRC24_INIT: u32 = 0xb704ce;
+const CRC24_POLY: u32 = 0x1864cfb;
+
+pub fn armor(data: &[u8]) -> String {
+    armor_as(LABEL, data)
+}
+
+pub fn armor_as(label: &str, data: &[u8]) -> String {
+    let encoded = STANDARD.encode(data);
+    let mut out = String::with_capacity(encoded.len() + encoded.len() / LINE_LENGTH + 100);
+
+    out.push_str(&begin_line(label));
+    out.push('\n');
+    if label == PGP_LABEL {
+        out.push('\n');
+    }
+    for line in encoded.as_bytes().chunks(LINE_LENGTH) {
+        // Base64 output is ASCII, so every chunk is valid UTF-8
+        out.push_str(std::str::from_utf8(line).unwrap());
+        out.push('\n');
+    }
+    out.push('=');
+    out.push_str(&STANDARD.encode(&crc24(data).to_be_bytes()[1..]));
+    out.push('\n');
+    out.push_str(&end_line(label));
+    out.push('\n');
+    out
+}
+
+/// Whether `data` is an armored message, ignoring leading whitespace.
+pub fn is_armored(data: &[u8]) -> bool {
+    is_armored_as(LABEL, data)
+}
+
+pub fn is_armored_as(label: &str, data: &[u8]) -> bool {
+    data.trim_ascii_start().starts_with(begin_line(label).as_bytes())
+}
+
+pub fn dearmor(data: &[u8]) -> Result<Vec<u8>> {
+    dearmor_as(LABEL, data)
+}
+
+pub fn dearmor_as(label: &str, data: &[u8]) -> Result<Vec<u8>> {
+    let (begin_line, end_line) = (begin_line(label), end_line(label));
+    let text = std::str::from_utf8(data).map_err(|_| malformed("not valid text"))?;
+    let mut lines = text.lines().map(str::trim).skip_while(|line| line.is_empty());
+
+    if lines.next() != Some(begin_line.as_str()) {
+        return Err(malformed("missing BEGIN line"));
+    }
+
+    let mut encoded = String::new();
+    let mut checksum = None;
+    let mut ended = false;
+    for line in lines.by_ref() {
+        if line == end_line {
+            ended = true;
+            break;
+        }
+        // Armor headers ("Key: value") and the blank line after them carry no data
+        if line.is_empty() || line.contains(':') {
+            continue;
+        }
+        match line.strip_prefix('=') {
+            // A base64 line can end in '=' padding but never starts with it
+            Some(crc) => checksum = Some(crc.to_string()),
+            None => encoded.push_str(line),
+        }
+    }
+    if !ended {
+        return Err(malformed("missing END line"));
+    }
+
+    let decoded = STANDARD.decode(&encoded).map_err(|_| malformed("invalid base64"))?;
+    if let Some(checksum) = checksum {
+        let expected = STANDARD.decode(&checksum).map_err(|_| malformed("invalid checksum line"))?;
+        if expected != crc24(&decoded).to_be_bytes()[1..] {
+            return Err(CryptoCoreError::IntegrityCheckFailed(
+                "armor checksum does not match; the message was damaged in transit".to_string(),
+            ));
+        }
+    }
+
+    Ok(decoded)
+}
+
+/// Strips armor from `data` if it has any, otherwise returns it unchanged.
+pub fn dearmor_if_armored(data: Vec<u8>) -> Result<Vec<u8>> {
+    if is_armored(&data) {
+        dearmor(&data)
+    } else {
+        Ok(data)
+    }
+}
+
+fn begin_line(label: &str) -> String {
+    format!("-----BEGIN {}-----", label)
+}
+
+fn end_line(label: &str) -> String {
+    format!("-----END {}-----", label)
+}
+
+// CRC-24 as defined in RFC 4880, section 6.1
+fn crc24(data: &[u8]) -> u32 {
+    let mut crc = CRC24_INIT;
+    for &byte in data {
+        crc ^= u32::from(byte) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x1000000 != 0 {
+                crc ^= CRC24_POLY;
+            }
+        }
+    }
+    crc & 0xffffff
+}
+
+fn malformed(reason: &str) -> CryptoCoreError {
+    CryptoCoreError::FileError(format!("Malformed armor: {}", reason))
+}
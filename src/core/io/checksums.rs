@@ -0,0 +1,53 @@
+//! Checksum manifests in the `sha256sum` text format: `<hex digest>  <path>`.
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use crate::core::crypto::hash::{self, HashAlgorithm};
+use crate::error::{CryptoCoreError, Result};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChecksumEntry {
+    pub digest: Vec<u8>,
+    pub path: PathBuf,
+}
+
+pub fn digest_file(algorithm: HashAlgorithm, path: &Path) -> Result<Vec<u8>> {
+    let mut file = File::open(path).map_err(|e| {
+        CryptoCoreError::FileError(format!("Failed to read file {}: {}", path.display(), e))
+    })?;
+    hash::hash_reader(algorithm, &mut file)
+}
+
+pub fn format_entry(digest: &[u8], path: &Path) -> String {
+    format!("{}  {}", hex::encode(digest), path.display())
+}
+
+pub fn parse_checksums(content: &str) -> Result<Vec<ChecksumEntry>> {
+    let mut entries = Vec::new();
+
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let invalid = || {
+            CryptoCoreError::FileError(format!("Malformed checksum line {}: {}", number + 1, line))
+        };
+
+        let (digest_hex, rest) = line.split_once(' ').ok_or_else(invalid)?;
+        // Second column is ' ' for text mode or '*' for binary mode, as in sha256sum
+        let path = rest
+            .strip_prefix(' ')
+            .or_else(|| rest.strip_prefix('*'))
+            .filter(|path| !path.is_empty())
+            .ok_or_else(invalid)?;
+
+        entries.push(ChecksumEntry {
+            digest: hex::decode(digest_hex).map_err(|_| invalid())?,
+            path: PathBuf::from(path),
+        });
+    }
+
+    Ok(entries)
+}
@@ -0,0 +1,99 @@
+//! Optional compression applied to the plaintext before encryption.
+//!
+//! Compression makes the ciphertext length depend on the content. When an
+//! attacker can mix their own input with a secret and observe the output size
+//! (CRIME/BREACH-style attacks), that length leaks the secret, so compression is
+//! off unless explicitly requested.
+//!
+//! Decompression stops at a size limit, so a small file that expands to an
+//! enormous output (a decompression bomb) fails instead of exhausting memory.
+
+use crate::error::{CryptoCoreError, Result};
+use flate2::read::{GzDecoder, GzEncoder};
+use std::io::Read;
+
+/// Default limit on the decompressed size (1 GiB).
+pub const DEFAULT_MAX_DECOMPRESSED_SIZE: u64 = 1 << 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionAlgorithm {
+    Zstd,
+    Gzip,
+}
+
+impl CompressionAlgorithm {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "zstd" => Ok(Self::Zstd),
+            "gzip" => Ok(Self::Gzip),
+            _ => Err(CryptoCoreError::InvalidArgument(
+                format!("Unsupported compression algorithm: {}", name)
+            )),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Zstd => "zstd",
+            Self::Gzip => "gzip",
+        }
+    }
+
+    /// Identifier used when the algorithm is recorded in a file header.
+    pub fn id(&self) -> u8 {
+        match self {
+            Self::Zstd => 1,
+            Self::Gzip => 2,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Result<Self> {
+        match id {
+            1 => Ok(Self::Zstd),
+            2 => Ok(Self::Gzip),
+            _ => Err(CryptoCoreError::FileError(format!("Unknown compression identifier: {}", id))),
+        }
+    }
+}
+
+impl std::fmt::Display for CompressionAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+pub fn compress(algorithm: CompressionAlgorithm, data: &[u8]) -> Result<Vec<u8>> {
+    let compressed = match algorithm {
+        // Level 0 selects zstd's default level
+        CompressionAlgorithm::Zstd => zstd::encode_all(data, 0),
+        CompressionAlgorithm::Gzip => {
+            let mut compressed = Vec::new();
+            GzEncoder::new(data, flate2::Compression::default())
+                .read_to_end(&mut compressed)
+                .map(|_| compressed)
+        }
+    };
+    compressed.map_err(|e| CryptoCoreError::Crypto(format!("{} compression failed: {}", algorithm, e)))
+}
+
+/// Decompresses `data`, failing once the output would exceed `max_size` bytes.
+pub fn decompress(algorithm: CompressionAlgorithm, data: &[u8], max_size: u64) -> Result<Vec<u8>> {
+    let error = |e: std::io::Error| {
+        CryptoCoreError::FileError(format!("{} decompression failed: {}", algorithm, e))
+    };
+    let decoder: Box<dyn Read + '_> = match algorithm {
+        CompressionAlgorithm::Zstd => Box::new(zstd::Decoder::new(data).map_err(error)?),
+        CompressionAlgorithm::Gzip => Box::new(GzDecoder::new(data)),
+    };
+
+    // One byte past the limit tells a too-large output from one that just fits
+    let mut decompressed = Vec::new();
+    decoder.take(max_size.saturating_add(1)).read_to_end(&mut decompressed).map_err(error)?;
+    if decompressed.len() as u64 > max_size {
+        return Err(CryptoCoreError::FileError(format!(
+            "Decompressed data exceeds the limit of {} bytes",
+            max_size
+        )));
+    }
+    Ok(decompressed)
+}
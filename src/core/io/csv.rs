@@ -0,0 +1,87 @@
+//! Minimal RFC 4180 CSV: quoted fields may hold delimiters, doubled quotes
+//! and line breaks. Records are read whole, so cells can be rewritten and
+//! the file written back with the same shape.
+
+use crate::error::{CryptoCoreError, Result};
+
+pub type Record = Vec<String>;
+
+pub fn parse(content: &str, delimiter: char) -> Result<Vec<Record>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut chars = content.chars().peekable();
+    // Whether the current record has anything in it, so a final line
+    // break does not produce an empty record
+    let mut started = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if field.is_empty() => {
+                started = true;
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => field.push(c),
+                        None => {
+                            return Err(CryptoCoreError::FileError(format!(
+                                "Malformed CSV: unterminated quoted field in row {}",
+                                records.len() + 1
+                            )));
+                        }
+                    }
+                }
+                if !matches!(chars.peek(), None | Some('\r' | '\n')) && chars.peek() != Some(&delimiter) {
+                    return Err(CryptoCoreError::FileError(format!(
+                        "Malformed CSV: text after a closing quote in row {}",
+                        records.len() + 1
+                    )));
+                }
+            }
+            c if c == delimiter => {
+                started = true;
+                record.push(std::mem::take(&mut field));
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+                started = false;
+            }
+            c => {
+                started = true;
+                field.push(c);
+            }
+        }
+    }
+
+    if started {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Joins fields, quoting those that need it.
+pub fn format_record(record: &[String], delimiter: char) -> String {
+    record
+        .iter()
+        .map(|field| {
+            if field.contains([delimiter, '"', '\r', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(&delimiter.to_string())
+}
+
+/// The line ending of the input, so rewritten files keep it.
+pub fn line_ending(content: &str) -> &'static str {
+    if content.contains("\r\n") { "\r\n" } else { "\n" }
+}
@@ -0,0 +1,308 @@
+//! Self-describing header for encrypted files.
+//!
+//! Layout: `MAGIC | VERSION | length (u32, big-endian) | fields`, where every
+//! field is `tag (u8) | length (u16, big-endian) | value`. Files without the
+//! magic are treated as the original `IV || ciphertext` format.
+
+use crate::core::crypto::asymmetric::{KeyAlgorithm, PrivateKey, PublicKey};
+use crate::core::crypto::kdf::{self, KdfAlgorithm};
+use crate::core::io::compression::CompressionAlgorithm;
+use crate::core::crypto::keywrap;
+use crate::error::{CryptoCoreError, Result};
+use std::io::Read;
+
+pub const MAGIC: &[u8; 10] = b"CRYPTOCORE";
+pub const VERSION: u8 = 1;
+
+const PREAMBLE_LEN: usize = MAGIC.len() + 1 + 4;
+/// Longer field sections are refused before anything is allocated for them
+pub const MAX_FIELDS_LEN: usize = 64 * 1024;
+// AES-KW output: a 64-bit integrity block on top of at least 128 bits of key
+const MIN_WRAPPED_KEY_LEN: usize = 24;
+
+const TAG_IV: u8 = 0x01;
+const TAG_KEY_DERIVATION: u8 = 0x02;
+const TAG_WRAPPED_KEY: u8 = 0x03;
+const TAG_COMPRESSION: u8 = 0x04;
+const TAG_RECIPIENT: u8 = 0x05;
+
+/// Subkey derivation applied to the key given on the command line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyDerivation {
+    pub algorithm: KdfAlgorithm,
+    pub info: Vec<u8>,
+}
+
+/// The data key, encrypted to one recipient's public key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecipientKey {
+    pub algorithm: KeyAlgorithm,
+    pub wrapped_key: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileHeader {
+    pub iv: Option<Vec<u8>>,
+    pub key_derivation: Option<KeyDerivation>,
+    /// Per-file data key, wrapped with AES-KW under the (derived) master key
+    pub wrapped_key: Option<Vec<u8>>,
+    /// Compression applied to the plaintext before encryption
+    pub compression: Option<CompressionAlgorithm>,
+    /// One copy of the data key per public-key recipient (a repeated field)
+    pub recipients: Vec<RecipientKey>,
+}
+
+impl FileHeader {
+    pub fn is_present(data: &[u8]) -> bool {
+        data.starts_with(MAGIC)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut fields = Vec::new();
+
+        if let Some(iv) = &self.iv {
+            write_field(&mut fields, TAG_IV, iv)?;
+        }
+        if let Some(derivation) = &self.key_derivation {
+            let mut value = vec![derivation.algorithm.id()];
+            value.extend_from_slice(&derivation.info);
+            write_field(&mut fields, TAG_KEY_DERIVATION, &value)?;
+        }
+        if let Some(wrapped_key) = &self.wrapped_key {
+            write_field(&mut fields, TAG_WRAPPED_KEY, wrapped_key)?;
+        }
+        if let Some(compression) = &self.compression {
+            write_field(&mut fields, TAG_COMPRESSION, &[compression.id()])?;
+        }
+        for recipient in &self.recipients {
+            let value = [&[recipient.algorithm.id()][..], &recipient.wrapped_key].concat();
+            write_field(&mut fields, TAG_RECIPIENT, &value)?;
+        }
+
+        if fields.len() > MAX_FIELDS_LEN {
+            return Err(CryptoCoreError::InvalidArgument(format!(
+                "File header would exceed {} bytes",
+                MAX_FIELDS_LEN
+            )));
+        }
+        let mut header = Vec::with_capacity(PREAMBLE_LEN + fields.len());
+        header.extend_from_slice(MAGIC);
+        header.push(VERSION);
+        header.extend_from_slice(&(fields.len() as u32).to_be_bytes());
+        header.extend_from_slice(&fields);
+        Ok(header)
+    }
+
+    /// What a `--sign-with` signature is bound to: the data key as encrypted
+    /// to each recipient. Re-encrypting the signed plaintext to someone else
+    /// changes these, while `rewrap` and `reencrypt` leave them alone.
+    pub fn signature_context(&self) -> Result<Vec<u8>> {
+        let mut context = Vec::new();
+        for recipient in &self.recipients {
+            let value = [&[recipient.algorithm.id()][..], &recipient.wrapped_key].concat();
+            write_field(&mut context, TAG_RECIPIENT, &value)?;
+        }
+        Ok(context)
+    }
+
+    /// What GCM and OCB authenticate along with the payload: the header
+    /// without the wrapped data key, which `rewrap` replaces and AES-KW
+    /// already protects.
+    pub fn associated_data(&self) -> Result<Vec<u8>> {
+        FileHeader { wrapped_key: None, ..self.clone() }.to_bytes()
+    }
+
+    /// Parses the header at the start of `data`, returning it with the payload offset.
+    pub fn parse(data: &[u8]) -> Result<(Self, usize)> {
+        if !Self::is_present(data) {
+            return Err(malformed("missing magic bytes"));
+        }
+        if data.len() < PREAMBLE_LEN {
+            return Err(malformed("truncated header"));
+        }
+        if data[MAGIC.len()] != VERSION {
+            return Err(CryptoCoreError::FileError(format!(
+                "Unsupported header version: {}",
+                data[MAGIC.len()]
+            )));
+        }
+
+        let fields_len = fields_len(&data[..PREAMBLE_LEN])?;
+        let end = PREAMBLE_LEN
+            .checked_add(fields_len)
+            .filter(|&end| end <= data.len())
+            .ok_or_else(|| malformed("truncated header"))?;
+
+        let mut header = FileHeader::default();
+        let mut fields = &data[PREAMBLE_LEN..end];
+        while !fields.is_empty() {
+            if fields.len() < 3 {
+                return Err(malformed("truncated field"));
+            }
+            let tag = fields[0];
+            let len = u16::from_be_bytes([fields[1], fields[2]]) as usize;
+            let value = fields.get(3..3 + len).ok_or_else(|| malformed("truncated field"))?;
+            fields = &fields[3 + len..];
+
+            match tag {
+                TAG_IV => header.iv = Some(value.to_vec()),
+                TAG_KEY_DERIVATION => {
+                    let (&id, info) = value.split_first().ok_or_else(|| malformed("empty key derivation"))?;
+                    header.key_derivation = Some(KeyDerivation {
+                        algorithm: KdfAlgorithm::from_id(id)?,
+                        info: info.to_vec(),
+                    });
+                }
+                TAG_WRAPPED_KEY => {
+                    if value.len() < MIN_WRAPPED_KEY_LEN || !value.len().is_multiple_of(8) {
+                        return Err(malformed("bad wrapped key length"));
+                    }
+                    header.wrapped_key = Some(value.to_vec());
+                }
+                TAG_COMPRESSION => {
+                    let [id] = value else {
+                        return Err(malformed("bad compression field"));
+                    };
+                    header.compression = Some(CompressionAlgorithm::from_id(*id)?);
+                }
+                TAG_RECIPIENT => {
+                    let (&id, wrapped_key) = value.split_first().ok_or_else(|| malformed("empty recipient"))?;
+                    header.recipients.push(RecipientKey {
+                        algorithm: KeyAlgorithm::from_id(id)?,
+                        wrapped_key: wrapped_key.to_vec(),
+                    });
+                }
+                // Every field changes how the payload must be processed, so
+                // skipping an unknown one would silently produce garbage
+                _ => {
+                    return Err(CryptoCoreError::FileError(format!(
+                        "Unsupported header field 0x{:02x}; the file was written by a newer version",
+                        tag
+                    )))
+                }
+            }
+        }
+
+        Ok((header, end))
+    }
+
+    /// Reads just the header from the start of a stream, leaving the payload unread.
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Option<(Self, usize)>> {
+        let mut preamble = [0u8; PREAMBLE_LEN];
+        let mut filled = 0;
+        while filled < PREAMBLE_LEN {
+            let read = reader.read(&mut preamble[filled..])?;
+            if read == 0 {
+                break;
+            }
+            filled += read;
+        }
+        if filled < PREAMBLE_LEN || !Self::is_present(&preamble) {
+            return Ok(None);
+        }
+
+        // The length is checked before it decides how much is read
+        let fields_len = fields_len(&preamble)?;
+        let mut data = preamble.to_vec();
+        reader.take(fields_len as u64).read_to_end(&mut data)?;
+        if data.len() < PREAMBLE_LEN + fields_len {
+            return Err(malformed("truncated header"));
+        }
+
+        Self::parse(&data).map(Some)
+    }
+
+    /// The key the payload is encrypted under, given the key supplied by the user.
+    pub fn content_key(&self, key: &[u8]) -> Result<Vec<u8>> {
+        if !self.recipients.is_empty() {
+            return Err(CryptoCoreError::InvalidArgument(
+                "File is encrypted to public-key recipients; decrypt it with --private-key".to_string(),
+            ));
+        }
+        let key_encryption_key = self.key_encryption_key(key)?;
+        match &self.wrapped_key {
+            Some(wrapped_key) => keywrap::unwrap(&key_encryption_key, wrapped_key),
+            None => Ok(key_encryption_key),
+        }
+    }
+
+    /// Stores `data_key` wrapped under the (derived) master key.
+    pub fn wrap_data_key(&mut self, key: &[u8], data_key: &[u8]) -> Result<()> {
+        let key_encryption_key = self.key_encryption_key(key)?;
+        self.wrapped_key = Some(keywrap::wrap(&key_encryption_key, data_key)?);
+        Ok(())
+    }
+
+    /// Re-wraps the data key from `old_key` to `new_key`; the payload is unaffected.
+    pub fn rewrap(&mut self, old_key: &[u8], new_key: &[u8]) -> Result<()> {
+        if self.wrapped_key.is_none() {
+            return Err(CryptoCoreError::InvalidArgument(
+                "File was not encrypted in envelope mode".to_string(),
+            ));
+        }
+
+        let data_key = self.content_key(old_key)?;
+        self.wrap_data_key(new_key, &data_key)
+    }
+
+    /// Stores `data_key` encrypted to each of `recipients`.
+    pub fn wrap_for_recipients(&mut self, recipients: &[PublicKey], data_key: &[u8]) -> Result<()> {
+        self.recipients = recipients
+            .iter()
+            .map(|recipient| {
+                Ok(RecipientKey { algorithm: recipient.algorithm(), wrapped_key: recipient.wrap_key(data_key)? })
+            })
+            .collect::<Result<_>>()?;
+        Ok(())
+    }
+
+    /// The data key, recovered with whichever of `private_keys` belongs to a recipient.
+    pub fn recipient_content_key(&self, private_keys: &[PrivateKey]) -> Result<Vec<u8>> {
+        if self.recipients.is_empty() {
+            return Err(CryptoCoreError::InvalidArgument(
+                "File is not encrypted to public-key recipients; decrypt it with --key".to_string(),
+            ));
+        }
+
+        // Recipients are not identified, so every key of the right type is tried
+        for recipient in &self.recipients {
+            for private_key in private_keys.iter().filter(|key| key.algorithm() == recipient.algorithm) {
+                if let Ok(data_key) = private_key.unwrap_key(&recipient.wrapped_key) {
+                    return Ok(data_key);
+                }
+            }
+        }
+        Err(CryptoCoreError::AuthenticationFailed(
+            "None of the private keys belongs to a recipient of this file".to_string(),
+        ))
+    }
+
+    fn key_encryption_key(&self, key: &[u8]) -> Result<Vec<u8>> {
+        match &self.key_derivation {
+            Some(derivation) => kdf::hkdf(derivation.algorithm, key, None, &derivation.info, key.len()),
+            None => Ok(key.to_vec()),
+        }
+    }
+}
+
+fn write_field(out: &mut Vec<u8>, tag: u8, value: &[u8]) -> Result<()> {
+    let len = u16::try_from(value.len())
+        .map_err(|_| CryptoCoreError::InvalidArgument("Header field is too long".to_string()))?;
+    out.push(tag);
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(value);
+    Ok(())
+}
+
+// The fields length from a preamble, refused past MAX_FIELDS_LEN
+fn fields_len(preamble: &[u8]) -> Result<usize> {
+    let fields_len = u32::from_be_bytes(preamble[MAGIC.len() + 1..PREAMBLE_LEN].try_into().unwrap()) as usize;
+    if fields_len > MAX_FIELDS_LEN {
+        return Err(malformed("header is too long"));
+    }
+    Ok(fields_len)
+}
+
+fn malformed(reason: &str) -> CryptoCoreError {
+    CryptoCoreError::FileError(format!("Malformed file header: {}", reason))
+}
@@ -0,0 +1,141 @@
+//! Manifest of a directory tree encrypted with `--recursive`.
+//!
+//! One line per entry, paths relative to the tree root with `/` separators:
+//!
+//! ```text
+//! dir <path>
+//! file <size> <sha256 hex> <path>    encrypted, stored as `<path>.enc`
+//! copy <size> <sha256 hex> <path>    already encrypted, stored verbatim
+//! ```
+//!
+//! The manifest itself is stored encrypted next to the files, so it leaks no
+//! more than the mirror tree does.
+
+use std::path::{Component, Path, PathBuf};
+use crate::error::{CryptoCoreError, Result};
+
+pub const MANIFEST_NAME: &str = ".cryptocore-manifest";
+pub const ENCRYPTED_SUFFIX: &str = ".enc";
+
+const FIRST_LINE: &str = "cryptocore-manifest 1";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ManifestEntry {
+    Directory {
+        path: String,
+    },
+    File {
+        path: String,
+        size: u64,
+        digest: Vec<u8>,
+        /// Whether the file was encrypted, or copied as is because it already was
+        encrypted: bool,
+    },
+}
+
+impl ManifestEntry {
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Directory { path } | Self::File { path, .. } => path,
+        }
+    }
+
+    /// Location of the entry's data inside the encrypted tree.
+    pub fn stored_path(&self) -> String {
+        match self {
+            Self::File { path, encrypted: true, .. } => format!("{}{}", path, ENCRYPTED_SUFFIX),
+            _ => self.path().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Manifest {
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = String::from(FIRST_LINE);
+        out.push('\n');
+
+        for entry in &self.entries {
+            match entry {
+                ManifestEntry::Directory { path } => out.push_str(&format!("dir {}\n", path)),
+                ManifestEntry::File { path, size, digest, encrypted } => {
+                    let kind = if *encrypted { "file" } else { "copy" };
+                    out.push_str(&format!("{} {} {} {}\n", kind, size, hex::encode(digest), path));
+                }
+            }
+        }
+
+        out.into_bytes()
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let content = std::str::from_utf8(data).map_err(|_| malformed(0, "not valid UTF-8"))?;
+        let mut lines = content.lines().enumerate();
+
+        if lines.next().map(|(_, line)| line) != Some(FIRST_LINE) {
+            return Err(malformed(1, "unsupported manifest version"));
+        }
+
+        let mut entries = Vec::new();
+        for (number, line) in lines {
+            let invalid = || malformed(number + 1, line);
+            let (kind, rest) = line.split_once(' ').ok_or_else(invalid)?;
+
+            let entry = match kind {
+                "dir" => ManifestEntry::Directory { path: rest.to_string() },
+                "file" | "copy" => {
+                    let mut fields = rest.splitn(3, ' ');
+                    let (Some(size), Some(digest), Some(path)) = (fields.next(), fields.next(), fields.next()) else {
+                        return Err(invalid());
+                    };
+                    ManifestEntry::File {
+                        path: path.to_string(),
+                        size: size.parse().map_err(|_| invalid())?,
+                        digest: hex::decode(digest).map_err(|_| invalid())?,
+                        encrypted: kind == "file",
+                    }
+                }
+                _ => return Err(invalid()),
+            };
+
+            // Entries are joined onto the output directory, so they must not escape it
+            if relative_path(entry.path()).is_none() {
+                return Err(invalid());
+            }
+            entries.push(entry);
+        }
+
+        Ok(Self { entries })
+    }
+}
+
+/// Converts a path relative to the tree root to its manifest form.
+pub fn manifest_path(relative: &Path) -> Result<String> {
+    let parts = relative
+        .components()
+        .map(|component| match component {
+            Component::Normal(part) => part.to_str().filter(|part| !part.contains('\n')),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| {
+            CryptoCoreError::FileError(format!("Unsupported file name: {}", relative.display()))
+        })?;
+    Ok(parts.join("/"))
+}
+
+/// Converts a manifest path back to a relative path, rejecting anything but plain names.
+pub fn relative_path(path: &str) -> Option<PathBuf> {
+    let relative = PathBuf::from(path);
+    let plain = !path.is_empty()
+        && relative.components().all(|component| matches!(component, Component::Normal(_)));
+    plain.then_some(relative)
+}
+
+fn malformed(line: usize, reason: &str) -> CryptoCoreError {
+    CryptoCoreError::FileError(format!("Malformed manifest line {}: {}", line, reason))
+}
@@ -1,7 +1,21 @@
-use std::fs;
+pub mod archive;
+pub mod armor;
+pub mod checksums;
+pub mod compression;
+pub mod csv;
+pub mod header;
+pub mod openssl;
+pub mod pem;
+pub mod signed;
+pub mod manifest;
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
+use crate::core::crypto::random;
 use crate::error::{CryptoCoreError, Result};
 use crate::Operation;
+use header::FileHeader;
 
 pub fn read_file(path: &Path) -> Result<Vec<u8>> {
     fs::read(path).map_err(|e| {
@@ -21,6 +35,43 @@ pub fn write_file(path: &Path, data: &[u8]) -> Result<()> {
     })
 }
 
+/// Writes private key material to a new file that only its owner can read,
+/// refusing to replace a file that is already there.
+pub fn write_private_file(path: &Path, data: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| {
+            CryptoCoreError::FileError(format!("Failed to create directories: {}", e))
+        })?;
+    }
+
+    let write_error = |e: std::io::Error| {
+        CryptoCoreError::FileError(format!("Failed to write file {}: {}", path.display(), e))
+    };
+    let mut file = private_file_options().open(path).map_err(|e| match e.kind() {
+        ErrorKind::AlreadyExists => CryptoCoreError::FileError(format!(
+            "{} already exists; refusing to overwrite it with a new key",
+            path.display()
+        )),
+        _ => write_error(e),
+    })?;
+    file.write_all(data).and_then(|_| file.sync_all()).map_err(write_error)
+}
+
+#[cfg(unix)]
+fn private_file_options() -> OpenOptions {
+    use std::os::unix::fs::OpenOptionsExt;
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true).mode(0o600);
+    options
+}
+
+#[cfg(not(unix))]
+fn private_file_options() -> OpenOptions {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    options
+}
+
 pub fn derive_output_path(input_path: &Path, operation: &Operation) -> PathBuf {
     match operation {
         Operation::Encrypt => input_path.with_extension("enc"),
@@ -34,25 +85,35 @@ pub fn derive_output_path(input_path: &Path, operation: &Operation) -> PathBuf {
 }
 
 // IV handling functions
-pub fn generate_iv() -> [u8; 16] {
+pub fn generate_iv() -> Result<[u8; 16]> {
     let mut iv = [0u8; 16];
-    getrandom::fill(&mut iv).expect("Failed to generate random IV");
-    iv
+    random::fill(&mut iv)?;
+    Ok(iv)
+}
+
+/// Random IV or nonce of the size a mode expects (see `crypto::iv_size`).
+pub fn generate_nonce(length: usize) -> Result<Vec<u8>> {
+    random::bytes(length)
+}
+
+pub fn generate_key(length: usize) -> Result<Vec<u8>> {
+    random::bytes(length)
 }
 
 pub fn read_file_with_iv(path: &Path) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
-    let data = read_file(path)?;
-    
-    if data.len() < 16 {
+    let (content, iv) = split_iv(read_file(path)?, 16)?;
+    Ok((content, Some(iv)))
+}
+
+pub fn split_iv(mut data: Vec<u8>, iv_len: usize) -> Result<(Vec<u8>, Vec<u8>)> {
+    if data.len() < iv_len {
         return Err(CryptoCoreError::FileError(
             "File is too short to contain IV".to_string()
         ));
     }
     
-    let iv = Some(data[..16].to_vec());
-    let content = data[16..].to_vec();
-    
-    Ok((content, iv))
+    let content = data.split_off(iv_len);
+    Ok((content, data))
 }
 
 pub fn write_file_with_iv(path: &Path, iv: &[u8], data: &[u8]) -> Result<()> {
@@ -60,4 +121,64 @@ pub fn write_file_with_iv(path: &Path, iv: &[u8], data: &[u8]) -> Result<()> {
     combined.extend_from_slice(iv);
     combined.extend_from_slice(data);
     write_file(path, &combined)
-}
\ No newline at end of file
+}
+
+pub fn read_file_with_header(path: &Path) -> Result<(Option<FileHeader>, Vec<u8>)> {
+    // Armored files are recognised by their BEGIN line, so no flag is needed to read them
+    let data = armor::dearmor_if_armored(read_file(path)?)?;
+
+    if !FileHeader::is_present(&data) {
+        return Ok((None, data));
+    }
+
+    let (header, offset) = FileHeader::parse(&data)?;
+    Ok((Some(header), data[offset..].to_vec()))
+}
+
+pub fn write_file_with_header(path: &Path, header: &FileHeader, data: &[u8]) -> Result<()> {
+    let mut combined = header.to_bytes()?;
+    combined.extend_from_slice(data);
+    write_file(path, &combined)
+}
+
+pub fn read_header(path: &Path) -> Result<Option<(FileHeader, usize)>> {
+    let mut file = File::open(path).map_err(|e| {
+        CryptoCoreError::FileError(format!("Failed to read file {}: {}", path.display(), e))
+    })?;
+    FileHeader::read_from(&mut file)
+}
+
+/// Replaces the `old_len` header bytes of `path`, leaving the payload bytes untouched.
+///
+/// The new file is always written beside the old one, synced and renamed over
+/// it, so a crash leaves either the old header or the new one, never a mix.
+pub fn replace_header(path: &Path, old_len: usize, header: &FileHeader) -> Result<()> {
+    let header_bytes = header.to_bytes()?;
+    let write_error = |e: std::io::Error| {
+        CryptoCoreError::FileError(format!("Failed to write file {}: {}", path.display(), e))
+    };
+
+    let temp_path = temp_path_for(path)?;
+    let copied = (|| {
+        let mut source = File::open(path)?;
+        let permissions = source.metadata()?.permissions();
+        source.seek(SeekFrom::Start(old_len as u64))?;
+        let mut target = File::create(&temp_path)?;
+        target.write_all(&header_bytes)?;
+        std::io::copy(&mut source, &mut target)?;
+        target.set_permissions(permissions)?;
+        target.sync_all()
+    })();
+
+    if let Err(e) = copied.and_then(|_| fs::rename(&temp_path, path)) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(write_error(e));
+    }
+    Ok(())
+}
+
+/// Hidden sibling of `path` to write into before atomically renaming it over `path`.
+pub fn temp_path_for(path: &Path) -> Result<PathBuf> {
+    let name = path.file_name().and_then(|s| s.to_str()).unwrap_or("output");
+    Ok(path.with_file_name(format!(".{}.{}.tmp", name, hex::encode(generate_key(4)?))))
+}
@@ -0,0 +1,107 @@
+//! Files in the format of `openssl enc` with a password: `Salted__ | salt (8 bytes) | ciphertext`.
+//!
+//! Key and IV are derived from the password and salt either with
+//! `EVP_BytesToKey` (a single iteration of the digest, what `openssl enc` does
+//! without `-pbkdf2`) or with PBKDF2-HMAC (`-pbkdf2`/`-iter`). The digest is
+//! SHA-256 since OpenSSL 1.1.0; files from older versions need MD5 (`-md md5`).
+
+use crate::error::{CryptoCoreError, Result};
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+pub const MAGIC: &[u8; 8] = b"Salted__";
+pub const SALT_SIZE: usize = 8;
+/// Iteration count `openssl enc -pbkdf2` uses when `-iter` is not given.
+pub const DEFAULT_ITERATIONS: u32 = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpensslDigest {
+    Md5,
+    Sha256,
+}
+
+impl OpensslDigest {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "md5" => Ok(Self::Md5),
+            "sha256" => Ok(Self::Sha256),
+            _ => Err(CryptoCoreError::InvalidArgument(
+                format!("Unsupported OpenSSL digest: {}", name)
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpensslKdf {
+    BytesToKey(OpensslDigest),
+    Pbkdf2 { digest: OpensslDigest, iterations: u32 },
+}
+
+pub fn is_salted(data: &[u8]) -> bool {
+    data.len() >= MAGIC.len() + SALT_SIZE && data.starts_with(MAGIC)
+}
+
+/// Splits a salted file into its salt and ciphertext.
+pub fn parse(data: &[u8]) -> Result<(&[u8], &[u8])> {
+    if !is_salted(data) {
+        return Err(CryptoCoreError::FileError(
+            "Not an OpenSSL salted file (files written with -nosalt are not supported)".to_string(),
+        ));
+    }
+    Ok(data[MAGIC.len()..].split_at(SALT_SIZE))
+}
+
+pub fn to_bytes(salt: &[u8], ciphertext: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(MAGIC.len() + salt.len() + ciphertext.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(salt);
+    out.extend_from_slice(ciphertext);
+    out
+}
+
+/// Derives `key_len` key bytes followed by `iv_len` IV bytes the way `openssl enc` does.
+pub fn derive_key_iv(
+    kdf: OpensslKdf,
+    password: &[u8],
+    salt: &[u8],
+    key_len: usize,
+    iv_len: usize,
+) -> Result<(Vec<u8>, Vec<u8>)> {
+    let mut material = vec![0u8; key_len + iv_len];
+
+    match kdf {
+        OpensslKdf::BytesToKey(digest) => {
+            // D_1 = H(password || salt), D_i = H(D_{i-1} || password || salt)
+            let mut block = Vec::new();
+            let mut filled = 0;
+            while filled < material.len() {
+                let mut input = block;
+                input.extend_from_slice(password);
+                input.extend_from_slice(salt);
+                block = match digest {
+                    OpensslDigest::Md5 => Md5::digest(&input).to_vec(),
+                    OpensslDigest::Sha256 => Sha256::digest(&input).to_vec(),
+                };
+
+                let take = block.len().min(material.len() - filled);
+                material[filled..filled + take].copy_from_slice(&block[..take]);
+                filled += take;
+            }
+        }
+        OpensslKdf::Pbkdf2 { digest, iterations } => {
+            if iterations == 0 {
+                return Err(CryptoCoreError::InvalidArgument(
+                    "PBKDF2 needs at least one iteration".to_string(),
+                ));
+            }
+            match digest {
+                OpensslDigest::Md5 => pbkdf2::pbkdf2_hmac::<Md5>(password, salt, iterations, &mut material),
+                OpensslDigest::Sha256 => pbkdf2::pbkdf2_hmac::<Sha256>(password, salt, iterations, &mut material),
+            }
+        }
+    }
+
+    let iv = material.split_off(key_len);
+    Ok((material, iv))
+}
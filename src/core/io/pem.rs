@@ -0,0 +1,53 @@
+//! PEM (RFC 7468) for key files: DER between `-----BEGIN <label>-----` and
+//! `-----END <label>-----` lines, base64 at 64 characters per line. Unlike
+//! [`armor`](super::armor) there is no checksum line.
+
+use crate::error::{CryptoCoreError, Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+pub const PUBLIC_KEY: &str = "PUBLIC KEY";
+pub const PRIVATE_KEY: &str = "PRIVATE KEY";
+pub const RSA_PUBLIC_KEY: &str = "RSA PUBLIC KEY";
+pub const RSA_PRIVATE_KEY: &str = "RSA PRIVATE KEY";
+pub const EC_PRIVATE_KEY: &str = "EC PRIVATE KEY";
+
+const LINE_LENGTH: usize = 64;
+
+pub fn encode(label: &str, der: &[u8]) -> String {
+    let encoded = STANDARD.encode(der);
+    let mut out = format!("-----BEGIN {}-----\n", label);
+    for line in encoded.as_bytes().chunks(LINE_LENGTH) {
+        out.push_str(std::str::from_utf8(line).unwrap());
+        out.push('\n');
+    }
+    out.push_str(&format!("-----END {}-----\n", label));
+    out
+}
+
+/// The label and contents of the first PEM block in `text`; anything before it
+/// (such as the comments `openssl` writes) is skipped.
+pub fn decode(text: &str) -> Result<(String, Vec<u8>)> {
+    let malformed = |reason: &str| CryptoCoreError::InvalidKey(format!("Malformed PEM: {}", reason));
+
+    let mut lines = text.lines().map(str::trim).skip_while(|line| !line.starts_with("-----BEGIN "));
+    let label = lines
+        .next()
+        .and_then(|line| line.strip_prefix("-----BEGIN "))
+        .and_then(|line| line.strip_suffix("-----"))
+        .ok_or_else(|| malformed("no BEGIN line"))?
+        .to_string();
+
+    let end_line = format!("-----END {}-----", label);
+    let mut encoded = String::new();
+    loop {
+        match lines.next() {
+            Some(line) if line == end_line => break,
+            Some(line) => encoded.push_str(line),
+            None => return Err(malformed("no END line")),
+        }
+    }
+
+    let der = STANDARD.decode(&encoded).map_err(|_| malformed("invalid base64"))?;
+    Ok((label, der))
+}
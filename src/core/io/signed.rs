@@ -0,0 +1,95 @@
+//! Files with an embedded signature.
+//!
+//! Layout: `MAGIC | VERSION | key algorithm id (u8) | message length (u64,
+//! big-endian) | message | signature`. The signature covers everything in
+//! front of it, so the algorithm and length cannot be swapped out either.
+//!
+//! A signature can also be bound to a context that is not stored in the file,
+//! such as the recipients it was encrypted to: it then only verifies when the
+//! same context is supplied again.
+
+use crate::core::crypto::asymmetric::{KeyAlgorithm, PrivateKey, PublicKey};
+use crate::error::{CryptoCoreError, Result};
+
+pub const MAGIC: &[u8; 10] = b"CRYPTOSIGN";
+pub const VERSION: u8 = 1;
+
+const PREAMBLE_LEN: usize = MAGIC.len() + 1 + 1 + 8;
+
+pub fn is_signed(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// `message` with a signature by `key` appended.
+pub fn sign(key: &PrivateKey, message: &[u8]) -> Result<Vec<u8>> {
+    sign_bound(key, message, &[])
+}
+
+/// As [`sign`], with the signature also covering `context`.
+pub fn sign_bound(key: &PrivateKey, message: &[u8], context: &[u8]) -> Result<Vec<u8>> {
+    let mut signed = Vec::with_capacity(PREAMBLE_LEN + message.len() + 128);
+    signed.extend_from_slice(MAGIC);
+    signed.push(VERSION);
+    signed.push(key.algorithm().id());
+    signed.extend_from_slice(&(message.len() as u64).to_be_bytes());
+    signed.extend_from_slice(message);
+    let signature = key.sign(&[&signed[..], context].concat())?;
+    signed.extend_from_slice(&signature);
+    Ok(signed)
+}
+
+/// The algorithm that signed `data`, without checking anything else.
+pub fn algorithm(data: &[u8]) -> Result<KeyAlgorithm> {
+    Parts::split(data).map(|parts| parts.algorithm)
+}
+
+/// Checks the signature of a signed file against `key` and returns the message.
+pub fn open<'a>(key: &PublicKey, data: &'a [u8]) -> Result<&'a [u8]> {
+    open_bound(key, data, &[])
+}
+
+/// As [`open`], for a signature made with [`sign_bound`] over `context`.
+pub fn open_bound<'a>(key: &PublicKey, data: &'a [u8], context: &[u8]) -> Result<&'a [u8]> {
+    let parts = Parts::split(data)?;
+    if parts.algorithm != key.algorithm() {
+        return Err(CryptoCoreError::AuthenticationFailed(format!(
+            "File is signed with {}, but the public key is {}",
+            parts.algorithm,
+            key.algorithm()
+        )));
+    }
+    key.verify(&[parts.signed, context].concat(), parts.signature)?;
+    Ok(&parts.signed[PREAMBLE_LEN..])
+}
+
+struct Parts<'a> {
+    algorithm: KeyAlgorithm,
+    /// Preamble and message, the bytes the signature covers
+    signed: &'a [u8],
+    signature: &'a [u8],
+}
+
+impl<'a> Parts<'a> {
+    fn split(data: &'a [u8]) -> Result<Self> {
+        let malformed = || CryptoCoreError::FileError("Malformed signed file".to_string());
+        if !is_signed(data) || data.len() < PREAMBLE_LEN {
+            return Err(CryptoCoreError::FileError("Not a signed file".to_string()));
+        }
+        if data[MAGIC.len()] != VERSION {
+            return Err(CryptoCoreError::FileError(format!(
+                "Unsupported signed file version: {}",
+                data[MAGIC.len()]
+            )));
+        }
+        let algorithm = KeyAlgorithm::from_id(data[MAGIC.len() + 1])?;
+
+        let length = u64::from_be_bytes(data[PREAMBLE_LEN - 8..PREAMBLE_LEN].try_into().unwrap());
+        let end = usize::try_from(length)
+            .ok()
+            .and_then(|length| PREAMBLE_LEN.checked_add(length))
+            .filter(|&end| end <= data.len())
+            .ok_or_else(malformed)?;
+        let (signed, signature) = data.split_at(end);
+        Ok(Self { algorithm, signed, signature })
+    }
+}
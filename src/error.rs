@@ -17,6 +17,12 @@ pub enum CryptoCoreError {
     #[error("File error: {0}")]
     FileError(String),
     
+    #[error("Authentication failed: {0}")]
+    AuthenticationFailed(String),
+    
+    #[error("Integrity check failed: {0}")]
+    IntegrityCheckFailed(String),
+    
     #[error("Padding error: {0}")]
     PaddingError(String),
     
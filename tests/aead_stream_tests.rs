@@ -0,0 +1,192 @@
+use assert_cmd::Command;
+use cryptocore::core::crypto::aead_stream::{self, Opener, Sealer, StreamAead, StreamHeader, HEADER_SIZE};
+use cryptocore::core::crypto::kdf::{self, KdfAlgorithm};
+use cryptocore::core::crypto::modes::gcm::GcmMode;
+use cryptocore::CryptoCoreError;
+use predicates::prelude::*;
+use std::fs;
+
+const KEY: [u8; 16] = [0x42; 16];
+const CHUNK_SIZE: usize = 32;
+const SEALED_SIZE: usize = CHUNK_SIZE + 16;
+
+fn header(aead: StreamAead) -> StreamHeader {
+    StreamHeader { aead, chunk_size: CHUNK_SIZE, salt: [9; 16], nonce_prefix: [7, 6, 5, 4, 3, 2, 1] }
+}
+
+fn seal(aead: StreamAead, plaintext: &[u8]) -> Result<Vec<u8>, CryptoCoreError> {
+    let mut output = Vec::new();
+    aead_stream::encrypt(&KEY, header(aead), plaintext, &mut output)?;
+    Ok(output)
+}
+
+fn open(stream: &[u8]) -> Result<Vec<u8>, CryptoCoreError> {
+    let mut output = Vec::new();
+    aead_stream::decrypt(&KEY, stream, &mut output)?;
+    Ok(output)
+}
+
+#[test]
+fn test_round_trip_at_chunk_boundaries() -> Result<(), Box<dyn std::error::Error>> {
+    for aead in [StreamAead::Gcm, StreamAead::Ocb] {
+        for length in [0, 1, CHUNK_SIZE - 1, CHUNK_SIZE, CHUNK_SIZE + 1, 3 * CHUNK_SIZE, 100] {
+            let plaintext: Vec<u8> = (0..length).map(|i| i as u8).collect();
+            let stream = seal(aead, &plaintext)?;
+
+            // A full final chunk is not followed by an empty one
+            let chunks = length.div_ceil(CHUNK_SIZE).max(1);
+            assert_eq!(stream.len(), HEADER_SIZE + length + chunks * 16, "{} bytes", length);
+            assert_eq!(open(&stream)?, plaintext);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_chunks_are_sealed_with_counter_and_last_flag() -> Result<(), Box<dyn std::error::Error>> {
+    let plaintext = [0xaau8; 40];
+    let stream = seal(StreamAead::Gcm, &plaintext)?;
+    let aad = &stream[..HEADER_SIZE];
+
+    // Under the per-stream key, not the one given
+    let key = kdf::hkdf(KdfAlgorithm::HkdfSha256, &KEY, Some(&[9; 16]), b"cryptocore stream key", KEY.len())?;
+    let first = GcmMode::new(&key, &[7, 6, 5, 4, 3, 2, 1, 0, 0, 0, 0, 0])?.encrypt_with_aad(aad, &plaintext[..32])?;
+    let last = GcmMode::new(&key, &[7, 6, 5, 4, 3, 2, 1, 0, 0, 0, 1, 1])?.encrypt_with_aad(aad, &plaintext[32..])?;
+    assert_eq!(&stream[HEADER_SIZE..], [first, last].concat());
+    Ok(())
+}
+
+#[test]
+fn test_salt_gives_each_stream_its_own_key() -> Result<(), Box<dyn std::error::Error>> {
+    let plaintext = [0x55u8; 40];
+    let mut other_salt = header(StreamAead::Gcm);
+    other_salt.salt[0] ^= 1;
+    let mut output = Vec::new();
+    aead_stream::encrypt(&KEY, other_salt, plaintext.as_slice(), &mut output)?;
+
+    // Same key and nonce prefix, yet no chunk repeats
+    let stream = seal(StreamAead::Gcm, &plaintext)?;
+    assert_ne!(stream[HEADER_SIZE..HEADER_SIZE + 16], output[HEADER_SIZE..HEADER_SIZE + 16]);
+    assert_eq!(open(&output)?, plaintext);
+    Ok(())
+}
+
+#[test]
+fn test_truncation_is_detected() -> Result<(), Box<dyn std::error::Error>> {
+    let stream = seal(StreamAead::Gcm, &[1u8; 3 * CHUNK_SIZE + 5])?;
+
+    // Dropping whole chunks leaves a final chunk that was not sealed as last
+    for end in [HEADER_SIZE + SEALED_SIZE, HEADER_SIZE + 3 * SEALED_SIZE] {
+        let result = open(&stream[..end]);
+        assert!(matches!(result, Err(CryptoCoreError::AuthenticationFailed(_))), "end {}", end);
+    }
+    // Cutting into a chunk, or removing every chunk
+    assert!(open(&stream[..stream.len() - 1]).is_err());
+    assert!(open(&stream[..HEADER_SIZE + SEALED_SIZE + 20]).is_err());
+    assert!(matches!(open(&stream[..HEADER_SIZE]), Err(CryptoCoreError::AuthenticationFailed(_))));
+    Ok(())
+}
+
+#[test]
+fn test_reordering_and_extension_are_detected() -> Result<(), Box<dyn std::error::Error>> {
+    let stream = seal(StreamAead::Ocb, &[2u8; 3 * CHUNK_SIZE])?;
+    let chunks: Vec<&[u8]> = stream[HEADER_SIZE..].chunks(SEALED_SIZE).collect();
+    let header_bytes = &stream[..HEADER_SIZE];
+
+    let swapped = [header_bytes, chunks[1], chunks[0], chunks[2]].concat();
+    assert!(matches!(open(&swapped), Err(CryptoCoreError::AuthenticationFailed(_))));
+
+    let repeated = [header_bytes, chunks[0], chunks[1], chunks[2], chunks[2]].concat();
+    assert!(matches!(open(&repeated), Err(CryptoCoreError::AuthenticationFailed(_))));
+
+    // The header is bound to every chunk
+    let mut tampered = stream.clone();
+    tampered[HEADER_SIZE - 1] ^= 1;
+    assert!(matches!(open(&tampered), Err(CryptoCoreError::AuthenticationFailed(_))));
+    Ok(())
+}
+
+#[test]
+fn test_decrypt_stops_at_the_failing_chunk() -> Result<(), Box<dyn std::error::Error>> {
+    let plaintext: Vec<u8> = (0..3 * CHUNK_SIZE as u8).collect();
+    let mut stream = seal(StreamAead::Gcm, &plaintext)?;
+    stream[HEADER_SIZE + SEALED_SIZE + 3] ^= 1;
+
+    let mut output = Vec::new();
+    let result = aead_stream::decrypt(&KEY, stream.as_slice(), &mut output);
+    assert!(matches!(result, Err(CryptoCoreError::AuthenticationFailed(_))));
+    // Only the verified first chunk was released
+    assert_eq!(output, &plaintext[..CHUNK_SIZE]);
+    Ok(())
+}
+
+#[test]
+fn test_sealer_and_opener_enforce_chunk_order() -> Result<(), Box<dyn std::error::Error>> {
+    let mut sealer = Sealer::new(&KEY, header(StreamAead::Gcm))?;
+    assert!(sealer.seal_chunk(&[0u8; 10], false).is_err());
+    let first = sealer.seal_chunk(&[0u8; CHUNK_SIZE], false)?;
+    let last = sealer.seal_chunk(b"tail", true)?;
+    assert!(sealer.seal_chunk(b"more", true).is_err());
+
+    let mut opener = Opener::new(&KEY, header(StreamAead::Gcm))?;
+    assert!(opener.open_chunk(&last, true).is_err());
+    let mut opener = Opener::new(&KEY, header(StreamAead::Gcm))?;
+    assert_eq!(opener.open_chunk(&first, false)?, [0u8; CHUNK_SIZE]);
+    assert_eq!(opener.open_chunk(&last, true)?, b"tail");
+
+    assert!(StreamHeader::new(StreamAead::Gcm, 8).is_err());
+    assert!(matches!(Sealer::new(&KEY[..5], header(StreamAead::Gcm)), Err(CryptoCoreError::InvalidKey(_))));
+    let mut bad_size = header(StreamAead::Gcm).to_bytes();
+    bad_size[12..16].copy_from_slice(&u32::MAX.to_be_bytes());
+    assert!(matches!(StreamHeader::parse(&bad_size), Err(CryptoCoreError::FileError(_))));
+    Ok(())
+}
+
+#[test]
+fn test_cli_stream_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let plain = dir.path().join("big.bin");
+    let sealed = dir.path().join("big.bin.cstream");
+    let restored = dir.path().join("restored.bin");
+    let key = "000102030405060708090a0b0c0d0e0f";
+    let data: Vec<u8> = (0..5000u32).map(|i| (i * 7) as u8).collect();
+    fs::write(&plain, &data)?;
+
+    Command::cargo_bin("cryptocore")?
+        .args(["stream", "encrypt", "--key", key, "--mode", "ocb", "--chunk-size", "1"])
+        .arg(&plain)
+        .assert()
+        .success();
+    // 5 chunks of at most 1 KiB
+    assert_eq!(fs::read(&sealed)?.len(), HEADER_SIZE + data.len() + 5 * 16);
+
+    Command::cargo_bin("cryptocore")?
+        .arg("inspect")
+        .arg(&sealed)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Chunked stream: ocb with 1024-byte chunks"));
+
+    Command::cargo_bin("cryptocore")?
+        .args(["stream", "decrypt", "--key", key])
+        .arg(&sealed)
+        .arg("-o").arg(&restored)
+        .assert()
+        .success();
+    assert_eq!(fs::read(&restored)?, data);
+
+    // A truncated file is rejected and leaves no output behind
+    let stream = fs::read(&sealed)?;
+    fs::write(&sealed, &stream[..HEADER_SIZE + 2 * (1024 + 16)])?;
+    fs::remove_file(&restored)?;
+    Command::cargo_bin("cryptocore")?
+        .args(["stream", "decrypt", "--key", key])
+        .arg(&sealed)
+        .arg("-o").arg(&restored)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("truncated"));
+    assert!(!restored.exists());
+    assert_eq!(fs::read_dir(dir.path())?.count(), 2);
+    Ok(())
+}
@@ -0,0 +1,213 @@
+use assert_cmd::Command;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use cryptocore::core::crypto::age::{self, Identity, Recipient, X25519Identity, X25519Recipient};
+use cryptocore::core::crypto::chacha20poly1305::ChaCha20Poly1305;
+use cryptocore::core::crypto::curve25519::x25519;
+use cryptocore::core::crypto::kdf;
+use cryptocore::CryptoCoreError;
+use predicates::prelude::*;
+use std::fs;
+
+// Secret key bytes 0x01..=0x20
+const IDENTITY: &str = "AGE-SECRET-KEY-1QYPQXPQ9QCRSSZG2PVXQ6RS0ZQG3YYC5Z5TPWXQERGD3C8G7RUSQGPQYEE";
+const RECIPIENT: &str = "age1q73he0q5yzfu3d64msd3p6rvksnrwjk3d2598mgtmlqt9wrdr37q2vrn72";
+
+// Written by an independent Python implementation of the specification on top of
+// `cryptography`, to RECIPIENT and with the passphrase "correct horse" (work factor 10)
+const X25519_FILE: &str = "YWdlLWVuY3J5cHRpb24ub3JnL3YxCi0+IFgyNTUxOSA2ZmZpR21lTHFzclovL0hpbTRLM0VzeHh6M3dEWGRuQnp6QllERTVuczJvCk1ROWl3ZDNuTGZ5cHl2VkgyVFBNSkNacjRoUE42bHlNNlZKUCt4dGNVdlUKLS0tIEFta2loWkwzZnlqa0R6aG82dnpVU2lmcGI1VGl3NXlCTWQrSjI2ZkVoTXcKQ4WMBaXUnn4r9j3sVrDM1VUGq+lH1IC5y4UUmajnICTT5UvmE4QczF/Y6SVfAD9j";
+const SCRYPT_FILE: &str = "YWdlLWVuY3J5cHRpb24ub3JnL3YxCi0+IHNjcnlwdCBxOTBUZUY1OGo4dFFHQU1lUkY2VGRBIDEwClNQSjRkMVpBdmVGQVlhSDhGYmI3S29tdDlKMmpjeTBmSDBrNDNVTXRtWDQKLS0tIEM2QmFBOVVxc3p2U2hxZEpVYU5kTm1NeVJ1TVhqZVB3MWgzanF1Y1REdGsKh39HWvlSNGX4KYMAgHFPBnY9c1OZSYJViRiHpsSOhJZn1AOgM1KBoMDqtBrzDyJ5";
+
+fn encrypt(recipients: &[Recipient], data: &[u8]) -> cryptocore::Result<Vec<u8>> {
+    let mut output = Vec::new();
+    age::encrypt(recipients, data, &mut output)?;
+    Ok(output)
+}
+
+fn decrypt(identities: &[Identity], data: &[u8]) -> cryptocore::Result<Vec<u8>> {
+    let mut output = Vec::new();
+    age::decrypt(identities, data, &mut output)?;
+    Ok(output)
+}
+
+fn scrypt_identity(max_work_factor: u8) -> Identity {
+    Identity::Scrypt { passphrase: b"correct horse".to_vec(), max_work_factor }
+}
+
+#[test]
+fn test_rfc7748_x25519() -> Result<(), Box<dyn std::error::Error>> {
+    let alice: [u8; 32] = hex::decode("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a")?.try_into().unwrap();
+    let bob: [u8; 32] = hex::decode("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb")?.try_into().unwrap();
+    let alice_public = x25519::public_key(&alice);
+    let bob_public = x25519::public_key(&bob);
+    assert_eq!(hex::encode(alice_public), "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a");
+    assert_eq!(hex::encode(bob_public), "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f");
+
+    let shared = "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742";
+    assert_eq!(hex::encode(x25519::shared_secret(&alice, &bob_public)?), shared);
+    assert_eq!(hex::encode(x25519::shared_secret(&bob, &alice_public)?), shared);
+
+    // A low-order point gives an all-zero secret
+    assert!(x25519::shared_secret(&alice, &[0u8; 32]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_rfc8439_chacha20_poly1305() -> Result<(), Box<dyn std::error::Error>> {
+    let key = hex::decode("808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f")?;
+    let nonce: [u8; 12] = hex::decode("070000004041424344454647")?.try_into().unwrap();
+    let aad = hex::decode("50515253c0c1c2c3c4c5c6c7")?;
+    let plaintext = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+    let expected = "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d63dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b3692ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc3ff4def08e4b7a9de576d26586cec64b61161ae10b594f09e26a7e902ecbd0600691";
+
+    let aead = ChaCha20Poly1305::new(&key)?;
+    let sealed = aead.encrypt(&nonce, &aad, plaintext)?;
+    assert_eq!(hex::encode(&sealed), expected);
+    assert_eq!(aead.decrypt(&nonce, &aad, &sealed)?, plaintext);
+
+    let mut tampered = sealed.clone();
+    tampered[0] ^= 1;
+    assert!(matches!(aead.decrypt(&nonce, &aad, &tampered), Err(CryptoCoreError::AuthenticationFailed(_))));
+    Ok(())
+}
+
+#[test]
+fn test_rfc7914_scrypt() -> Result<(), Box<dyn std::error::Error>> {
+    let key = kdf::scrypt(b"password", b"NaCl", 10, 8, 16, 64)?;
+    assert_eq!(
+        hex::encode(key),
+        "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b3731622eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_key_encoding() -> Result<(), Box<dyn std::error::Error>> {
+    let identity = X25519Identity::parse(IDENTITY)?;
+    assert_eq!(identity.to_string(), IDENTITY);
+    assert_eq!(identity.recipient().to_string(), RECIPIENT);
+    assert_eq!(X25519Recipient::parse(RECIPIENT)?, identity.recipient());
+
+    // Checksum, kind of key and mixed case are all checked
+    assert!(X25519Recipient::parse(&RECIPIENT.replace("q7", "q8")).is_err());
+    assert!(X25519Recipient::parse(IDENTITY).is_err());
+    assert!(X25519Identity::parse(&IDENTITY.replacen('Q', "q", 1)).is_err());
+
+    let identities = age::parse_identities(&format!("# created: today\n# public key: {}\n\n{}\n", RECIPIENT, IDENTITY))?;
+    assert_eq!(identities.len(), 1);
+    assert!(age::parse_identities("# nothing here\n").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_decrypts_reference_files() -> Result<(), Box<dyn std::error::Error>> {
+    let identities = age::parse_identities(IDENTITY)?;
+    assert_eq!(decrypt(&identities, &STANDARD.decode(X25519_FILE)?)?, b"Attack at dawn.\n");
+    assert_eq!(decrypt(&[scrypt_identity(22)], &STANDARD.decode(SCRYPT_FILE)?)?, b"Attack at dawn.\n");
+
+    // The work factor cap protects against files that take too long to open
+    assert!(decrypt(&[scrypt_identity(9)], &STANDARD.decode(SCRYPT_FILE)?).is_err());
+    let wrong = Identity::Scrypt { passphrase: b"wrong".to_vec(), max_work_factor: 22 };
+    assert!(decrypt(&[wrong], &STANDARD.decode(SCRYPT_FILE)?).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_round_trip_and_tampering() -> Result<(), Box<dyn std::error::Error>> {
+    let first = X25519Identity::generate()?;
+    let second = X25519Identity::generate()?;
+    let recipients = [Recipient::X25519(first.recipient()), Recipient::X25519(second.recipient())];
+
+    // Empty, exactly one chunk, and more than one chunk
+    for length in [0, 64 * 1024, 150_000] {
+        let data: Vec<u8> = (0..length).map(|i| (i % 251) as u8).collect();
+        let file = encrypt(&recipients, &data)?;
+        assert_eq!(decrypt(&[Identity::X25519(second.clone())], &file)?, data);
+    }
+
+    let file = encrypt(&recipients, &vec![7u8; 150_000])?;
+    let stranger = Identity::X25519(X25519Identity::generate()?);
+    assert!(matches!(decrypt(&[stranger], &file), Err(CryptoCoreError::AuthenticationFailed(_))));
+
+    // Truncating at a chunk boundary leaves a final chunk that was not sealed as last
+    let header_length = file.windows(4).position(|w| w == b"\n---").unwrap();
+    let header_end = header_length + file[header_length + 1..].iter().position(|&b| b == b'\n').unwrap() + 2;
+    let truncated = &file[..header_end + 16 + 64 * 1024 + 16];
+    assert!(decrypt(&[Identity::X25519(first.clone())], truncated).is_err());
+
+    // Chunks are released as they are verified, so the stream stops at the bad one
+    let mut released = Vec::new();
+    let mut corrupted = file.clone();
+    corrupted[header_end + 16 + 64 * 1024 + 16 + 5] ^= 1;
+    assert!(age::decrypt(&[Identity::X25519(first.clone())], corrupted.as_slice(), &mut released).is_err());
+    assert_eq!(released, vec![7u8; 64 * 1024]);
+
+    // The header MAC covers the stanzas
+    let mut tampered = file.clone();
+    tampered[header_length - 1] ^= 1;
+    assert!(decrypt(&[Identity::X25519(first.clone())], &tampered).is_err());
+
+    // A passphrase must be the file's only recipient
+    let mixed = [Recipient::X25519(first.recipient()), Recipient::Scrypt { passphrase: b"x".to_vec(), work_factor: 10 }];
+    assert!(encrypt(&mixed, b"data").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_cli_age_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let key = dir.path().join("key.txt");
+    let plain = dir.path().join("notes.txt");
+    fs::write(&plain, "meet at noon")?;
+
+    Command::cargo_bin("cryptocore")?
+        .args(["age", "keygen", "-o"])
+        .arg(&key)
+        .assert()
+        .success()
+        .stderr(predicate::str::starts_with("Public key: age1"));
+    let contents = fs::read_to_string(&key)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(fs::metadata(&key)?.permissions().mode() & 0o777, 0o600);
+    }
+    Command::cargo_bin("cryptocore")?
+        .args(["age", "keygen", "-o"])
+        .arg(&key)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already exists"));
+    assert_eq!(fs::read_to_string(&key)?, contents);
+    let recipient = contents.lines().next().unwrap().trim_start_matches("# public key: ");
+
+    Command::cargo_bin("cryptocore")?
+        .args(["age", "encrypt", "-r", recipient, "-r", RECIPIENT])
+        .arg(&plain)
+        .assert()
+        .success();
+    let encrypted = dir.path().join("notes.txt.age");
+    assert!(fs::read(&encrypted)?.starts_with(b"age-encryption.org/v1\n-> X25519 "));
+
+    Command::cargo_bin("cryptocore")?
+        .args(["age", "decrypt", "-i"])
+        .arg(&key)
+        .arg(&encrypted)
+        .assert()
+        .success()
+        .stdout("meet at noon");
+
+    Command::cargo_bin("cryptocore")?
+        .args(["age", "encrypt", "--pass", "hunter2", "--work-factor", "10", "-o"])
+        .arg(&encrypted)
+        .arg(&plain)
+        .assert()
+        .success();
+    Command::cargo_bin("cryptocore")?
+        .args(["age", "decrypt", "--pass", "hunter3"])
+        .arg(&encrypted)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Incorrect age passphrase"));
+    Ok(())
+}
@@ -0,0 +1,192 @@
+use assert_cmd::Command;
+use cryptocore::core::io::archive::{ArchiveEntry, ArchiveReader, ArchiveWriter, EntryKind, SEGMENT_SIZE};
+use cryptocore::CryptoCoreError;
+use predicates::prelude::*;
+use std::fs;
+use std::io::Cursor;
+use std::time::{Duration, UNIX_EPOCH};
+
+const KEY: [u8; 16] = [0x42; 16];
+
+fn sample_archive() -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut archive = ArchiveWriter::new(Vec::new(), &KEY, "ctr")?;
+    archive.add_entry(&ArchiveEntry {
+        path: "docs".to_string(),
+        mode: 0o750,
+        modified: UNIX_EPOCH + Duration::new(1_000_000_000, 5),
+        kind: EntryKind::Directory,
+    })?;
+    archive.add_file(
+        &ArchiveEntry {
+            path: "docs/secret-plan.txt".to_string(),
+            mode: 0o600,
+            modified: UNIX_EPOCH - Duration::new(86_400, 250),
+            kind: EntryKind::File { size: 11 },
+        },
+        &mut &b"hello world"[..],
+    )?;
+    archive.add_entry(&ArchiveEntry {
+        path: "latest".to_string(),
+        mode: 0o777,
+        modified: UNIX_EPOCH,
+        kind: EntryKind::Symlink { target: "docs/secret-plan.txt".to_string() },
+    })?;
+    Ok(archive.finish()?)
+}
+
+#[test]
+fn test_archive_round_trip_keeps_metadata() -> Result<(), Box<dyn std::error::Error>> {
+    let data = sample_archive()?;
+    assert!(!data.windows(11).any(|window| window == b"secret-plan"));
+
+    let mut archive = ArchiveReader::open(Cursor::new(data), &KEY, "ctr")?;
+
+    let dir = archive.next_entry()?.unwrap();
+    assert_eq!((dir.path.as_str(), dir.mode, dir.kind), ("docs", 0o750, EntryKind::Directory));
+    assert_eq!(dir.modified, UNIX_EPOCH + Duration::new(1_000_000_000, 5));
+
+    let file = archive.next_entry()?.unwrap();
+    assert_eq!(file.kind, EntryKind::File { size: 11 });
+    assert_eq!(file.modified, UNIX_EPOCH - Duration::new(86_400, 250));
+    let mut contents = Vec::new();
+    archive.read_contents(&mut contents)?;
+    assert_eq!(contents, b"hello world");
+
+    let link = archive.next_entry()?.unwrap();
+    assert_eq!(link.kind, EntryKind::Symlink { target: "docs/secret-plan.txt".to_string() });
+    assert!(archive.next_entry()?.is_none());
+    Ok(())
+}
+
+#[test]
+fn test_archive_rejects_tampering_before_extraction() -> Result<(), Box<dyn std::error::Error>> {
+    let mut data = sample_archive()?;
+    let middle = data.len() / 2;
+    data[middle] ^= 1;
+
+    let result = ArchiveReader::open(Cursor::new(data), &KEY, "ctr");
+    assert!(matches!(result, Err(CryptoCoreError::AuthenticationFailed(_))));
+
+    let result = ArchiveReader::open(Cursor::new(sample_archive()?), &[0x24; 16], "ctr");
+    assert!(matches!(result, Err(CryptoCoreError::AuthenticationFailed(_))));
+    Ok(())
+}
+
+fn large_archive(contents: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut archive = ArchiveWriter::new(Vec::new(), &KEY, "cbc")?;
+    let entry = ArchiveEntry {
+        path: "big.bin".to_string(),
+        mode: 0o644,
+        modified: UNIX_EPOCH,
+        kind: EntryKind::File { size: contents.len() as u64 },
+    };
+    archive.add_file(&entry, &mut &contents[..])?;
+    Ok(archive.finish()?)
+}
+
+fn read_large(data: &[u8]) -> cryptocore::Result<Vec<u8>> {
+    // A plain slice: reading needs no seeking
+    let mut archive = ArchiveReader::open(data, &KEY, "cbc")?;
+    archive.next_entry()?;
+    let mut contents = Vec::new();
+    archive.read_contents(&mut contents)?;
+    assert!(archive.next_entry()?.is_none());
+    Ok(contents)
+}
+
+#[test]
+fn test_archive_segments_are_checked_in_one_pass() -> Result<(), Box<dyn std::error::Error>> {
+    let contents: Vec<u8> = (0..3 * SEGMENT_SIZE as u32).map(|i| (i % 251) as u8).collect();
+    let data = large_archive(&contents)?;
+    assert_eq!(read_large(&data)?, contents);
+
+    // 39 bytes of framing pad to exactly two full segments of ciphertext
+    let exact = &contents[..2 * SEGMENT_SIZE - 40];
+    assert_eq!(read_large(&large_archive(exact)?)?, exact);
+
+    // A change in a later segment is caught before that segment is used
+    let mut tampered = data.clone();
+    let end = tampered.len() - 100;
+    tampered[end] ^= 1;
+    assert!(matches!(read_large(&tampered), Err(CryptoCoreError::AuthenticationFailed(_))));
+
+    // Dropping the last segment (the 48 bytes of ciphertext past the three
+    // full ones, and its tag) leaves a segment that was not sealed as last
+    let cut = &data[..data.len() - 48 - 32];
+    assert!(matches!(read_large(cut), Err(CryptoCoreError::AuthenticationFailed(_))));
+    Ok(())
+}
+
+#[test]
+fn test_cli_pack_unpack() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let source = dir.path().join("source");
+    let archive = dir.path().join("backup.cca");
+    let restored = dir.path().join("restored");
+    let key = "000102030405060708090a0b0c0d0e0f";
+
+    fs::create_dir_all(source.join("nested/empty"))?;
+    fs::write(source.join("nested/payroll.csv"), "name,salary\n")?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(source.join("nested/payroll.csv"), fs::Permissions::from_mode(0o4750))?;
+    }
+
+    Command::cargo_bin("cryptocore")?
+        .args(["pack", "--key", key])
+        .arg(&source)
+        .arg("-o").arg(&archive)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Packed 3 entries"));
+    assert!(!fs::read(&archive)?.windows(7).any(|window| window == b"payroll"));
+    // Written through a temporary file that is renamed into place
+    assert_eq!(fs::read_dir(dir.path())?.count(), 2);
+
+    Command::cargo_bin("cryptocore")?
+        .args(["unpack", "--key", key])
+        .arg(&archive)
+        .arg("-o").arg(&restored)
+        .assert()
+        .success();
+
+    assert_eq!(fs::read_to_string(restored.join("nested/payroll.csv"))?, "name,salary\n");
+    assert!(restored.join("nested/empty").is_dir());
+    #[cfg(unix)]
+    {
+        // Only the permission bits come back, never set-user-ID
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(restored.join("nested/payroll.csv"))?.permissions().mode();
+        assert_eq!(mode & 0o7777, 0o750);
+    }
+
+    // Files that already exist are not overwritten
+    fs::write(restored.join("nested/payroll.csv"), "edited")?;
+    Command::cargo_bin("cryptocore")?
+        .args(["unpack", "--key", key])
+        .arg(&archive)
+        .arg("-o").arg(&restored)
+        .assert()
+        .failure();
+    assert_eq!(fs::read_to_string(restored.join("nested/payroll.csv"))?, "edited");
+
+    // Nor is anything written through a link already in the output directory
+    #[cfg(unix)]
+    {
+        let outside = dir.path().join("outside");
+        let planted = dir.path().join("planted");
+        fs::create_dir_all(&outside)?;
+        fs::create_dir_all(&planted)?;
+        std::os::unix::fs::symlink(&outside, planted.join("nested"))?;
+        Command::cargo_bin("cryptocore")?
+            .args(["unpack", "--key", key])
+            .arg(&archive)
+            .arg("-o").arg(&planted)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("existing link"));
+        assert_eq!(fs::read_dir(&outside)?.count(), 0);
+    }
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use assert_cmd::Command;
+use cryptocore::core::io::armor;
+use cryptocore::CryptoCoreError;
+use predicates::prelude::*;
+use std::fs;
+
+#[test]
+fn test_armor_checksum_matches_openpgp() -> Result<(), Box<dyn std::error::Error>> {
+    // Same CRC-24 line as `gpg --enarmor` gives for this input
+    let armored = armor::armor(b"hello world");
+    assert_eq!(
+        armored,
+        "-----BEGIN CRYPTOCORE MESSAGE-----\naGVsbG8gd29ybGQ=\n=sDy3\n-----END CRYPTOCORE MESSAGE-----\n"
+    );
+    // OpenPGP armor ends its empty header section with a blank line
+    assert!(armor::armor_as(armor::PGP_LABEL, b"hello world").starts_with("-----BEGIN PGP MESSAGE-----\n\naGVs"));
+
+    // Leading whitespace, CRLF line endings and armor headers are tolerated
+    let pasted = format!("\n  {}", armored.replacen("-----\n", "-----\nComment: pasted\n\n", 1)).replace('\n', "\r\n");
+    assert!(armor::is_armored(pasted.as_bytes()));
+    assert_eq!(armor::dearmor(pasted.as_bytes())?, b"hello world");
+    Ok(())
+}
+
+#[test]
+fn test_armor_wraps_lines_and_detects_damage() -> Result<(), Box<dyn std::error::Error>> {
+    let data: Vec<u8> = (0..=255).collect();
+    let armored = armor::armor(&data);
+    assert!(armored.lines().all(|line| line.len() <= 64 || line.starts_with("-----")));
+    assert_eq!(armor::dearmor(armored.as_bytes())?, data);
+
+    let damaged = armored.replacen("AAEC", "AAED", 1);
+    assert!(matches!(armor::dearmor(damaged.as_bytes()), Err(CryptoCoreError::IntegrityCheckFailed(_))));
+    Ok(())
+}
+
+#[test]
+fn test_cli_armored_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let plain = dir.path().join("plain.txt");
+    let cipher = dir.path().join("plain.asc");
+    let decrypted = dir.path().join("plain.dec");
+    let key = "000102030405060708090a0b0c0d0e0f";
+    fs::write(&plain, "paste me into a ticket")?;
+
+    Command::cargo_bin("cryptocore")?
+        .args(["enc", "--mode", "gcm", "--armor", "--derive-info", "tickets", "--key", key])
+        .arg("-i").arg(&plain)
+        .arg("-o").arg(&cipher)
+        .assert()
+        .success();
+    let armored = fs::read_to_string(&cipher)?;
+    assert!(armored.starts_with("-----BEGIN CRYPTOCORE MESSAGE-----\n"));
+
+    // Armor is detected without a flag
+    Command::cargo_bin("cryptocore")?
+        .args(["dec", "--mode", "gcm", "--key", key])
+        .arg("-i").arg(&cipher)
+        .arg("-o").arg(&decrypted)
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(&decrypted)?, "paste me into a ticket");
+
+    fs::write(&cipher, format!("\n\n{}", armored.replacen("-----\n", "-----\nVersion: 1\n\n", 1)))?;
+    Command::cargo_bin("cryptocore")?
+        .args(["dec", "--mode", "gcm", "--key", key])
+        .arg("-i").arg(&cipher)
+        .arg("-o").arg(&decrypted)
+        .assert()
+        .success();
+
+    Command::cargo_bin("cryptocore")?
+        .args(["reencrypt", "--old-key", key, "--new-key", key, "--old-mode", "gcm"])
+        .arg(&cipher)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is armored"));
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use cryptocore::core::crypto::modes::ccm::CcmMode;
+use cryptocore::core::crypto::modes::eax::EaxMode;
+use cryptocore::CryptoCoreError;
+
+// RFC 3610, section 8, packet vectors #1, #7 and #13:
+// (key, tag size, nonce, header, payload, encrypted payload and tag)
+const CCM_VECTORS: [(&str, usize, &str, &str, &str, &str); 3] = [
+    (
+        "c0c1c2c3c4c5c6c7c8c9cacbcccdcecf",
+        8,
+        "00000003020100a0a1a2a3a4a5",
+        "0001020304050607",
+        "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e",
+        "588c979a61c663d2f066d0c2c0f989806d5f6b61dac38417e8d12cfdf926e0",
+    ),
+    (
+        "c0c1c2c3c4c5c6c7c8c9cacbcccdcecf",
+        10,
+        "00000009080706a0a1a2a3a4a5",
+        "0001020304050607",
+        "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e",
+        "0135d1b2c95f41d5d1d4fec185d166b8094e999dfed96c048c56602c97acbb7490",
+    ),
+    (
+        "d7828d13b2b0bdc325a76236df93cc6b",
+        8,
+        "00412b4ea9cdbe3c9696766cfa",
+        "0be1a88bace018b1",
+        "08e8cf97d820ea258460e96ad9cf5289054d895ceac47c",
+        "4cb97f86a2a4689a877947ab8091ef5386a6ffbdd080f8e78cf7cb0cddd7b3",
+    ),
+];
+
+// Test vectors from the EAX paper, appendix: (message, key, nonce, header, ciphertext and tag)
+const EAX_VECTORS: [(&str, &str, &str, &str, &str); 5] = [
+    (
+        "",
+        "233952dee4d5ed5f9b9c6d6ff80ff478",
+        "62ec67f9c3a4a407fcb2a8c49031a8b3",
+        "6bfb914fd07eae6b",
+        "e037830e8389f27b025a2d6527e79d01",
+    ),
+    (
+        "f7fb",
+        "91945d3f4dcbee0bf45ef52255f095a4",
+        "becaf043b0a23d843194ba972c66debd",
+        "fa3bfd4806eb53fa",
+        "19dd5c4c9331049d0bdab0277408f67967e5",
+    ),
+    (
+        "1a47cb4933",
+        "01f74ad64077f2e704c0f60ada3dd523",
+        "70c3db4f0d26368400a10ed05d2bff5e",
+        "234a3463c1264ac6",
+        "d851d5bae03a59f238a23e39199dc9266626c40f80",
+    ),
+    (
+        "481c9e39b1",
+        "d07cf6cbb7f313bdde66b727afd3c5e8",
+        "8408dfff3c1a2b1292dc199e46b7d617",
+        "33cce2eabff5a79d",
+        "632a9d131ad4c168a4225d8e1ff755939974a7bede",
+    ),
+    (
+        "8b0a79306c9ce7ed99dae4f87f8dd61636",
+        "7c77d6e813bed5ac98baa417477a2e7d",
+        "1a8c98dcd73d38393b2bf1569deefc19",
+        "65d2017990d62528",
+        "02083e3979da014812f59f11d52630da30137327d10649b0aa6e1c181db617d7f2",
+    ),
+];
+
+#[test]
+fn test_ccm_rfc3610_vectors() -> Result<(), Box<dyn std::error::Error>> {
+    for (key, tag_size, nonce, header, payload, expected) in CCM_VECTORS {
+        let ccm = CcmMode::new(&hex::decode(key)?, tag_size, 2)?;
+        assert_eq!(ccm.nonce_size(), 13);
+        let (nonce, header, payload) = (hex::decode(nonce)?, hex::decode(header)?, hex::decode(payload)?);
+
+        let output = ccm.encrypt(&nonce, &header, &payload)?;
+        assert_eq!(hex::encode(&output), expected);
+        assert_eq!(ccm.decrypt(&nonce, &header, &output)?, payload);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_ccm_other_parameters() -> Result<(), Box<dyn std::error::Error>> {
+    // Generated with Python's cryptography AESCCM: AES-256, 16-byte tag, L = 8
+    let key: Vec<u8> = (0..32).collect();
+    let nonce: Vec<u8> = (0..7).collect();
+    let plaintext: Vec<u8> = (0..40).collect();
+    let ccm = CcmMode::new(&key, 16, 8)?;
+    let output = ccm.encrypt(&nonce, &[b'x'; 300], &plaintext)?;
+    assert_eq!(
+        hex::encode(&output),
+        "9ff08efc978590fa264a27f52ec35dab4088b916b7a223c3904601900d4951b8\
+         6df15681b4d2a8fa6b281fd5f1f07f3a0a3053343f08b980"
+    );
+    assert_eq!(ccm.decrypt(&nonce, &[b'x'; 300], &output)?, plaintext);
+
+    // 4-byte tag, L = 3, nothing to encrypt
+    let ccm = CcmMode::new(&(0..16).collect::<Vec<u8>>(), 4, 3)?;
+    assert_eq!(hex::encode(ccm.encrypt(&(0..12).collect::<Vec<u8>>(), b"", b"")?), "e9f64ce1");
+
+    assert!(matches!(CcmMode::new(&key, 5, 2), Err(CryptoCoreError::InvalidArgument(_))));
+    assert!(matches!(CcmMode::new(&key, 8, 1), Err(CryptoCoreError::InvalidArgument(_))));
+    assert!(ccm.encrypt(&[0u8; 13], b"", b"").is_err());
+    assert!(CcmMode::new(&key, 8, 2)?.encrypt(&[0u8; 13], b"", &vec![0u8; 1 << 16]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_ccm_rejects_tampering() -> Result<(), Box<dyn std::error::Error>> {
+    let (key, tag_size, nonce, header, _, output) = CCM_VECTORS[0];
+    let ccm = CcmMode::new(&hex::decode(key)?, tag_size, 2)?;
+    let (nonce, header, output) = (hex::decode(nonce)?, hex::decode(header)?, hex::decode(output)?);
+
+    let mut tampered = output.clone();
+    tampered[3] ^= 0x80;
+    let result = ccm.decrypt(&nonce, &header, &tampered);
+    assert!(matches!(result, Err(CryptoCoreError::AuthenticationFailed(_))));
+
+    let result = ccm.decrypt(&nonce, b"other header", &output);
+    assert!(matches!(result, Err(CryptoCoreError::AuthenticationFailed(_))));
+
+    assert!(ccm.decrypt(&nonce, &header, &output[..7]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_eax_paper_vectors() -> Result<(), Box<dyn std::error::Error>> {
+    for (message, key, nonce, header, expected) in EAX_VECTORS {
+        let eax = EaxMode::new(&hex::decode(key)?, 16)?;
+        let (message, nonce, header) = (hex::decode(message)?, hex::decode(nonce)?, hex::decode(header)?);
+
+        let output = eax.encrypt(&nonce, &header, &message)?;
+        assert_eq!(hex::encode(&output), expected);
+        assert_eq!(eax.decrypt(&nonce, &header, &output)?, message);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_eax_truncated_tag_and_tampering() -> Result<(), Box<dyn std::error::Error>> {
+    let (message, key, nonce, header, expected) = EAX_VECTORS[4];
+    let (message, nonce, header) = (hex::decode(message)?, hex::decode(nonce)?, hex::decode(header)?);
+
+    // A truncated tag is a prefix of the full one
+    let eax = EaxMode::new(&hex::decode(key)?, 8)?;
+    let output = eax.encrypt(&nonce, &header, &message)?;
+    assert_eq!(hex::encode(&output), expected[..(message.len() + 8) * 2]);
+    assert_eq!(eax.decrypt(&nonce, &header, &output)?, message);
+
+    let mut tampered = output.clone();
+    tampered[0] ^= 1;
+    let result = eax.decrypt(&nonce, &header, &tampered);
+    assert!(matches!(result, Err(CryptoCoreError::AuthenticationFailed(_))));
+
+    let result = eax.decrypt(&nonce[..12], &header, &output);
+    assert!(matches!(result, Err(CryptoCoreError::AuthenticationFailed(_))));
+
+    assert!(matches!(EaxMode::new(&hex::decode(key)?, 0), Err(CryptoCoreError::InvalidArgument(_))));
+    Ok(())
+}
@@ -0,0 +1,215 @@
+use assert_cmd::Command;
+use cryptocore::core::crypto::drbg::{CtrDrbg, MAX_REQUEST_SIZE};
+use cryptocore::CryptoCoreError;
+use predicates::prelude::*;
+
+// Expected outputs come from OpenSSL 3's CTR-DRBG (with derivation
+// function), driven through a TEST-RAND parent that supplies the entropy.
+
+fn instantiate(key_size: usize, entropy: &str, nonce: &str, personalization: &str) -> CtrDrbg {
+    CtrDrbg::instantiate(
+        key_size,
+        &hex::decode(entropy).unwrap(),
+        &hex::decode(nonce).unwrap(),
+        &hex::decode(personalization).unwrap(),
+    )
+    .unwrap()
+}
+
+fn generate(drbg: &mut CtrDrbg, length: usize, additional: &str) -> String {
+    let mut output = vec![0u8; length];
+    drbg.generate(&mut output, &hex::decode(additional).unwrap()).unwrap();
+    hex::encode(output)
+}
+
+#[test]
+fn test_ctr_drbg_aes256() {
+    let mut drbg = instantiate(
+        32,
+        "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+        "2021222324252627",
+        "",
+    );
+    assert_eq!(
+        generate(&mut drbg, 64, ""),
+        "bec219522095a8e12954c60ff9a83d4b4eaecc0eccde7e2e6e0491ddb8f509fa\
+         5989dcf396c78c2e9dac11708450c4b1b52c859f7c64593fc089855186e626b3"
+    );
+    assert_eq!(
+        generate(&mut drbg, 64, ""),
+        "b42ffe258bff4e5ef6c8b617a57fe25fdacd255f542d1a3e7e759740533c8bb4\
+         99bf8b516d4f183f1e53364a6ddaf8f24e218cd1e4476db969e7531c882f10ee"
+    );
+}
+
+#[test]
+fn test_ctr_drbg_personalization_and_additional_input() {
+    let mut drbg = instantiate(16, "8a7b6c5d4e3f2a1b0c9d8e7f6a5b4c3d", "0f1e2d3c4b5a6978", "637279707463");
+    assert_eq!(
+        generate(&mut drbg, 32, "0102030405"),
+        "1501f4918db9b5627b4367e0c7536ce54b27ad8b5f0b936dec0726eb86dd5d87"
+    );
+    assert_eq!(
+        generate(&mut drbg, 48, "a0a1a2a3a4a5a6a7a8a9aaabacadaeafb0b1b2b3b4b5b6b7b8b9babbbcbdbebf"),
+        "388061593afc1095de677ad99a2374b22a74b03b683563556e70df02487521791edc7d96a6280a97a40491c3b69cc656"
+    );
+}
+
+#[test]
+fn test_ctr_drbg_reseed() {
+    let mut drbg = instantiate(
+        24,
+        "f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff000102030405060708",
+        "55aa55aa55aa55aa55aa55aa",
+        "",
+    );
+    assert_eq!(generate(&mut drbg, 16, ""), "d18daeafcaf1db95b6bf8c2159ebdf67");
+
+    let entropy = hex::decode("1111111111111111111111111111111111111111111111111111").unwrap();
+    drbg.reseed_with(&entropy, &[0xab, 0xcd, 0xef]).unwrap();
+    assert_eq!(
+        generate(&mut drbg, 40, "99"),
+        "fb2e3d188f143dd32563d2ca124f6d6a11fa1e51de38ce9ad79eeb9af989bec3327c170a08de8307"
+    );
+
+    // A seeded generator has no entropy source of its own
+    assert!(matches!(drbg.reseed(&[]), Err(CryptoCoreError::Crypto(_))));
+    assert!(drbg.reseed_with(&[0u8; 23], &[]).is_err());
+}
+
+#[test]
+fn test_ctr_drbg_prediction_resistance() {
+    // Prediction resistance reseeds before each request, taking the
+    // additional input into the reseed
+    let mut drbg = instantiate(
+        32,
+        "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+        "2021222324252627",
+        "7072",
+    );
+    drbg.reseed_with(&[0xfe; 32], &[0x0a, 0x0b]).unwrap();
+    assert_eq!(
+        generate(&mut drbg, 32, ""),
+        "c35ceaa04b12b7cd9e4925823bc798a71d504cada028686c343cd95e1c52d0a4"
+    );
+    drbg.reseed_with(&[0x01; 32], &[]).unwrap();
+    assert_eq!(
+        generate(&mut drbg, 32, ""),
+        "c7fadb3cb5d839fb84628c7ecd06d64f462ceda6c40e0650f71b19607fb8a821"
+    );
+
+    assert!(drbg.set_prediction_resistance(true).is_err());
+    let mut system = CtrDrbg::new(32, b"test").unwrap();
+    system.set_prediction_resistance(true).unwrap();
+    let (mut first, mut second) = ([0u8; 32], [0u8; 32]);
+    system.generate(&mut first, b"extra").unwrap();
+    system.generate(&mut second, &[]).unwrap();
+    assert_ne!(first, second);
+}
+
+#[test]
+fn test_ctr_drbg_request_limits() {
+    let mut drbg = instantiate(16, "00112233445566778899aabbccddeeff", "", "");
+    let mut large = vec![0u8; MAX_REQUEST_SIZE + 1];
+    assert!(matches!(drbg.generate(&mut large, &[]), Err(CryptoCoreError::InvalidArgument(_))));
+
+    // fill splits long outputs into separate requests
+    let mut expected = instantiate(16, "00112233445566778899aabbccddeeff", "", "");
+    let mut first = vec![0u8; MAX_REQUEST_SIZE];
+    let mut rest = [0u8; 1];
+    expected.generate(&mut first, &[]).unwrap();
+    expected.generate(&mut rest, &[]).unwrap();
+    drbg.fill(&mut large).unwrap();
+    assert_eq!(large, [first, rest.to_vec()].concat());
+
+    assert!(CtrDrbg::instantiate(16, &[0u8; 15], &[], &[]).is_err());
+    assert!(matches!(CtrDrbg::instantiate(20, &[0u8; 20], &[], &[]), Err(CryptoCoreError::InvalidKey(_))));
+}
+
+#[test]
+fn test_cli_rand() -> Result<(), Box<dyn std::error::Error>> {
+    let seed = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+    let seeded = |args: &[&str]| -> Result<String, Box<dyn std::error::Error>> {
+        let output = Command::cargo_bin("cryptocore")?
+            .args(["--rng", "ctr-drbg", "--rng-seed", seed])
+            .args(args)
+            .output()?;
+        assert!(output.status.success());
+        Ok(String::from_utf8(output.stdout)?)
+    };
+
+    // The seed is the entropy input, with an empty nonce and personalization
+    let mut drbg = CtrDrbg::instantiate(32, &hex::decode(seed)?, &[], &[])?;
+    let mut expected = [0u8; 24];
+    drbg.fill(&mut expected)?;
+    assert_eq!(seeded(&["rand", "24"])?, format!("{}\n", hex::encode(expected)));
+    assert_eq!(seeded(&["rand", "24"])?, seeded(&["rand", "24"])?);
+
+    // The option is for tests: it is left out of --help and warns whenever used
+    Command::cargo_bin("cryptocore")?
+        .args(["--rng-seed", seed, "rand", "8"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Warning: --rng-seed"));
+    Command::cargo_bin("cryptocore")?
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--rng-seed").not());
+    assert_ne!(seeded(&["rand", "24"])?, seeded(&["--rng-personalization", "run 2", "rand", "24"])?);
+
+    // A seeded generator also drives key generation
+    assert_eq!(seeded(&["keygen", "--length", "24"])?, seeded(&["rand", "24"])?);
+
+    Command::cargo_bin("cryptocore")?
+        .args(["rand", "--base64", "12"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match("^[A-Za-z0-9+/]{16}\n$")?);
+
+    Command::cargo_bin("cryptocore")?
+        .args(["rand", "16", "--rng", "ctr-drbg", "--prediction-resistance"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match("^[0-9a-f]{32}\n$")?);
+
+    Command::cargo_bin("cryptocore")?
+        .args(["rand", "16", "--rng-seed", seed, "--prediction-resistance"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Prediction resistance"));
+    Ok(())
+}
+
+#[test]
+fn test_cli_seeded_rng_makes_encryption_reproducible() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let plain = dir.path().join("plain.txt");
+    std::fs::write(&plain, "same IV every run")?;
+    let key = "000102030405060708090a0b0c0d0e0f";
+    let seed = "ffeeddccbbaa99887766554433221100ffeeddccbbaa99887766554433221100";
+
+    let encrypt = |output: &str, rng: &[&str]| -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let output = dir.path().join(output);
+        Command::cargo_bin("cryptocore")?
+            .args(["enc", "--mode", "gcm", "--key", key])
+            .args(rng)
+            .arg("-i").arg(&plain)
+            .arg("-o").arg(&output)
+            .assert()
+            .success();
+        Ok(std::fs::read(output)?)
+    };
+
+    let first = encrypt("a.enc", &["--rng-seed", seed])?;
+    assert_eq!(first, encrypt("b.enc", &["--rng-seed", seed])?);
+    assert_ne!(first, encrypt("c.enc", &[])?);
+    assert_ne!(encrypt("d.enc", &["--rng", "ctr-drbg"])?, encrypt("e.enc", &["--rng", "ctr-drbg"])?);
+
+    Command::cargo_bin("cryptocore")?
+        .args(["rand", "8", "--rng", "system", "--rng-seed", seed])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--rng ctr-drbg"));
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use assert_cmd::Command;
+use cryptocore::core::crypto::fernet::Fernet;
+use cryptocore::CryptoCoreError;
+use predicates::prelude::*;
+use std::fs;
+
+// generate.json from the Fernet specification (github.com/fernet/spec)
+const SECRET: &str = "cw_0x689RpI-jtRR7oE8h_eQsKImvJapLeSbXpwF4e4=";
+const TOKEN: &str = "gAAAAAAdwJ6wAAECAwQFBgcICQoLDA0ODy021cpGVWKZ_eEwCGM4BLLF_5CV9dOPmrhuVUPgJobwOz7JcbmrR64jVmpU4IwqDA==";
+// 1985-10-26T01:20:00-07:00
+const NOW: u64 = 499_162_800;
+
+#[test]
+fn test_fernet_spec_vector() -> Result<(), Box<dyn std::error::Error>> {
+    let fernet = Fernet::from_base64(SECRET)?;
+    let iv: [u8; 16] = std::array::from_fn(|i| i as u8);
+
+    assert_eq!(fernet.encrypt_with(b"hello", NOW, &iv)?, TOKEN);
+    assert_eq!(fernet.decrypt_at_time(TOKEN, Some(60), NOW)?, b"hello");
+    Ok(())
+}
+
+#[test]
+fn test_fernet_rejects_expired_and_tampered_tokens() -> Result<(), Box<dyn std::error::Error>> {
+    let fernet = Fernet::from_base64(SECRET)?;
+
+    let expired = fernet.decrypt_at_time(TOKEN, Some(60), NOW + 61);
+    assert!(matches!(expired, Err(CryptoCoreError::AuthenticationFailed(_))));
+    // Without a TTL the age does not matter
+    assert_eq!(fernet.decrypt_at_time(TOKEN, None, NOW + 1_000_000)?, b"hello");
+
+    let future = fernet.decrypt_at_time(TOKEN, Some(60), NOW - 120);
+    assert!(matches!(future, Err(CryptoCoreError::AuthenticationFailed(_))));
+    // Nor does clock skew
+    assert_eq!(fernet.decrypt_at_time(TOKEN, None, NOW - 120)?, b"hello");
+
+    let mut tampered = TOKEN.to_string();
+    tampered.replace_range(40..41, "A");
+    let result = fernet.decrypt_at_time(&tampered, None, NOW);
+    assert!(matches!(result, Err(CryptoCoreError::AuthenticationFailed(_))));
+
+    let other_key = Fernet::from_base64(&Fernet::generate_key()?)?;
+    assert!(other_key.decrypt_at_time(TOKEN, None, NOW).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_cli_fernet_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let plain = dir.path().join("value.txt");
+    let token = dir.path().join("value.token");
+    fs::write(&plain, "shared with the python services")?;
+
+    Command::cargo_bin("cryptocore")?
+        .args(["fernet", "encrypt", "--key", SECRET])
+        .arg(&plain)
+        .arg("-o").arg(&token)
+        .assert()
+        .success();
+    assert!(fs::read_to_string(&token)?.starts_with("gAAAAA"));
+
+    Command::cargo_bin("cryptocore")?
+        .args(["fernet", "decrypt", "--key", SECRET, "--ttl", "3600"])
+        .arg(&token)
+        .assert()
+        .success()
+        .stdout("shared with the python services");
+
+    Command::cargo_bin("cryptocore")?
+        .args(["fernet", "decrypt", "--key", SECRET, "--ttl", "60", "-"])
+        .write_stdin(TOKEN)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("token has expired"));
+    Ok(())
+}
@@ -0,0 +1,196 @@
+use assert_cmd::Command;
+use cryptocore::core::crypto::fpe::{Alphabet, Ff1, Ff3, FpeAlgorithm, FpeCipher};
+use cryptocore::CryptoCoreError;
+use predicates::prelude::*;
+use std::fs;
+
+const FF1_KEY_128: &str = "2b7e151628aed2a6abf7158809cf4f3c";
+const FF1_KEY_192: &str = "2b7e151628aed2a6abf7158809cf4f3cef4359d8d580aa4f";
+const FF1_KEY_256: &str = "2b7e151628aed2a6abf7158809cf4f3cef4359d8d580aa4f7f036d6f04fc6a94";
+
+// NIST SP 800-38G FF1 samples: (key, tweak, radix, plaintext, ciphertext)
+const FF1_SAMPLES: [(&str, &str, u32, &str, &str); 9] = [
+    (FF1_KEY_128, "", 10, "0123456789", "2433477484"),
+    (FF1_KEY_128, "39383736353433323130", 10, "0123456789", "6124200773"),
+    (FF1_KEY_128, "3737373770717273373737", 36, "0123456789abcdefghi", "a9tv40mll9kdu509eum"),
+    (FF1_KEY_192, "", 10, "0123456789", "2830668132"),
+    (FF1_KEY_192, "39383736353433323130", 10, "0123456789", "2496655549"),
+    (FF1_KEY_192, "3737373770717273373737", 36, "0123456789abcdefghi", "xbj3kv35jrawxv32ysr"),
+    (FF1_KEY_256, "", 10, "0123456789", "6657667009"),
+    (FF1_KEY_256, "39383736353433323130", 10, "0123456789", "1001623463"),
+    (FF1_KEY_256, "3737373770717273373737", 36, "0123456789abcdefghi", "xs8a0azh2avyalyzuwd"),
+];
+
+const FF3_KEY: &str = "ef4359d8d580aa4f7f036d6f04fc6a94";
+
+// NIST FF3 samples with 64-bit tweaks, which only decrypt: (tweak, radix, plaintext, ciphertext)
+const FF3_SAMPLES: [(&str, u32, &str, &str); 5] = [
+    ("d8e7920afa330a73", 10, "890121234567890000", "750918814058654607"),
+    ("9a768a92f60e12d8", 10, "890121234567890000", "018989839189395384"),
+    ("d8e7920afa330a73", 10, "89012123456789000000789000000", "48598367162252569629397416226"),
+    ("0000000000000000", 10, "89012123456789000000789000000", "34695224821734535122613701434"),
+    ("9a768a92f60e12d8", 26, "0123456789abcdefghi", "g2pk40i992fn20cjakb"),
+];
+
+#[test]
+fn test_ff1_nist_samples() -> Result<(), Box<dyn std::error::Error>> {
+    for (key, tweak, radix, plaintext, expected) in FF1_SAMPLES {
+        let cipher = FpeCipher::new(FpeAlgorithm::Ff1, &hex::decode(key)?, Alphabet::for_radix(radix)?)?;
+        let tweak = hex::decode(tweak)?;
+
+        let ciphertext = cipher.encrypt(&tweak, plaintext)?;
+        assert_eq!(ciphertext, expected, "key {} radix {}", key, radix);
+        assert_eq!(cipher.decrypt(&tweak, &ciphertext)?, plaintext);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_ff3_nist_samples() -> Result<(), Box<dyn std::error::Error>> {
+    for (tweak, radix, plaintext, ciphertext) in FF3_SAMPLES {
+        let ff3 = Ff3::new(&hex::decode(FF3_KEY)?, radix)?;
+        let alphabet = Alphabet::for_radix(radix)?;
+        let tweak = hex::decode(tweak)?;
+        let numerals = alphabet.to_numerals(ciphertext)?;
+
+        assert_eq!(ff3.decrypt_legacy(&tweak, &numerals)?, alphabet.to_numerals(plaintext)?, "radix {}", radix);
+        // 64-bit tweaks are refused everywhere else
+        assert!(matches!(ff3.encrypt(&tweak, &numerals), Err(CryptoCoreError::InvalidArgument(_))));
+        assert!(matches!(ff3.decrypt(&tweak, &numerals), Err(CryptoCoreError::InvalidArgument(_))));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_ff3_1_56_bit_tweak() -> Result<(), Box<dyn std::error::Error>> {
+    // Sample from the mysto/python-fpe FF3-1 test suite
+    let key = hex::decode("2de79d232df5585d68ce47882ae256d6")?;
+    let cipher = FpeCipher::new(FpeAlgorithm::Ff3_1, &key, Alphabet::digits())?;
+    let tweak = hex::decode("cbd09280979564")?;
+
+    assert_eq!(cipher.encrypt(&tweak, "3992520240")?, "8901801106");
+    assert_eq!(cipher.decrypt(&tweak, "8901801106")?, "3992520240");
+    Ok(())
+}
+
+#[test]
+fn test_ff3_1_tweak_split() -> Result<(), Box<dyn std::error::Error>> {
+    let key = hex::decode(FF3_KEY)?;
+    let ff3 = Ff3::new(&key, 10)?;
+    let plaintext = [8, 9, 0, 1, 2, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 0, 0, 0];
+
+    // The 56-bit tweak 0xABCDEF 1 234567 maps to T_L = ABCDEF10, T_R = 23456710
+    let ff3_1 = ff3.encrypt(&hex::decode("abcdef12345678")?, &plaintext)?;
+    assert_eq!(ff3.decrypt_legacy(&hex::decode("abcdef1034567820")?, &ff3_1)?, plaintext);
+    assert_eq!(ff3.decrypt(&hex::decode("abcdef12345678")?, &ff3_1)?, plaintext);
+
+    assert!(matches!(ff3.encrypt(&[0u8; 6], &plaintext), Err(CryptoCoreError::InvalidArgument(_))));
+    assert!(matches!(ff3.decrypt_legacy(&[0u8; 7], &plaintext), Err(CryptoCoreError::InvalidArgument(_))));
+    Ok(())
+}
+
+#[test]
+fn test_length_and_radix_limits() -> Result<(), Box<dyn std::error::Error>> {
+    let key = hex::decode(FF1_KEY_128)?;
+    let ff1 = Ff1::new(&key, 10)?;
+    // 10^6 is the smallest domain allowed
+    assert!(ff1.encrypt(&[], &[1, 2, 3, 4, 5]).is_err());
+    assert_eq!(ff1.decrypt(&[], &ff1.encrypt(&[], &[1, 2, 3, 4, 5, 6])?)?, [1, 2, 3, 4, 5, 6]);
+    assert!(ff1.encrypt(&[], &[1, 2, 3, 4, 5, 10]).is_err());
+
+    // Long inputs go through the same Feistel rounds
+    let long: Vec<u16> = (0..200).map(|i| (i * 7 % 10) as u16).collect();
+    let ciphertext = ff1.encrypt(b"tweak", &long)?;
+    assert_eq!(ciphertext.len(), long.len());
+    assert_eq!(ff1.decrypt(b"tweak", &ciphertext)?, long);
+
+    // FF3-1 halves must fit in 96 bits: 28 decimal digits each
+    let ff3 = Ff3::new(&key, 10)?;
+    assert_eq!(ff3.max_length(), 56);
+    assert!(ff3.encrypt(&[0u8; 7], &[1u16; 57]).is_err());
+    assert_eq!(Ff3::new(&key, 2)?.max_length(), 192);
+
+    assert!(Ff1::new(&key, 1).is_err());
+    assert!(Ff1::new(&key, 65537).is_err());
+    assert!(Ff1::new(&key[..15], 10).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_custom_alphabet() -> Result<(), Box<dyn std::error::Error>> {
+    let key = hex::decode(FF1_KEY_256)?;
+    let alphabet = Alphabet::new("ABCDEFGHJKLMNPQRSTUVWXYZ23456789")?;
+    assert_eq!(alphabet.radix(), 32);
+    let cipher = FpeCipher::new(FpeAlgorithm::Ff1, &key, alphabet)?;
+
+    let ciphertext = cipher.encrypt(b"licence", "AB23CD45")?;
+    assert_eq!(ciphertext.len(), 8);
+    assert!(ciphertext.chars().all(|c| "ABCDEFGHJKLMNPQRSTUVWXYZ23456789".contains(c)));
+    assert_eq!(cipher.decrypt(b"licence", &ciphertext)?, "AB23CD45");
+
+    // Same output as the numeral interface in radix 32
+    let numerals = cipher.alphabet().to_numerals("AB23CD45")?;
+    let direct = Ff1::new(&key, 32)?.encrypt(b"licence", &numerals)?;
+    assert_eq!(cipher.alphabet().to_text(&direct), ciphertext);
+
+    assert!(matches!(cipher.encrypt(b"licence", "AB10CD45"), Err(CryptoCoreError::InvalidArgument(_))));
+    assert!(Alphabet::new("0123456789012").is_err());
+    assert!(Alphabet::for_radix(37).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_cli_fpe_csv_columns() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let input = dir.path().join("customers.csv");
+    let tokenized = dir.path().join("tokenized.csv");
+    let restored = dir.path().join("restored.csv");
+    let key = FF1_KEY_128;
+    let csv = "name,card,ssn\n\
+               \"Doe, Jane\",4111111111111111,123456789\n\
+               \"Smith \"\"Bob\"\"\",5500000000000004,\n";
+    fs::write(&input, csv)?;
+
+    Command::cargo_bin("cryptocore")?
+        .args(["fpe", "encrypt", "--key", key, "--columns", "card,3"])
+        .arg(&input)
+        .arg("-o").arg(&tokenized)
+        .assert()
+        .success();
+
+    let output = fs::read_to_string(&tokenized)?;
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines[0], "name,card,ssn");
+    assert!(lines[1].starts_with("\"Doe, Jane\","));
+    assert!(lines[2].starts_with("\"Smith \"\"Bob\"\"\","));
+    assert!(lines[2].ends_with(','), "empty cells stay empty");
+
+    // Digits stay digits and lengths stay the same
+    let cipher = FpeCipher::new(FpeAlgorithm::Ff1, &hex::decode(key)?, Alphabet::digits())?;
+    let fields: Vec<&str> = lines[1].rsplitn(3, ',').collect();
+    assert_eq!(fields[1], cipher.encrypt(&[], "4111111111111111")?);
+    assert_eq!(fields[0], cipher.encrypt(&[], "123456789")?);
+
+    Command::cargo_bin("cryptocore")?
+        .args(["fpe", "decrypt", "--key", key, "--columns", "card,ssn"])
+        .arg(&tokenized)
+        .arg("-o").arg(&restored)
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(&restored)?, csv);
+
+    Command::cargo_bin("cryptocore")?
+        .args(["fpe", "encrypt", "--key", key, "--columns", "name"])
+        .arg(&input)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("row 2"));
+
+    Command::cargo_bin("cryptocore")?
+        .args(["fpe", "encrypt", "--key", key, "--algorithm", "ff3-1", "--columns", "card"])
+        .arg(&input)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("tweak"));
+    Ok(())
+}
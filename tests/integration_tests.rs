@@ -1,4 +1,6 @@
 use assert_cmd::Command;
+use cryptocore::core::io::header::FileHeader;
+use cryptocore::CryptoCoreError;
 use predicates::prelude::*;
 use std::fs;
 
@@ -12,7 +14,7 @@ fn test_encrypt_decrypt_round_trip() -> Result<(), Box<dyn std::error::Error>> {
     
     // Encrypt
     let mut cmd = Command::cargo_bin("cryptocore")?;
-    cmd.args(&[
+    cmd.args([
         "--algorithm", "aes",
         "--mode", "ecb", 
         "--encrypt",
@@ -25,7 +27,7 @@ fn test_encrypt_decrypt_round_trip() -> Result<(), Box<dyn std::error::Error>> {
     
     // Decrypt  
     let mut cmd = Command::cargo_bin("cryptocore")?;
-    cmd.args(&[
+    cmd.args([
         "--algorithm", "aes",
         "--mode", "ecb",
         "--decrypt", 
@@ -51,7 +53,7 @@ fn test_encrypt_decrypt_round_trip() -> Result<(), Box<dyn std::error::Error>> {
 #[test]
 fn test_missing_operation() -> Result<(), Box<dyn std::error::Error>> {
     let mut cmd = Command::cargo_bin("cryptocore")?;
-    cmd.args(&[
+    cmd.args([
         "--algorithm", "aes",
         "--mode", "ecb",
         "--key", "@00112233445566778899aabbccddeeff",
@@ -67,7 +69,7 @@ fn test_missing_operation() -> Result<(), Box<dyn std::error::Error>> {
 #[test]
 fn test_invalid_key_length() -> Result<(), Box<dyn std::error::Error>> {
     let mut cmd = Command::cargo_bin("cryptocore")?;
-    cmd.args(&[
+    cmd.args([
         "--algorithm", "aes", 
         "--mode", "ecb",
         "--encrypt",
@@ -79,4 +81,503 @@ fn test_invalid_key_length() -> Result<(), Box<dyn std::error::Error>> {
     .stderr(predicate::str::contains("Key must be 16 bytes"));
     
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_subcommand_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let plain = dir.path().join("plain.txt");
+    let cipher = dir.path().join("cipher.bin");
+    let decrypted = dir.path().join("decrypted.txt");
+    let key = "00112233445566778899aabbccddeeff";
+    fs::write(&plain, "Subcommand round trip")?;
+
+    Command::cargo_bin("cryptocore")?
+        .args(["enc", "--mode", "cbc", "--key", key])
+        .arg("--input").arg(&plain)
+        .arg("--output").arg(&cipher)
+        .assert()
+        .success();
+
+    Command::cargo_bin("cryptocore")?
+        .args(["dec", "--mode", "cbc", "--key", key])
+        .arg("-i").arg(&cipher)
+        .arg("-o").arg(&decrypted)
+        .assert()
+        .success();
+
+    assert_eq!(fs::read_to_string(&decrypted)?, "Subcommand round trip");
+    Ok(())
+}
+
+#[test]
+fn test_legacy_flags_warn_about_deprecation() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("cryptocore")?;
+    cmd.args([
+        "--algorithm", "aes",
+        "--mode", "ecb",
+        "--key", "@00112233445566778899aabbccddeeff",
+        "--input", "test.txt"
+    ])
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains("deprecated"));
+
+    Ok(())
+}
+
+#[test]
+fn test_keygen_prints_hex_key() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("cryptocore")?
+        .args(["keygen", "--length", "16"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match("^[0-9a-f]{32}\n$")?);
+
+    Ok(())
+}
+
+#[test]
+fn test_selftest_passes() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("cryptocore")?
+        .arg("selftest")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("All self-tests passed"));
+
+    Ok(())
+}
+
+#[test]
+fn test_dgst_manifest_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let first = dir.path().join("first.txt");
+    let second = dir.path().join("second.txt");
+    let manifest = dir.path().join("SHA256SUMS");
+    fs::write(&first, "abc")?;
+    fs::write(&second, "second file")?;
+
+    Command::cargo_bin("cryptocore")?
+        .arg("dgst")
+        .arg(&first)
+        .arg(&second)
+        .arg("--output").arg(&manifest)
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(&manifest)?;
+    assert!(content.starts_with(
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  "
+    ));
+
+    Command::cargo_bin("cryptocore")?
+        .arg("dgst")
+        .arg("--check").arg(&manifest)
+        .assert()
+        .success();
+
+    fs::write(&second, "tampered")?;
+    Command::cargo_bin("cryptocore")?
+        .arg("dgst")
+        .arg("--check").arg(&manifest)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("second.txt: FAILED"));
+
+    Ok(())
+}
+
+#[test]
+fn test_derive_info_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let plain = dir.path().join("tenant.txt");
+    let cipher = dir.path().join("tenant.enc");
+    let decrypted = dir.path().join("tenant.dec");
+    let master = "000102030405060708090a0b0c0d0e0f";
+    fs::write(&plain, "per-tenant data")?;
+
+    Command::cargo_bin("cryptocore")?
+        .args(["enc", "--mode", "cbc", "--key", master, "--derive-info", "tenant-42"])
+        .arg("-i").arg(&plain)
+        .arg("-o").arg(&cipher)
+        .assert()
+        .success();
+
+    Command::cargo_bin("cryptocore")?
+        .args(["inspect"])
+        .arg(&cipher)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("hkdf-sha256 (context: \"tenant-42\")"));
+
+    Command::cargo_bin("cryptocore")?
+        .args(["dec", "--mode", "cbc", "--key", master])
+        .arg("-i").arg(&cipher)
+        .arg("-o").arg(&decrypted)
+        .assert()
+        .success();
+
+    assert_eq!(fs::read_to_string(&decrypted)?, "per-tenant data");
+    Ok(())
+}
+
+#[test]
+fn test_kdf_command_rfc5869_vector() -> Result<(), Box<dyn std::error::Error>> {
+    // RFC 5869, test case 3 (no salt, empty info)
+    Command::cargo_bin("cryptocore")?
+        .args(["kdf", "--key", "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b", "--length", "42"])
+        .assert()
+        .success()
+        .stdout(
+            "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d\
+             9d201395faa4b61a96c8\n",
+        );
+
+    Ok(())
+}
+
+#[test]
+fn test_envelope_rewrap_keeps_payload() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let plain = dir.path().join("plain.txt");
+    let cipher = dir.path().join("plain.enc");
+    let decrypted = dir.path().join("plain.dec");
+    let old_key = "000102030405060708090a0b0c0d0e0f";
+    let new_key = "f0e0d0c0b0a090807060504030201000";
+    fs::write(&plain, "envelope encrypted payload")?;
+
+    Command::cargo_bin("cryptocore")?
+        .args(["enc", "--mode", "ctr", "--envelope", "--key", old_key])
+        .arg("-i").arg(&plain)
+        .arg("-o").arg(&cipher)
+        .assert()
+        .success();
+    let before = fs::read(&cipher)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&cipher, fs::Permissions::from_mode(0o640))?;
+    }
+
+    Command::cargo_bin("cryptocore")?
+        .args(["rewrap", "--old-key", old_key, "--new-key", new_key])
+        .arg(&cipher)
+        .assert()
+        .success();
+    let after = fs::read(&cipher)?;
+
+    // The file is swapped in whole, with its permissions and no leftovers
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(fs::metadata(&cipher)?.permissions().mode() & 0o777, 0o640);
+    }
+    assert_eq!(fs::read_dir(dir.path())?.count(), 2);
+
+    // Only the wrapped key in the header changes
+    assert_eq!(before.len(), after.len());
+    let payload_start = before.len() - "envelope encrypted payload".len();
+    assert_eq!(before[payload_start..], after[payload_start..]);
+    assert_ne!(before, after);
+
+    Command::cargo_bin("cryptocore")?
+        .args(["dec", "--mode", "ctr", "--key", old_key])
+        .arg("-i").arg(&cipher)
+        .arg("-o").arg(&decrypted)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Integrity check failed"));
+
+    Command::cargo_bin("cryptocore")?
+        .args(["dec", "--mode", "ctr", "--key", new_key])
+        .arg("-i").arg(&cipher)
+        .arg("-o").arg(&decrypted)
+        .assert()
+        .success();
+
+    assert_eq!(fs::read_to_string(&decrypted)?, "envelope encrypted payload");
+    Ok(())
+}
+
+#[test]
+fn test_malformed_headers_are_rejected() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let file = dir.path().join("malformed.enc");
+    let key = "000102030405060708090a0b0c0d0e0f";
+
+    // A 4 GiB fields length is refused before anything is allocated for it
+    fs::write(&file, b"CRYPTOCORE\x01\xff\xff\xff\xf0")?;
+    Command::cargo_bin("cryptocore")?
+        .args(["rewrap", "--old-key", key, "--new-key", key])
+        .arg(&file)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Malformed file header"));
+
+    // A wrapped key too short to be AES-KW output
+    fs::write(&file, b"CRYPTOCORE\x01\x00\x00\x00\x03\x03\x00\x00")?;
+    Command::cargo_bin("cryptocore")?
+        .arg("inspect")
+        .arg(&file)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Malformed file header"));
+    for length in [16u8, 25] {
+        let mut data = b"CRYPTOCORE\x01\x00\x00\x00".to_vec();
+        data.extend_from_slice(&[3 + length, 0x03, 0x00, length]);
+        data.extend_from_slice(&vec![0u8; length as usize]);
+        assert!(matches!(FileHeader::parse(&data), Err(CryptoCoreError::FileError(_))), "{} bytes", length);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_gcm_authenticates_the_header() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let plain = dir.path().join("notes.txt");
+    let cipher = dir.path().join("notes.enc");
+    let tampered = dir.path().join("notes.tampered");
+    let decrypted = dir.path().join("notes.dec");
+    let key = "000102030405060708090a0b0c0d0e0f";
+    let new_key = "f0e0d0c0b0a090807060504030201000";
+    let notes = "header fields are covered by the tag\n".repeat(100);
+    fs::write(&plain, &notes)?;
+
+    Command::cargo_bin("cryptocore")?
+        .args(["enc", "--mode", "gcm", "--compress", "zstd", "--key", key])
+        .arg("-i").arg(&plain)
+        .arg("-o").arg(&cipher)
+        .assert()
+        .success();
+
+    // Dropping the compression field leaves a well-formed header that no longer matches the tag
+    let data = fs::read(&cipher)?;
+    let (mut header, header_len) = FileHeader::parse(&data)?;
+    header.compression = None;
+    fs::write(&tampered, [header.to_bytes()?, data[header_len..].to_vec()].concat())?;
+    Command::cargo_bin("cryptocore")?
+        .args(["dec", "--mode", "gcm", "--key", key])
+        .arg("-i").arg(&tampered)
+        .arg("-o").arg(&decrypted)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Authentication failed"));
+    assert!(!decrypted.exists());
+
+    // Re-encryption binds the new header in the same way
+    Command::cargo_bin("cryptocore")?
+        .args(["reencrypt", "--old-key", key, "--new-key", new_key, "--old-mode", "gcm", "--mode", "ocb"])
+        .arg(&cipher)
+        .assert()
+        .success();
+    Command::cargo_bin("cryptocore")?
+        .args(["dec", "--mode", "ocb", "--key", new_key])
+        .arg("-i").arg(&cipher)
+        .arg("-o").arg(&decrypted)
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(&decrypted)?, notes);
+
+    let data = fs::read(&cipher)?;
+    let (mut header, header_len) = FileHeader::parse(&data)?;
+    header.compression = None;
+    fs::write(&tampered, [header.to_bytes()?, data[header_len..].to_vec()].concat())?;
+    Command::cargo_bin("cryptocore")?
+        .args(["dec", "--mode", "ocb", "--key", new_key])
+        .arg("-i").arg(&tampered)
+        .arg("-o").arg(dir.path().join("notes.ocb.dec"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Authentication failed"));
+
+    Ok(())
+}
+
+#[test]
+fn test_reencrypt_ecb_to_gcm() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let nested = dir.path().join("nested");
+    fs::create_dir(&nested)?;
+    let old_key = "000102030405060708090a0b0c0d0e0f";
+    let new_key = "f0e0d0c0b0a090807060504030201000";
+
+    let files = [dir.path().join("a.enc"), nested.join("b.enc")];
+    for (i, file) in files.iter().enumerate() {
+        let plain = dir.path().join(format!("plain{}.txt", i));
+        fs::write(&plain, format!("legacy ecb file number {}", i).repeat(500))?;
+        Command::cargo_bin("cryptocore")?
+            .args(["enc", "--mode", "ecb", "--key", old_key])
+            .arg("-i").arg(&plain)
+            .arg("-o").arg(file)
+            .assert()
+            .success();
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&files[0], fs::Permissions::from_mode(0o600))?;
+        // A link into the tree is skipped rather than followed or replaced
+        std::os::unix::fs::symlink(&files[0], nested.join("link.enc"))?;
+    }
+
+    Command::cargo_bin("cryptocore")?
+        .args(["reencrypt", "--old-key", old_key, "--new-key", new_key, "--old-mode", "ecb", "--mode", "gcm"])
+        .arg(dir.path().join("a.enc"))
+        .arg(&nested)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Re-encrypted:").count(2));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(fs::metadata(&files[0])?.permissions().mode() & 0o777, 0o600);
+        assert!(fs::symlink_metadata(nested.join("link.enc"))?.is_symlink());
+    }
+
+    for (i, file) in files.iter().enumerate() {
+        let decrypted = dir.path().join(format!("plain{}.dec", i));
+        Command::cargo_bin("cryptocore")?
+            .args(["dec", "--mode", "gcm", "--key", new_key])
+            .arg("-i").arg(file)
+            .arg("-o").arg(&decrypted)
+            .assert()
+            .success();
+        assert_eq!(fs::read_to_string(&decrypted)?, format!("legacy ecb file number {}", i).repeat(500));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_reencrypt_wrong_key_leaves_file_untouched() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let plain = dir.path().join("plain.txt");
+    let cipher = dir.path().join("plain.enc");
+    fs::write(&plain, "must survive a failed rotation")?;
+
+    Command::cargo_bin("cryptocore")?
+        .args(["enc", "--mode", "gcm", "--key", "000102030405060708090a0b0c0d0e0f"])
+        .arg("-i").arg(&plain)
+        .arg("-o").arg(&cipher)
+        .assert()
+        .success();
+    let before = fs::read(&cipher)?;
+
+    Command::cargo_bin("cryptocore")?
+        .args(["reencrypt", "--old-key", "ffffffffffffffffffffffffffffffff"])
+        .args(["--new-key", "f0e0d0c0b0a090807060504030201000", "--old-mode", "gcm"])
+        .arg(&cipher)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Authentication failed"));
+
+    // Neither the original nor a leftover temp file is changed or created
+    assert_eq!(fs::read(&cipher)?, before);
+    assert_eq!(fs::read_dir(dir.path())?.count(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_recursive_round_trip_restores_tree() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let source = dir.path().join("source");
+    let encrypted = dir.path().join("encrypted");
+    let restored = dir.path().join("restored");
+    let key = "000102030405060708090a0b0c0d0e0f";
+
+    fs::create_dir_all(source.join("docs/drafts"))?;
+    fs::create_dir_all(source.join("empty"))?;
+    fs::write(source.join("docs/report.txt"), "quarterly report")?;
+    fs::write(source.join("docs/drafts/notes.md"), "")?;
+    fs::write(source.join("old.enc"), "already encrypted elsewhere")?;
+
+    Command::cargo_bin("cryptocore")?
+        .args(["enc", "--recursive", "--mode", "cbc", "--key", key])
+        .arg("-i").arg(&source)
+        .arg("-o").arg(&encrypted)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Encrypted 2 files"))
+        .stdout(predicate::str::contains("Copied 1 already encrypted files"));
+
+    // Names are kept, contents are not
+    assert!(encrypted.join("docs/report.txt.enc").exists());
+    assert!(!fs::read(encrypted.join(".cryptocore-manifest"))?.windows(6).any(|w| w == b"report"));
+    assert_eq!(fs::read(encrypted.join("old.enc"))?, b"already encrypted elsewhere");
+
+    Command::cargo_bin("cryptocore")?
+        .args(["dec", "--recursive", "--mode", "cbc", "--key", key])
+        .arg("-i").arg(&encrypted)
+        .arg("-o").arg(&restored)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Restored 3 files"));
+
+    assert_eq!(fs::read_to_string(restored.join("docs/report.txt"))?, "quarterly report");
+    assert_eq!(fs::read(restored.join("docs/drafts/notes.md"))?, b"");
+    assert_eq!(fs::read(restored.join("old.enc"))?, b"already encrypted elsewhere");
+    assert!(restored.join("empty").is_dir());
+    Ok(())
+}
+
+#[test]
+fn test_manifest_rejects_paths_outside_tree() {
+    use cryptocore::core::io::manifest::Manifest;
+
+    for path in ["../escape", "/etc/passwd", "a/../../b"] {
+        let manifest = format!("cryptocore-manifest 1\ndir {}\n", path);
+        assert!(Manifest::parse(manifest.as_bytes()).is_err(), "{}", path);
+    }
+}
+
+#[test]
+fn test_compressed_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let plain = dir.path().join("app.log");
+    let key = "000102030405060708090a0b0c0d0e0f";
+    let log = "2024-01-01T00:00:00Z INFO request served in 3ms\n".repeat(2000);
+    fs::write(&plain, &log)?;
+
+    for algorithm in ["zstd", "gzip"] {
+        let cipher = dir.path().join(format!("app.{}.enc", algorithm));
+        let decrypted = dir.path().join(format!("app.{}.dec", algorithm));
+
+        Command::cargo_bin("cryptocore")?
+            .args(["enc", "--mode", "ctr", "--compress", algorithm, "--key", key])
+            .arg("-i").arg(&plain)
+            .arg("-o").arg(&cipher)
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("compression makes the output size depend on the content"));
+        assert!(fs::metadata(&cipher)?.len() < log.len() as u64 / 10);
+
+        // No --compress needed: the algorithm comes from the header
+        Command::cargo_bin("cryptocore")?
+            .args(["dec", "--mode", "ctr", "--key", key])
+            .arg("-i").arg(&cipher)
+            .arg("-o").arg(&decrypted)
+            .assert()
+            .success();
+        assert_eq!(fs::read_to_string(&decrypted)?, log);
+
+        // Output past the limit is refused rather than buffered
+        Command::cargo_bin("cryptocore")?
+            .args(["dec", "--mode", "ctr", "--key", key, "--max-decompressed-size"])
+            .arg((log.len() - 1).to_string())
+            .arg("-i").arg(&cipher)
+            .arg("-o").arg(&decrypted)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("exceeds the limit"));
+        Command::cargo_bin("cryptocore")?
+            .args(["dec", "--mode", "ctr", "--key", key, "--max-decompressed-size"])
+            .arg(log.len().to_string())
+            .arg("-i").arg(&cipher)
+            .arg("-o").arg(&decrypted)
+            .assert()
+            .success();
+    }
+
+    Ok(())
+}
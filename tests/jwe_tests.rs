@@ -0,0 +1,162 @@
+use assert_cmd::Command;
+use cryptocore::core::crypto::jwe::{self, ContentEncryption, Header, KeyManagement};
+use cryptocore::CryptoCoreError;
+use predicates::prelude::*;
+use std::fs;
+
+// RFC 7516, appendix A.3: A128KW with A128CBC-HS256
+const A3_KEK: &str = "19ac2082e1721ab58a6afec05f854a52"; // "GawgguFyGrWKav7AX4VKUg"
+const A3_CEK: [u8; 32] = [
+    4, 211, 31, 197, 84, 157, 252, 254, 11, 100, 157, 250, 63, 170, 106, 206, 107, 124, 212, 45, 111, 107, 9, 219,
+    200, 177, 0, 240, 143, 156, 44, 207,
+];
+const A3_IV: [u8; 16] = [3, 22, 60, 12, 43, 67, 104, 105, 108, 108, 105, 99, 111, 116, 104, 101];
+const A3_TOKEN: &str = "eyJhbGciOiJBMTI4S1ciLCJlbmMiOiJBMTI4Q0JDLUhTMjU2In0.\
+    6KB707dM9YTIgHtLvtgWQ8mKwboJW3of9locizkDTHzBC2IlrT1oOQ.\
+    AxY8DCtDaGlsbGljb3RoZQ.\
+    KDlTtXchhZTGufMYmOYGS4HffxPSUrfmqCHXaI9wOGY.\
+    U0m_YmjN04DJvceFICbCVQ";
+
+fn a3_token() -> String {
+    A3_TOKEN.split_whitespace().collect()
+}
+
+#[test]
+fn test_rfc7516_a3_encrypt() -> Result<(), Box<dyn std::error::Error>> {
+    let header = Header::new(KeyManagement::A128Kw, ContentEncryption::A128CbcHs256);
+    let token = jwe::encrypt_with(&hex::decode(A3_KEK)?, &header, b"Live long and prosper.", &A3_CEK, &A3_IV)?;
+    assert_eq!(token, a3_token());
+    Ok(())
+}
+
+#[test]
+fn test_rfc7516_a3_decrypt() -> Result<(), Box<dyn std::error::Error>> {
+    let (header, plaintext) = jwe::decrypt(&hex::decode(A3_KEK)?, &a3_token())?;
+    assert_eq!(header, Header::new(KeyManagement::A128Kw, ContentEncryption::A128CbcHs256));
+    assert_eq!(plaintext, b"Live long and prosper.");
+
+    // A changed header no longer matches the tag, even with the same algorithms
+    let respaced = a3_token().replacen("eyJhbGciOiJBMTI4S1ciLCJlbmMiOiJBMTI4Q0JDLUhTMjU2In0", "eyJhbGciOiAiQTEyOEtXIiwgImVuYyI6ICJBMTI4Q0JDLUhTMjU2In0", 1);
+    assert!(matches!(
+        jwe::decrypt(&hex::decode(A3_KEK)?, &respaced),
+        Err(CryptoCoreError::AuthenticationFailed(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_rfc7518_b1_aes_128_cbc_hmac_sha_256() -> Result<(), Box<dyn std::error::Error>> {
+    let key: Vec<u8> = (0..32).collect();
+    let iv = hex::decode("1af38c2dc2b96ffdd86694092341bc04")?;
+    let plaintext = b"A cipher system must not be required to be secret, and it must be able to fall into the hands of the enemy without inconvenience";
+    let aad = b"The second principle of Auguste Kerckhoffs";
+
+    let (ciphertext, tag) = ContentEncryption::A128CbcHs256.encrypt(&key, &iv, aad, plaintext)?;
+    assert_eq!(
+        hex::encode(&ciphertext),
+        "c80edfa32ddf39d5ef00c0b468834279a2e46a1b8049f792f76bfe54b903a9c9\
+         a94ac9b47ad2655c5f10f9aef71427e2fc6f9b3f399a221489f16362c7032336\
+         09d45ac69864e3321cf82935ac4096c86e133314c54019e8ca7980dfa4b9cf1b\
+         384c486f3a54c51078158ee5d79de59fbd34d848b3d69550a67646344427ade5\
+         4b8851ffb598f7f80074b9473c82e2db"
+    );
+    assert_eq!(hex::encode(&tag), "652c3fa36b0a7c5b3219fab3a30bc1c4");
+    assert_eq!(ContentEncryption::A128CbcHs256.decrypt(&key, &iv, aad, &ciphertext, &tag)?, plaintext);
+    Ok(())
+}
+
+#[test]
+fn test_gcm_tokens_from_python_cryptography() -> Result<(), Box<dyn std::error::Error>> {
+    // Built with AESGCM and aes_key_wrap from Python's cryptography package
+    let direct = "eyJhbGciOiJkaXIiLCJlbmMiOiJBMTI4R0NNIn0..EBESExQVFhcYGRob.iEd1yi8j2YFw_TybowebTFXPBOJE2g.Nb1WHi-sx89UXq3XO9Lmzw";
+    let key: Vec<u8> = (0..16).collect();
+    let (header, plaintext) = jwe::decrypt(&key, direct)?;
+    assert_eq!(header.alg, KeyManagement::Direct);
+    assert_eq!(header.enc, ContentEncryption::A128Gcm);
+    assert_eq!(plaintext, b"Live long and prosper.");
+    let iv: Vec<u8> = (0x10..0x1c).collect();
+    assert_eq!(jwe::encrypt_with(&key, &header, b"Live long and prosper.", &key, &iv)?, direct);
+
+    let wrapped = "eyJhbGciOiJBMjU2S1ciLCJlbmMiOiJBMjU2R0NNIn0.vSonaujHRkx-izlmdKxuDpVYyExgCbP6QTzwameiAII-TXIN8kGfqQ.EBESExQVFhcYGRob.NVNLtHb9e03Jhrc-j9h_xGpk9Kx0Pw.ltU-2ICWUNg1dHGBbh_3PQ";
+    let key: Vec<u8> = (0..32).collect();
+    let (header, plaintext) = jwe::decrypt(&key, wrapped)?;
+    assert_eq!(header.alg, KeyManagement::A256Kw);
+    assert_eq!(header.enc, ContentEncryption::A256Gcm);
+    assert_eq!(plaintext, b"Live long and prosper.");
+
+    let mut wrong_key = key.clone();
+    wrong_key[0] ^= 1;
+    assert!(jwe::decrypt(&wrong_key, wrapped).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_header_parsing() -> Result<(), Box<dyn std::error::Error>> {
+    let header = Header::parse(r#" { "enc" : "A128GCM", "alg":"dir", "kid":"kéy \"1\"", "x5t#S256": ["a", {"b": 1}], "n": -1.5e3 } "#)?;
+    assert_eq!(header.alg, KeyManagement::Direct);
+    assert_eq!(header.kid.as_deref(), Some("k\u{e9}y \"1\""));
+    assert_eq!(Header::parse(&header.to_json())?, header);
+
+    assert!(Header::parse(r#"{"alg":"dir","enc":"A128GCM","zip":"DEF"}"#).is_err());
+    assert!(Header::parse(r#"{"alg":"dir","enc":"A128GCM","crit":["exp"]}"#).is_err());
+    assert!(Header::parse(r#"{"alg":"RSA-OAEP","enc":"A128GCM"}"#).is_err());
+    assert!(Header::parse(r#"{"alg":"dir","alg":"A128KW","enc":"A128GCM"}"#).is_err());
+    assert!(Header::parse(r#"{"alg":"dir","enc":"A128GCM""#).is_err());
+    // Only real JSON literals and numbers are accepted
+    assert!(Header::parse(r#"{"alg":"dir","enc":"A128GCM","x":nullxyz}"#).is_err());
+    assert!(Header::parse(r#"{"alg":"dir","enc":"A128GCM","x":1abc}"#).is_err());
+    assert!(Header::parse(r#"{"alg":"dir","enc":"A128GCM","x":01}"#).is_err());
+    assert!(Header::parse(r#"{"alg":"dir","enc":"A128GCM","x":[1,]}"#).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_cli_jwe_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let plain = dir.path().join("claims.json");
+    let token = dir.path().join("claims.jwe");
+    let decrypted = dir.path().join("claims.out");
+    let key = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+    fs::write(&plain, r#"{"sub":"alice"}"#)?;
+
+    Command::cargo_bin("cryptocore")?
+        .args(["jwe", "encrypt", "--key", key, "--enc", "A128CBC-HS256", "--kid", "2024-01"])
+        .arg(&plain)
+        .arg("-o").arg(&token)
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(&token)?.trim().split('.').count(), 5);
+
+    Command::cargo_bin("cryptocore")?
+        .args(["jwe", "decrypt", "--key", key, "--alg", "A256KW"])
+        .arg(&token)
+        .arg("-o").arg(&decrypted)
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(&decrypted)?, r#"{"sub":"alice"}"#);
+
+    Command::cargo_bin("cryptocore")?
+        .args(["jwe", "decrypt", "--key", key, "--enc", "A256GCM"])
+        .arg(&token)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("expected \"A256GCM\""));
+
+    // The pins are checked before the key is tried, so a wrong key still
+    // reports the unexpected algorithm rather than a failed unwrap
+    Command::cargo_bin("cryptocore")?
+        .args(["jwe", "decrypt", "--key", &"ff".repeat(32), "--alg", "dir"])
+        .arg(&token)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("expected \"dir\""));
+
+    // "dir" needs a key of the content encryption size
+    Command::cargo_bin("cryptocore")?
+        .args(["jwe", "encrypt", "--key", "000102030405060708090a0b0c0d0e0f", "--alg", "dir", "--enc", "A256GCM"])
+        .arg(&plain)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("32-byte key"));
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use assert_cmd::Command;
+use cryptocore::core::crypto::keywrap;
+use cryptocore::CryptoCoreError;
+use predicates::prelude::*;
+
+// RFC 3394, section 4
+const KW_VECTORS: [(&str, &str, &str); 6] = [
+    (
+        "000102030405060708090a0b0c0d0e0f",
+        "00112233445566778899aabbccddeeff",
+        "1fa68b0a8112b447aef34bd8fb5a7b829d3e862371d2cfe5",
+    ),
+    (
+        "000102030405060708090a0b0c0d0e0f1011121314151617",
+        "00112233445566778899aabbccddeeff",
+        "96778b25ae6ca435f92b5b97c050aed2468ab8a17ad84e5d",
+    ),
+    (
+        "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+        "00112233445566778899aabbccddeeff",
+        "64e8c3f9ce0f5ba263e9777905818a2a93c8191e7d6e8ae7",
+    ),
+    (
+        "000102030405060708090a0b0c0d0e0f1011121314151617",
+        "00112233445566778899aabbccddeeff0001020304050607",
+        "031d33264e15d33268f24ec260743edce1c6c7ddee725a936ba814915c6762d2",
+    ),
+    (
+        "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+        "00112233445566778899aabbccddeeff0001020304050607",
+        "a8f9bc1612c68b3ff6e6f4fbe30e71e4769c8b80a32cb8958cd5d17d6b254da1",
+    ),
+    (
+        "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+        "00112233445566778899aabbccddeeff000102030405060708090a0b0c0d0e0f",
+        "28c9f404c4b810f4cbccb35cfb87f8263f5786e2d80ed326cbc7f0e71a99f43bfb988b9b7a02dd21",
+    ),
+];
+
+// RFC 5649, section 6
+const KWP_KEK: &str = "5840df6e29b02af1ab493b705bf16ea1ae8338f4dcc176a8";
+const KWP_VECTORS: [(&str, &str); 2] = [
+    (
+        "c37b7e6492584340bed12207808941155068f738",
+        "138bdeaa9b8fa7fc61f97742e72248ee5ae6ae5360d1ae6a5f54f373fa543b6a",
+    ),
+    ("466f7250617369", "afbeb0f07dfbf5419200f2ccb50bb24f"),
+];
+
+#[test]
+fn test_key_wrap_rfc3394_vectors() -> Result<(), Box<dyn std::error::Error>> {
+    for (kek, key_data, expected) in KW_VECTORS {
+        let kek = hex::decode(kek)?;
+        let key_data = hex::decode(key_data)?;
+
+        let wrapped = keywrap::wrap(&kek, &key_data)?;
+        assert_eq!(hex::encode(&wrapped), expected);
+        assert_eq!(keywrap::unwrap(&kek, &wrapped)?, key_data);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_key_wrap_with_padding_rfc5649_vectors() -> Result<(), Box<dyn std::error::Error>> {
+    let kek = hex::decode(KWP_KEK)?;
+    for (key_data, expected) in KWP_VECTORS {
+        let key_data = hex::decode(key_data)?;
+
+        let wrapped = keywrap::wrap_with_padding(&kek, &key_data)?;
+        assert_eq!(hex::encode(&wrapped), expected);
+        assert_eq!(keywrap::unwrap_with_padding(&kek, &wrapped)?, key_data);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_unwrap_with_wrong_kek_is_integrity_error() -> Result<(), Box<dyn std::error::Error>> {
+    let (_, _, wrapped) = KW_VECTORS[0];
+    let wrong_kek = hex::decode("ff0102030405060708090a0b0c0d0e0f")?;
+
+    let result = keywrap::unwrap(&wrong_kek, &hex::decode(wrapped)?);
+    assert!(matches!(result, Err(CryptoCoreError::IntegrityCheckFailed(_))));
+
+    let (_, wrapped) = KWP_VECTORS[1];
+    let mut wrapped = hex::decode(wrapped)?;
+    wrapped[15] ^= 1;
+    let result = keywrap::unwrap_with_padding(&hex::decode(KWP_KEK)?, &wrapped);
+    assert!(matches!(result, Err(CryptoCoreError::IntegrityCheckFailed(_))));
+    Ok(())
+}
+
+#[test]
+fn test_wrap_and_unwrap_commands() -> Result<(), Box<dyn std::error::Error>> {
+    let (kek, key_data, wrapped) = KW_VECTORS[0];
+
+    Command::cargo_bin("cryptocore")?
+        .args(["wrap", "--kek", kek, key_data])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", wrapped));
+
+    Command::cargo_bin("cryptocore")?
+        .args(["unwrap", "--padding", "--kek", KWP_KEK, KWP_VECTORS[1].1])
+        .assert()
+        .success()
+        .stdout("466f7250617369\n");
+
+    Command::cargo_bin("cryptocore")?
+        .args(["unwrap", "--kek", "ff0102030405060708090a0b0c0d0e0f", wrapped])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Integrity check failed"));
+
+    Ok(())
+}
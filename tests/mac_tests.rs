@@ -0,0 +1,82 @@
+use assert_cmd::Command;
+use cryptocore::core::crypto::mac::{self, MacAlgorithm};
+use cryptocore::CryptoCoreError;
+use predicates::prelude::*;
+use std::fs;
+
+const CMAC_KEY: &str = "2b7e151628aed2a6abf7158809cf4f3c";
+const CMAC_MESSAGE: &str = "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+                            30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710";
+
+#[test]
+fn test_aes_cmac_rfc4493_examples() -> Result<(), Box<dyn std::error::Error>> {
+    let key = hex::decode(CMAC_KEY)?;
+    let message = hex::decode(CMAC_MESSAGE)?;
+
+    let examples = [
+        (0, "bb1d6929e95937287fa37d129b756746"),
+        (16, "070a16b46b4d4144f79bdd9dd04a287c"),
+        (40, "dfa66747de9ae63030ca32611497c827"),
+        (64, "51f0bebf7e3b9d92fc49741779363cfe"),
+    ];
+
+    for (length, expected) in examples {
+        let tag = mac::compute(MacAlgorithm::AesCmac, &key, &message[..length])?;
+        assert_eq!(hex::encode(tag), expected, "message length {}", length);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_aes_cmac_incremental_matches_one_shot() -> Result<(), Box<dyn std::error::Error>> {
+    let key = hex::decode(CMAC_KEY)?;
+    let message = hex::decode(CMAC_MESSAGE)?;
+
+    let mut incremental = mac::Mac::new(MacAlgorithm::AesCmac, &key)?;
+    for piece in message.chunks(7) {
+        incremental.update(piece);
+    }
+
+    assert_eq!(
+        incremental.finalize(),
+        mac::compute(MacAlgorithm::AesCmac, &key, &message)?
+    );
+    Ok(())
+}
+
+#[test]
+fn test_verify_rejects_wrong_tag() -> Result<(), Box<dyn std::error::Error>> {
+    let key = [0x0b; 20];
+    let mut tag = mac::compute(MacAlgorithm::HmacSha256, &key, b"Hi There")?;
+    tag[31] ^= 1;
+
+    let result = mac::verify(MacAlgorithm::HmacSha256, &key, b"Hi There", &tag);
+    assert!(matches!(result, Err(CryptoCoreError::AuthenticationFailed(_))));
+    Ok(())
+}
+
+#[test]
+fn test_mac_command_verifies_cmac_tag() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let payment = dir.path().join("payment.dat");
+    fs::write(&payment, hex::decode(CMAC_MESSAGE)?)?;
+
+    Command::cargo_bin("cryptocore")?
+        .args(["mac", "--algorithm", "aes-cmac", "--key", CMAC_KEY])
+        .args(["--verify", "51f0bebf7e3b9d92fc49741779363cfe"])
+        .arg(&payment)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Verified OK"));
+
+    Command::cargo_bin("cryptocore")?
+        .args(["mac", "--algorithm", "aes-cmac", "--key", CMAC_KEY])
+        .args(["--verify", "00f0bebf7e3b9d92fc49741779363cfe"])
+        .arg(&payment)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Authentication failed"));
+
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use assert_cmd::Command;
+use cryptocore::core::crypto::modes::ocb::OcbMode;
+use cryptocore::CryptoCoreError;
+use predicates::prelude::*;
+use std::fs;
+
+// RFC 7253, appendix A: K = 000102...0f, nonce BBAA99887766554433221100 + i.
+// Each entry is (associated data length, plaintext length, ciphertext and tag),
+// where both inputs are the bytes 00 01 02 ... of that length.
+const SAMPLE_RESULTS: [(usize, usize, &str); 16] = [
+    (0, 0, "785407bfffc8ad9edcc5520ac9111ee6"),
+    (8, 8, "6820b3657b6f615a5725bda0d3b4eb3a257c9af1f8f03009"),
+    (8, 0, "81017f8203f081277152fade694a0a00"),
+    (0, 8, "45dd69f8f5aae72414054cd1f35d82760b2cd00d2f99bfa9"),
+    (16, 16, "571d535b60b277188be5147170a9a22c3ad7a4ff3835b8c5701c1ccec8fc3358"),
+    (16, 0, "8cf761b6902ef764462ad86498ca6b97"),
+    (0, 16, "5ce88ec2e0692706a915c00aeb8b2396f40e1c743f52436bdf06d8fa1eca343d"),
+    (
+        24,
+        24,
+        "1ca2207308c87c010756104d8840ce1952f09673a448a122c92c62241051f57356d7f3c90bb0e07f",
+    ),
+    (24, 0, "6dc225a071fc1b9f7c69f93b0f1e10de"),
+    (
+        0,
+        24,
+        "221bd0de7fa6fe993eccd769460a0af2d6cded0c395b1c3ce725f32494b9f914d85c0b1eb38357ff",
+    ),
+    (
+        32,
+        32,
+        "bd6f6c496201c69296c11efd138a467abd3c707924b964deaffc40319af5a485\
+         40fbba186c5553c68ad9f592a79a4240",
+    ),
+    (32, 0, "fe80690bee8a485d11f32965bc9d2a32"),
+    (
+        0,
+        32,
+        "2942bfc773bda23cabc6acfd9bfd5835bd300f0973792ef46040c53f1432bcdf\
+         b5e1dde3bc18a5f840b52e653444d5df",
+    ),
+    (
+        40,
+        40,
+        "d5ca91748410c1751ff8a2f618255b68a0a12e093ff454606e59f9c1d0ddc54b\
+         65e8628e568bad7aed07ba06a4a69483a7035490c5769e60",
+    ),
+    (40, 0, "c5cd9d1850c141e358649994ee701b68"),
+    (
+        0,
+        40,
+        "4412923493c57d5de0d700f753cce0d1d2d95060122e9f15a5ddbfc5787e50b5\
+         cc55ee507bcb084e479ad363ac366b95a98ca5f3000b1479",
+    ),
+];
+
+fn counting(length: usize) -> Vec<u8> {
+    (0..length as u8).collect()
+}
+
+#[test]
+fn test_ocb_rfc7253_sample_results() -> Result<(), Box<dyn std::error::Error>> {
+    let key = counting(16);
+    for (i, (aad_length, plaintext_length, expected)) in SAMPLE_RESULTS.into_iter().enumerate() {
+        let mut nonce = hex::decode("bbaa99887766554433221100")?;
+        nonce[11] = i as u8;
+        let ocb = OcbMode::new(&key, &nonce)?;
+        let (aad, plaintext) = (counting(aad_length), counting(plaintext_length));
+
+        let output = ocb.encrypt_with_aad(&aad, &plaintext)?;
+        assert_eq!(hex::encode(&output), expected, "sample {}", i);
+        assert_eq!(ocb.decrypt_with_aad(&aad, &output)?, plaintext);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_ocb_rfc7253_96_bit_tag() -> Result<(), Box<dyn std::error::Error>> {
+    let key = hex::decode("0f0e0d0c0b0a09080706050403020100")?;
+    let nonce = hex::decode("bbaa9988776655443322110d")?;
+    let ocb = OcbMode::with_tag_size(&key, &nonce, 12)?;
+    let data = counting(40);
+
+    let output = ocb.encrypt_with_aad(&data, &data)?;
+    assert_eq!(
+        hex::encode(&output),
+        "1792a4e31e0755fb03e31b22116e6c2ddf9efd6e33d536f1a0124b0a55bae884\
+         ed93481529c76b6ad0c515f4d1cdd4fdac4f02aa"
+    );
+    assert_eq!(ocb.decrypt_with_aad(&data, &output)?, data);
+    Ok(())
+}
+
+#[test]
+fn test_ocb_rejects_tampering() -> Result<(), Box<dyn std::error::Error>> {
+    let key = counting(16);
+    let nonce = hex::decode("bbaa9988776655443322110d")?;
+    let ocb = OcbMode::new(&key, &nonce)?;
+    let data = counting(40);
+    let output = ocb.encrypt_with_aad(&data, &data)?;
+
+    for index in [0, 20, 39, 45] {
+        let mut tampered = output.clone();
+        tampered[index] ^= 1;
+        let result = ocb.decrypt_with_aad(&data, &tampered);
+        assert!(matches!(result, Err(CryptoCoreError::AuthenticationFailed(_))));
+    }
+
+    let result = ocb.decrypt_with_aad(&data[1..], &output);
+    assert!(matches!(result, Err(CryptoCoreError::AuthenticationFailed(_))));
+
+    let result = OcbMode::new(&key, &nonce[..11])?.decrypt_with_aad(&data, &output);
+    assert!(matches!(result, Err(CryptoCoreError::AuthenticationFailed(_))));
+
+    assert!(matches!(OcbMode::new(&key, &[0u8; 16]), Err(CryptoCoreError::InvalidArgument(_))));
+    Ok(())
+}
+
+#[test]
+fn test_cli_ocb_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let plain = dir.path().join("plain.txt");
+    let cipher = dir.path().join("plain.enc");
+    let restored = dir.path().join("restored.txt");
+    let key = "000102030405060708090a0b0c0d0e0f";
+    fs::write(&plain, "single-pass authenticated encryption")?;
+
+    Command::cargo_bin("cryptocore")?
+        .args(["enc", "--mode", "ocb", "--key", key])
+        .arg("-i").arg(&plain)
+        .arg("-o").arg(&cipher)
+        .assert()
+        .success();
+    // 12-byte nonce and 16-byte tag around the ciphertext
+    assert_eq!(fs::read(&cipher)?.len(), 12 + 36 + 16);
+
+    Command::cargo_bin("cryptocore")?
+        .args(["dec", "--mode", "ocb", "--key", key])
+        .arg("-i").arg(&cipher)
+        .arg("-o").arg(&restored)
+        .assert()
+        .success();
+    assert_eq!(fs::read(&restored)?, fs::read(&plain)?);
+
+    let mut tampered = fs::read(&cipher)?;
+    tampered[20] ^= 1;
+    fs::write(&cipher, tampered)?;
+    Command::cargo_bin("cryptocore")?
+        .args(["dec", "--mode", "ocb", "--key", key])
+        .arg("-i").arg(&cipher)
+        .arg("-o").arg(&restored)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Authentication failed"));
+    Ok(())
+}
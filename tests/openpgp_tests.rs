@@ -0,0 +1,222 @@
+use assert_cmd::Command;
+use cryptocore::core::crypto::modes::cfb::CfbMode;
+use cryptocore::core::crypto::openpgp::s2k::{S2k, S2kHash};
+use cryptocore::core::crypto::openpgp::{self, AeadAlgorithm, EncryptOptions, LiteralData, SymmetricAlgorithm};
+use cryptocore::core::crypto::traits::Cipher;
+use cryptocore::core::io::armor;
+use cryptocore::CryptoCoreError;
+use predicates::prelude::*;
+use sha1::{Digest, Sha1};
+use std::fs;
+
+const PASSPHRASE: &[u8] = b"correct horse";
+
+// gpg 2.2: `gpg -c -a --s2k-count 65536 dawn.txt` (AES256, SHA1 S2K, ZIP compression)
+const GPG_DEFAULT: &str = "-----BEGIN PGP MESSAGE-----
+
+jA0ECQMCbgYeS22eBMZg0koBmklD8D17Dn2Ng4E/5RMEVLq4ViDetVK5cKmOZXZt
+neky1cDlg+7xojwrfxEoKA+sjkSnQ/1ozj4RuR1q6Nzsg+Yj2BPbzeFscQ==
+=oTqk
+-----END PGP MESSAGE-----
+";
+
+// ... --cipher-algo AES128 --compress-algo zlib --s2k-digest-algo SHA512
+const GPG_AES128_ZLIB: &str = "-----BEGIN PGP MESSAGE-----
+
+jA0EBwMKvoMrJslStrNg0lABo+zmjvMeEDT9J95DamUq+NMbwFZjkaYm8tzwGjL5
+c4mniO8ox/aTwX/dLwTCX0hzznvGjLvX0fSUlcr0TCUJ0g40wceU35y4zlOoiUDs
+jg==
+=euM2
+-----END PGP MESSAGE-----
+";
+
+// ... --rfc2440 --cipher-algo AES192 --compress-algo none: no MDC, resynchronized CFB
+const GPG_RFC2440: &str = "-----BEGIN PGP MESSAGE-----
+
+jA0ECAMCwjK1wD7jTjxgpDKMzcDuHm/FvtS6GjN79tKuWfgSERNxXuNRN1EyW0Vp
+HTkB1RBnK6J7FC+I0BXsDcxzVA==
+=X3Go
+-----END PGP MESSAGE-----
+";
+
+fn dearmor(message: &str) -> Vec<u8> {
+    armor::dearmor_as(armor::PGP_LABEL, message.as_bytes()).unwrap()
+}
+
+// A new-format packet with a one-octet length
+fn packet(tag: u8, body: &[u8]) -> Vec<u8> {
+    [&[0xc0 | tag, u8::try_from(body.len()).unwrap()][..], body].concat()
+}
+
+// SKESK v4 + SEIPD v1 around arbitrary packets, which `openpgp::encrypt` cannot produce
+fn seipd_v1_message(packets: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let s2k = S2k::iterated(S2kHash::Sha256, [7; 8], 1024);
+    let key = s2k.derive_key(PASSPHRASE, 32);
+
+    // Prefix with its last two bytes repeated, then the packets and the MDC
+    let mut plaintext = vec![0x42; 18];
+    plaintext.extend_from_slice(packets);
+    plaintext.extend_from_slice(&[0xd3, 20]);
+    let mdc = Sha1::digest(&plaintext);
+    plaintext.extend_from_slice(&mdc);
+    let body = [&[1][..], &CfbMode::openpgp(&key, false)?.encrypt(&plaintext)?].concat();
+
+    let skesk = [&[4, SymmetricAlgorithm::Aes256.id()][..], &s2k.to_bytes()].concat();
+    Ok([packet(3, &skesk), packet(18, &body)].concat())
+}
+
+#[test]
+fn test_decrypts_gpg_messages() -> Result<(), Box<dyn std::error::Error>> {
+    for message in [GPG_DEFAULT, GPG_AES128_ZLIB] {
+        let literal = openpgp::decrypt(PASSPHRASE, &dearmor(message), false)?;
+        assert_eq!(literal.data, b"Attack at dawn.\n");
+        assert_eq!(literal.filename, "dawn.txt");
+    }
+
+    let wrong = openpgp::decrypt(b"battery staple", &dearmor(GPG_DEFAULT), false);
+    assert!(matches!(wrong, Err(CryptoCoreError::AuthenticationFailed(_))));
+    Ok(())
+}
+
+#[test]
+fn test_unprotected_messages_need_opt_in() -> Result<(), Box<dyn std::error::Error>> {
+    let message = dearmor(GPG_RFC2440);
+    assert!(matches!(
+        openpgp::decrypt(PASSPHRASE, &message, false),
+        Err(CryptoCoreError::AuthenticationFailed(_))
+    ));
+    assert_eq!(openpgp::decrypt(PASSPHRASE, &message, true)?.data, b"Attack at dawn.\n");
+    Ok(())
+}
+
+#[test]
+fn test_modified_message_is_rejected() -> Result<(), Box<dyn std::error::Error>> {
+    let mut message = dearmor(GPG_DEFAULT);
+    let last = message.len() - 1;
+    message[last] ^= 1;
+    assert!(matches!(
+        openpgp::decrypt(PASSPHRASE, &message, false),
+        Err(CryptoCoreError::AuthenticationFailed(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_compressed_packets_nest_only_a_few_levels() -> Result<(), Box<dyn std::error::Error>> {
+    // Stored (algorithm 0) compressed packets around one literal data packet
+    let nested = |levels: usize| {
+        let mut packets = packet(11, b"b\x01x\0\0\0\0nested");
+        for _ in 0..levels {
+            packets = packet(8, &[&[0][..], &packets].concat());
+        }
+        packets
+    };
+
+    let literal = openpgp::decrypt(PASSPHRASE, &seipd_v1_message(&nested(4))?, false)?;
+    assert_eq!(literal.data, b"nested");
+    match openpgp::decrypt(PASSPHRASE, &seipd_v1_message(&nested(5))?, false) {
+        Err(CryptoCoreError::FileError(message)) => assert!(message.contains("nested too deeply")),
+        other => panic!("expected a malformed message error, got {:?}", other),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_round_trip_seipd_v1_and_v2() -> Result<(), Box<dyn std::error::Error>> {
+    let literal = LiteralData {
+        filename: "report.txt".to_string(),
+        modified: 1_700_000_000,
+        // Spans several AEAD chunks
+        data: (0..200_000u32).map(|i| i as u8).collect(),
+    };
+
+    for (cipher, aead) in [
+        (SymmetricAlgorithm::Aes256, None),
+        (SymmetricAlgorithm::Aes128, None),
+        (SymmetricAlgorithm::Aes256, Some(AeadAlgorithm::Gcm)),
+        (SymmetricAlgorithm::Aes192, Some(AeadAlgorithm::Gcm)),
+    ] {
+        let options = EncryptOptions { cipher, aead, s2k_iterations: 1024, ..EncryptOptions::default() };
+        let message = openpgp::encrypt(PASSPHRASE, &literal, &options)?;
+        assert_eq!(openpgp::decrypt(PASSPHRASE, &message, false)?, literal);
+        assert!(openpgp::decrypt(b"wrong", &message, false).is_err());
+
+        // Dropping the last chunk's bytes must not go unnoticed
+        let mut truncated = message.clone();
+        truncated.truncate(message.len() - 40);
+        assert!(openpgp::decrypt(PASSPHRASE, &truncated, false).is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_s2k() -> Result<(), Box<dyn std::error::Error>> {
+    let salt = [1, 2, 3, 4, 5, 6, 7, 8];
+    // Coded count 96 is 65536 bytes, the smallest count of at least 65536
+    let s2k = S2k::iterated(S2kHash::Sha256, salt, 65536);
+    assert_eq!(s2k.to_bytes(), [3, 8, 1, 2, 3, 4, 5, 6, 7, 8, 96]);
+    assert_eq!(S2k::parse(&s2k.to_bytes())?, (s2k.clone(), 11));
+
+    // Keys longer than the digest continue with zero-preloaded contexts
+    let key = S2k::Salted { hash: S2kHash::Sha1, salt }.derive_key(b"pw", 32);
+    let first = S2k::Salted { hash: S2kHash::Sha1, salt }.derive_key(b"pw", 20);
+    assert_eq!(key[..20], first[..]);
+    assert_ne!(key[20..], first[..12]);
+    Ok(())
+}
+
+#[test]
+fn test_cli_pgp_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let plain = dir.path().join("notes.txt");
+    let decrypted = dir.path().join("notes.out");
+    fs::write(&plain, "meet at noon")?;
+
+    Command::cargo_bin("cryptocore")?
+        .args(["pgp", "encrypt", "--pass", "hunter2", "--armor", "--s2k-count", "1024"])
+        .arg(&plain)
+        .assert()
+        .success();
+    let armored = dir.path().join("notes.txt.asc");
+    assert!(fs::read_to_string(&armored)?.starts_with("-----BEGIN PGP MESSAGE-----\n\n"));
+
+    Command::cargo_bin("cryptocore")?
+        .args(["pgp", "decrypt", "--pass", "hunter2"])
+        .arg(&armored)
+        .arg("-o").arg(&decrypted)
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(&decrypted)?, "meet at noon");
+
+    Command::cargo_bin("cryptocore")?
+        .args(["pgp", "decrypt", "--pass", "hunter3"])
+        .arg(&armored)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("wrong passphrase"));
+    Ok(())
+}
+
+#[test]
+fn test_gpg_reads_our_messages() -> Result<(), Box<dyn std::error::Error>> {
+    // Only where gpg is installed
+    if std::process::Command::new("gpg").arg("--version").output().is_err() {
+        return Ok(());
+    }
+    let dir = tempfile::tempdir()?;
+    let home = dir.path().join("gnupg");
+    fs::create_dir(&home)?;
+    let literal = LiteralData { filename: "x".to_string(), modified: 0, data: b"from cryptocore".to_vec() };
+    let options = EncryptOptions { s2k_iterations: 1024, ..EncryptOptions::default() };
+    let message = dir.path().join("x.gpg");
+    fs::write(&message, openpgp::encrypt(PASSPHRASE, &literal, &options)?)?;
+
+    let output = std::process::Command::new("gpg")
+        .arg("--homedir").arg(&home)
+        .args(["--batch", "--quiet", "--pinentry-mode", "loopback", "--passphrase", "correct horse", "--decrypt"])
+        .arg(&message)
+        .output()?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(output.stdout, b"from cryptocore");
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use assert_cmd::Command;
+use cryptocore::core::crypto::create_cipher;
+use cryptocore::core::io::openssl::{self, OpensslDigest, OpensslKdf};
+use std::fs;
+
+// Produced with `openssl enc -aes-256-cbc [-pbkdf2 | -md md5] -pass pass:secret -S 0102030405060708`
+const PASSWORD: &str = "secret";
+const SALT: &str = "0102030405060708";
+const PLAINTEXT: &str = "Partner data 2024\n";
+const PBKDF2_CIPHERTEXT: &str = "399151d623d29669888163835082d64779fa2c9b0a1f80205a13d6a9e6dc7b14";
+const MD5_CIPHERTEXT: &str = "a4f5a915e023bdd9516b6299e0f8b00e005125eaf2e9bf82c41c4fc44412032a";
+
+fn salted_file(ciphertext: &str) -> Vec<u8> {
+    openssl::to_bytes(&hex::decode(SALT).unwrap(), &hex::decode(ciphertext).unwrap())
+}
+
+#[test]
+fn test_key_derivation_matches_openssl() -> Result<(), Box<dyn std::error::Error>> {
+    let salt = hex::decode(SALT)?;
+
+    // openssl enc -aes-256-cbc -md md5 -P
+    let (key, iv) = openssl::derive_key_iv(
+        OpensslKdf::BytesToKey(OpensslDigest::Md5),
+        PASSWORD.as_bytes(),
+        &salt,
+        32,
+        16,
+    )?;
+    assert_eq!(hex::encode(key), "c9e5a1bd216dbe1317e230cef48f38ee7f0e17ad64022144bccec4a1aa2879ab");
+    assert_eq!(hex::encode(iv), "e24b32bbbc4ef02ecbcb6576523ad893");
+
+    // openssl enc -aes-128-cbc -iter 1000 -P
+    let (key, iv) = openssl::derive_key_iv(
+        OpensslKdf::Pbkdf2 { digest: OpensslDigest::Sha256, iterations: 1000 },
+        PASSWORD.as_bytes(),
+        &salt,
+        16,
+        16,
+    )?;
+    assert_eq!(hex::encode(key), "d9bf4f8b9d6a9ca73fb33112ebed290a");
+    assert_eq!(hex::encode(iv), "4c6df9017a23add035fea1a1d83b5db8");
+    Ok(())
+}
+
+#[test]
+fn test_encryption_is_byte_for_byte_openssl() -> Result<(), Box<dyn std::error::Error>> {
+    let kdf = OpensslKdf::Pbkdf2 { digest: OpensslDigest::Sha256, iterations: openssl::DEFAULT_ITERATIONS };
+    let (key, iv) = openssl::derive_key_iv(kdf, PASSWORD.as_bytes(), &hex::decode(SALT)?, 32, 16)?;
+
+    let ciphertext = create_cipher("aes", "cbc", &key, Some(&iv))?.encrypt(PLAINTEXT.as_bytes())?;
+    assert_eq!(hex::encode(ciphertext), PBKDF2_CIPHERTEXT);
+    Ok(())
+}
+
+#[test]
+fn test_cli_decrypts_openssl_files() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let cases: [(&str, &[&str]); 2] = [
+        (PBKDF2_CIPHERTEXT, &["--pbkdf2"]),
+        (MD5_CIPHERTEXT, &["--md", "md5"]),
+    ];
+
+    for (i, (ciphertext, kdf_args)) in cases.into_iter().enumerate() {
+        let input = dir.path().join(format!("partner{}.bin", i));
+        let output = dir.path().join(format!("partner{}.txt", i));
+        fs::write(&input, salted_file(ciphertext))?;
+
+        Command::cargo_bin("cryptocore")?
+            .args(["dec", "--format", "openssl", "--mode", "cbc", "--pass", PASSWORD])
+            .args(kdf_args)
+            .arg("-i").arg(&input)
+            .arg("-o").arg(&output)
+            .assert()
+            .success();
+        assert_eq!(fs::read_to_string(&output)?, PLAINTEXT);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_cli_openssl_round_trip_writes_salted_header() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let plain = dir.path().join("plain.txt");
+    let cipher = dir.path().join("plain.enc");
+    let decrypted = dir.path().join("plain.dec");
+    fs::write(&plain, PLAINTEXT)?;
+
+    Command::cargo_bin("cryptocore")?
+        .args(["enc", "--format", "openssl", "--mode", "ctr", "--iter", "5000", "--pass", PASSWORD])
+        .arg("-i").arg(&plain)
+        .arg("-o").arg(&cipher)
+        .assert()
+        .success();
+
+    let data = fs::read(&cipher)?;
+    assert!(data.starts_with(b"Salted__"));
+    assert_eq!(data.len(), 16 + PLAINTEXT.len());
+
+    Command::cargo_bin("cryptocore")?
+        .args(["dec", "--format", "openssl", "--mode", "ctr", "--iter", "5000", "--pass", PASSWORD])
+        .arg("-i").arg(&cipher)
+        .arg("-o").arg(&decrypted)
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(&decrypted)?, PLAINTEXT);
+    Ok(())
+}
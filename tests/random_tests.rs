@@ -0,0 +1,92 @@
+use cryptocore::core::crypto::aead_stream::{StreamAead, StreamHeader};
+use cryptocore::core::crypto::age::X25519Identity;
+use cryptocore::core::crypto::asymmetric::{KeyAlgorithm, PrivateKey};
+use cryptocore::core::crypto::drbg::CtrDrbg;
+use cryptocore::core::crypto::fernet::Fernet;
+use cryptocore::core::crypto::random::{self, RandomSource};
+use cryptocore::core::io;
+use cryptocore::{CryptoCoreError, Result};
+
+/// An entropy source that has stopped working.
+struct Failing;
+
+impl RandomSource for Failing {
+    fn fill(&mut self, _output: &mut [u8]) -> Result<()> {
+        Err(CryptoCoreError::Crypto("entropy source unavailable".to_string()))
+    }
+}
+
+/// Counts up from a starting byte, so outputs are predictable.
+struct Counting(u8);
+
+impl RandomSource for Counting {
+    fn fill(&mut self, output: &mut [u8]) -> Result<()> {
+        for byte in output {
+            *byte = self.0;
+            self.0 = self.0.wrapping_add(1);
+        }
+        Ok(())
+    }
+}
+
+fn is_entropy_failure<T>(result: Result<T>) -> bool {
+    matches!(result, Err(CryptoCoreError::Crypto(message)) if message.contains("unavailable"))
+}
+
+#[test]
+fn test_failing_source_is_an_error_not_a_panic() {
+    random::with_source(Failing, || {
+        assert!(is_entropy_failure(io::generate_iv()));
+        assert!(is_entropy_failure(io::generate_nonce(12)));
+        assert!(is_entropy_failure(io::generate_key(32)));
+        assert!(is_entropy_failure(Fernet::generate_key()));
+        assert!(is_entropy_failure(X25519Identity::generate()));
+        assert!(is_entropy_failure(PrivateKey::generate(KeyAlgorithm::P256, 0)));
+        assert!(is_entropy_failure(PrivateKey::generate(KeyAlgorithm::Rsa, 2048)));
+        assert!(is_entropy_failure(StreamHeader::new(StreamAead::Gcm, 1024)));
+        assert!(is_entropy_failure(io::temp_path_for(std::path::Path::new("out.bin"))));
+
+        let fernet = Fernet::new(&[7u8; 32]).unwrap();
+        assert!(is_entropy_failure(fernet.encrypt(b"message")));
+    });
+}
+
+#[test]
+fn test_injected_source_supplies_ivs_and_keys() -> Result<()> {
+    random::with_source(Counting(0), || {
+        assert_eq!(io::generate_iv()?, std::array::from_fn::<u8, 16, _>(|i| i as u8));
+        assert_eq!(io::generate_key(4)?, [16, 17, 18, 19]);
+        let header = StreamHeader::new(StreamAead::Ocb, 1024)?;
+        assert_eq!(header.salt, std::array::from_fn::<u8, 16, _>(|i| 20 + i as u8));
+        assert_eq!(header.nonce_prefix, [36, 37, 38, 39, 40, 41, 42]);
+        Ok(())
+    })
+}
+
+#[test]
+fn test_sources_are_scoped_to_the_thread() -> Result<()> {
+    random::with_source(Failing, || -> Result<()> {
+        // Nested sources apply inside and the outer one comes back after
+        let inner = random::with_source(Counting(5), || io::generate_key(2))?;
+        assert_eq!(inner, [5, 6]);
+        assert!(is_entropy_failure(io::generate_key(2)));
+
+        // Other threads keep the process source
+        let other = std::thread::spawn(|| io::generate_key(16)).join().unwrap()?;
+        assert_eq!(other.len(), 16);
+        Ok(())
+    })?;
+    assert_eq!(io::generate_key(8)?.len(), 8);
+    Ok(())
+}
+
+#[test]
+fn test_ctr_drbg_as_injected_source() -> Result<()> {
+    let seed = [0x5au8; 32];
+    let mut expected = [0u8; 48];
+    CtrDrbg::instantiate(32, &seed, &[], b"tests")?.fill(&mut expected)?;
+
+    let key = random::with_source(CtrDrbg::instantiate(32, &seed, &[], b"tests")?, || io::generate_key(48))?;
+    assert_eq!(key, expected);
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use cryptocore::core::crypto::modes::gcm_siv::AesGcmSiv;
+use cryptocore::core::crypto::modes::siv::AesSiv;
+use cryptocore::CryptoCoreError;
+
+// RFC 5297, appendix A.1 (deterministic) and A.2 (nonce-based)
+const SIV_A1_KEY: &str = "fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff";
+const SIV_A1_AD: &str = "101112131415161718191a1b1c1d1e1f2021222324252627";
+const SIV_A1_PLAINTEXT: &str = "112233445566778899aabbccddee";
+const SIV_A1_OUTPUT: &str = "85632d07c6e8f37f950acd320a2ecc9340c02b9690c4dc04daef7f6afe5c";
+
+const SIV_A2_KEY: &str = "7f7e7d7c7b7a79787776757473727170404142434445464748494a4b4c4d4e4f";
+const SIV_A2_AD: [&str; 3] = [
+    "00112233445566778899aabbccddeeffdeaddadadeaddadaffeeddccbbaa99887766554433221100",
+    "102030405060708090a0",
+    "09f911029d74e35bd84156c5635688c0",
+];
+const SIV_A2_OUTPUT: &str = "7bdb6e3b432667eb06f4d14bff2fbd0fcb900f2fddbe404326601965c889bf17\
+                             dba77ceb094fa663b7a3f748ba8af829ea64ad544a272e9c485b62a3fd5c0d";
+
+// RFC 8452, appendix C.1 and C.2: (key, nonce, aad, plaintext, result)
+const GCM_SIV_NONCE: &str = "030000000000000000000000";
+const GCM_SIV_VECTORS: [(&str, &str, &str, &str); 5] = [
+    ("01000000000000000000000000000000", "", "", "dc20e2d83f25705bb49e439eca56de25"),
+    (
+        "01000000000000000000000000000000",
+        "",
+        "0100000000000000",
+        "b5d839330ac7b786578782fff6013b815b287c22493a364c",
+    ),
+    (
+        "01000000000000000000000000000000",
+        "01",
+        "0200000000000000",
+        "1e6daba35669f4273b0a1a2560969cdf790d99759abd1508",
+    ),
+    (
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "01",
+        "0200000000000000",
+        "1de22967237a813291213f267e3b452f02d01ae33e4ec854",
+    ),
+    (
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "01",
+        "02000000000000000000000000000000030000000000000000000000000000000400000000000000",
+        "bdf21f4913e5f08f4ae4afaf2ab98d4c2bfdb33e78df11fe84e4572a412ca639\
+         508f8f535cea6342c7c492f145d7a8bb3c5973eaae7a3dc8",
+    ),
+];
+
+#[test]
+fn test_aes_siv_rfc5297_vectors() -> Result<(), Box<dyn std::error::Error>> {
+    let siv = AesSiv::new(&hex::decode(SIV_A1_KEY)?)?;
+    let ad = hex::decode(SIV_A1_AD)?;
+    let output = siv.encrypt(&[&ad], &hex::decode(SIV_A1_PLAINTEXT)?)?;
+    assert_eq!(hex::encode(&output), SIV_A1_OUTPUT);
+    assert_eq!(hex::encode(siv.decrypt(&[&ad], &output)?), SIV_A1_PLAINTEXT);
+
+    let siv = AesSiv::new(&hex::decode(SIV_A2_KEY)?)?;
+    let ad = SIV_A2_AD.iter().map(hex::decode).collect::<Result<Vec<_>, _>>()?;
+    let ad: Vec<&[u8]> = ad.iter().map(Vec::as_slice).collect();
+    let plaintext = b"this is some plaintext to encrypt using SIV-AES";
+    let output = siv.encrypt(&ad, plaintext)?;
+    assert_eq!(hex::encode(&output), SIV_A2_OUTPUT);
+    assert_eq!(siv.decrypt(&ad, &output)?, plaintext);
+    Ok(())
+}
+
+#[test]
+fn test_aes_siv_longer_keys() -> Result<(), Box<dyn std::error::Error>> {
+    // Generated with Python's cryptography AESSIV
+    let key: Vec<u8> = (0..64).collect();
+    let plaintext: Vec<u8> = (0..100).collect();
+    let output = AesSiv::new(&key)?.encrypt(&[b"a", b""], &plaintext)?;
+    assert_eq!(
+        hex::encode(&output),
+        "d236fba651f0c34514e79fd8e9396b84b13b9c219df884359444c0c75a8ed66e4ab820ee940d9d1ec9f28a71\
+         7296da5c89f6c5f3609424505fa85f6aa7217792114d2fb0eaf06c9c7bdbdd942c7083bb241b41f0aebd77e2\
+         f141792750548d01ac6dd8b509c1a84ae41039dbbc620a5496abfb44"
+    );
+
+    let key: Vec<u8> = (0..48).collect();
+    assert_eq!(hex::encode(AesSiv::new(&key)?.encrypt(&[], b"")?), "9e44df35c90f1138e1788e367d580588");
+
+    assert!(matches!(AesSiv::new(&[0u8; 16]), Err(CryptoCoreError::InvalidKey(_))));
+    Ok(())
+}
+
+#[test]
+fn test_aes_siv_is_deterministic_and_authenticated() -> Result<(), Box<dyn std::error::Error>> {
+    let siv = AesSiv::new(&hex::decode(SIV_A1_KEY)?)?;
+    let column: &[u8] = b"users.email";
+
+    let first = siv.encrypt(&[column], b"alice@example.com")?;
+    assert_eq!(siv.encrypt(&[column], b"alice@example.com")?, first);
+    assert_ne!(siv.encrypt(&[column], b"bob@example.com")?, first);
+    assert_ne!(siv.encrypt(&[b"users.name"], b"alice@example.com")?, first);
+
+    // The same value under another column does not decrypt
+    let result = siv.decrypt(&[b"users.name"], &first);
+    assert!(matches!(result, Err(CryptoCoreError::AuthenticationFailed(_))));
+
+    let mut tampered = first.clone();
+    *tampered.last_mut().unwrap() ^= 1;
+    let result = siv.decrypt(&[column], &tampered);
+    assert!(matches!(result, Err(CryptoCoreError::AuthenticationFailed(_))));
+
+    assert!(siv.decrypt(&[column], &first[..15]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_aes_gcm_siv_rfc8452_vectors() -> Result<(), Box<dyn std::error::Error>> {
+    let nonce: [u8; 12] = hex::decode(GCM_SIV_NONCE)?.try_into().unwrap();
+    for (key, aad, plaintext, expected) in GCM_SIV_VECTORS {
+        let cipher = AesGcmSiv::new(&hex::decode(key)?)?;
+        let aad = hex::decode(aad)?;
+        let plaintext = hex::decode(plaintext)?;
+
+        let output = cipher.encrypt(&nonce, &aad, &plaintext)?;
+        assert_eq!(hex::encode(&output), expected);
+        assert_eq!(cipher.decrypt(&nonce, &aad, &output)?, plaintext);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_aes_gcm_siv_rejects_tampering() -> Result<(), Box<dyn std::error::Error>> {
+    let (key, aad, _, output) = GCM_SIV_VECTORS[4];
+    let cipher = AesGcmSiv::new(&hex::decode(key)?)?;
+    let nonce: [u8; 12] = hex::decode(GCM_SIV_NONCE)?.try_into().unwrap();
+    let aad = hex::decode(aad)?;
+    let output = hex::decode(output)?;
+
+    let mut tampered = output.clone();
+    tampered[0] ^= 1;
+    let result = cipher.decrypt(&nonce, &aad, &tampered);
+    assert!(matches!(result, Err(CryptoCoreError::AuthenticationFailed(_))));
+
+    let result = cipher.decrypt(&[0u8; 12], &aad, &output);
+    assert!(matches!(result, Err(CryptoCoreError::AuthenticationFailed(_))));
+
+    let result = cipher.decrypt(&nonce, b"", &output);
+    assert!(matches!(result, Err(CryptoCoreError::AuthenticationFailed(_))));
+
+    assert!(matches!(AesGcmSiv::new(&[0u8; 24]), Err(CryptoCoreError::InvalidKey(_))));
+    Ok(())
+}
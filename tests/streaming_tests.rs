@@ -0,0 +1,99 @@
+use cryptocore::core::crypto::create_cipher;
+use cryptocore::core::crypto::modes::gcm::{GcmEncryptor, GcmMode};
+use cryptocore::core::crypto::streaming::{StreamDecryptor, StreamEncryptor};
+use cryptocore::CryptoCoreError;
+
+const KEY: &str = "000102030405060708090a0b0c0d0e0f";
+const IV: &str = "f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff";
+
+fn iv_for(mode: &str) -> Option<Vec<u8>> {
+    match mode {
+        "ecb" => None,
+        "gcm" => Some(hex::decode(&IV[..24]).unwrap()),
+        _ => Some(hex::decode(IV).unwrap()),
+    }
+}
+
+#[test]
+fn test_streaming_matches_one_shot() -> Result<(), Box<dyn std::error::Error>> {
+    let key = hex::decode(KEY)?;
+    let plaintext: Vec<u8> = (0..1000u32).map(|i| (i * 7) as u8).collect();
+
+    for mode in ["ecb", "cbc", "cfb", "ofb", "ctr", "gcm"] {
+        let iv = iv_for(mode);
+        let expected = create_cipher("aes", mode, &key, iv.as_deref())?.encrypt(&plaintext)?;
+
+        // Chunk sizes that are smaller than, equal to and not aligned with a block
+        for chunk_size in [1, 7, 16, 33, 1000] {
+            let mut encryptor = StreamEncryptor::new("aes", mode, &key, iv.as_deref())?;
+            let mut ciphertext = Vec::new();
+            for chunk in plaintext.chunks(chunk_size) {
+                ciphertext.extend(encryptor.update(chunk)?);
+            }
+            ciphertext.extend(encryptor.finish()?);
+            assert_eq!(ciphertext, expected, "{} encrypt, chunks of {}", mode, chunk_size);
+
+            let mut decryptor = StreamDecryptor::new("aes", mode, &key, iv.as_deref())?;
+            let mut decrypted = Vec::new();
+            for chunk in ciphertext.chunks(chunk_size) {
+                decrypted.extend(decryptor.update(chunk)?);
+            }
+            decrypted.extend(decryptor.finish()?);
+            assert_eq!(decrypted, plaintext, "{} decrypt, chunks of {}", mode, chunk_size);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_streaming_gcm_releases_nothing_before_tag_check() -> Result<(), Box<dyn std::error::Error>> {
+    let key = hex::decode(KEY)?;
+    let iv = iv_for("gcm");
+    let mut ciphertext = create_cipher("aes", "gcm", &key, iv.as_deref())?.encrypt(&[0x42; 100])?;
+    ciphertext[10] ^= 1;
+
+    let mut decryptor = StreamDecryptor::new("aes", "gcm", &key, iv.as_deref())?;
+    assert!(decryptor.update(&ciphertext)?.is_empty());
+    assert!(matches!(decryptor.finish(), Err(CryptoCoreError::AuthenticationFailed(_))));
+
+    Ok(())
+}
+
+#[test]
+fn test_streaming_gcm_encrypts_as_it_goes() -> Result<(), Box<dyn std::error::Error>> {
+    let key = hex::decode(KEY)?;
+    let iv = iv_for("gcm");
+    let plaintext = [0x42u8; 100];
+    let expected = create_cipher("aes", "gcm", &key, iv.as_deref())?.encrypt(&plaintext)?;
+
+    // Whole blocks come out straight away; the rest and the tag at the end
+    let mut encryptor = StreamEncryptor::new("aes", "gcm", &key, iv.as_deref())?;
+    let first = encryptor.update(&plaintext[..40])?;
+    assert_eq!(first, expected[..32]);
+    let second = encryptor.update(&plaintext[40..])?;
+    assert_eq!(second, expected[32..96]);
+    assert_eq!(encryptor.finish()?, expected[96..]);
+
+    Ok(())
+}
+
+#[test]
+fn test_gcm_refuses_to_wrap_its_counter() -> Result<(), Box<dyn std::error::Error>> {
+    let key = hex::decode(KEY)?;
+    let iv = iv_for("gcm").unwrap();
+
+    // Counter 2^32 - 1 is the last usable block; the next would be J0 again
+    let mut encryptor = GcmEncryptor::new(GcmMode::new(&key, &iv)?, &[]);
+    encryptor.set_counter(u32::MAX - 1);
+    assert_eq!(encryptor.update(&[0u8; 32])?.len(), 32);
+    let error = encryptor.update(&[0u8; 16]);
+    assert!(matches!(&error, Err(CryptoCoreError::InvalidArgument(message)) if message.contains("stream")));
+    assert!(encryptor.finish(&[0u8; 1]).is_err());
+
+    let mut encryptor = GcmEncryptor::new(GcmMode::new(&key, &iv)?, &[]);
+    encryptor.set_counter(u32::MAX);
+    assert!(encryptor.update(&[0u8; 32]).is_err());
+    assert_eq!(encryptor.finish(&[0u8; 16])?.len(), 32);
+    Ok(())
+}